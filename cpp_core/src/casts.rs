@@ -1,6 +1,7 @@
-//! `static_cast` and `dynamic_cast`.
+//! `static_cast` and `dynamic_cast`, including cross-casts.
 
 use crate::{MutPtr, Ptr};
+use std::ffi::c_void;
 
 /// Converts a class pointer to a base class pointer.
 ///
@@ -122,3 +123,67 @@ pub trait DynamicCast<T>: Sized {
     /// This operation is safe as long as `ptr` is either valid or null.
     unsafe fn dynamic_cast_mut(ptr: MutPtr<Self>) -> MutPtr<T>;
 }
+
+/// Converts a class pointer to a pointer to another base class of the object.
+///
+/// A null pointer is always converted to a null pointer.
+/// If the object can't be converted to the requested type, a null pointer is returned.
+///
+/// It's recommended to perform the conversion by calling `cross_cast` and
+/// `cross_cast_mut` methods on pointer types (`CppBox`, `Ptr`, `MutPtr`, `Ref`, `MutRef`)
+/// instead of importing the trait directly.
+///
+/// Provides access to C++ `dynamic_cast` conversion between two polymorphic classes
+/// that are not derived from each other but have a common derived class.
+/// For example, if `T3` class is derived from both `T1` and `T2`, a pointer to `T1`
+/// can be converted to `T2` if the object's type is `T3` or inherits `T3`.
+///
+/// `CrossCast<T2>` is implemented for `T1` and `CrossCast<T1>` is implemented for `T2`.
+/// The implementation is generated by `ritual` automatically.
+pub trait CrossCast<T>: Sized {
+    /// Convert type of a const pointer.
+    ///
+    /// Returns a null pointer if the object doesn't have the requested type.
+    ///
+    /// ### Safety
+    ///
+    /// This operation is safe as long as `ptr` is either valid or null.
+    unsafe fn cross_cast(ptr: Ptr<Self>) -> Ptr<T>;
+    /// Convert type of a mutable pointer.
+    ///
+    /// Returns a null pointer if the object doesn't have the requested type.
+    ///
+    /// ### Safety
+    ///
+    /// This operation is safe as long as `ptr` is either valid or null.
+    unsafe fn cross_cast_mut(ptr: MutPtr<Self>) -> MutPtr<T>;
+}
+
+/// Returns the address of the most derived object pointed to by a class pointer.
+///
+/// A null pointer is always converted to a null pointer.
+///
+/// It's recommended to perform the conversion by calling `most_derived` and
+/// `most_derived_mut` methods on pointer types (`CppBox`, `Ptr`, `MutPtr`, `Ref`, `MutRef`)
+/// instead of importing the trait directly.
+///
+/// Provides access to C++ `dynamic_cast<void*>` conversion. Pointers to different
+/// base classes of the same object are converted to the same address, so the result
+/// can be used to check whether two pointers refer to the same object.
+///
+/// `MostDerived` is implemented for all polymorphic classes.
+/// The implementation is generated by `ritual` automatically.
+pub trait MostDerived: Sized {
+    /// Convert type of a const pointer.
+    ///
+    /// ### Safety
+    ///
+    /// This operation is safe as long as `ptr` is either valid or null.
+    unsafe fn most_derived(ptr: Ptr<Self>) -> Ptr<c_void>;
+    /// Convert type of a mutable pointer.
+    ///
+    /// ### Safety
+    ///
+    /// This operation is safe as long as `ptr` is either valid or null.
+    unsafe fn most_derived_mut(ptr: MutPtr<Self>) -> MutPtr<c_void>;
+}
@@ -212,3 +212,31 @@ where
         Self::cast_from(MutPtr::from_raw(value))
     }
 }
+
+/// Creates a new C++ object from a value of type `T`. This trait is implemented for
+/// non-explicit single-argument constructors (e.g. `QString(const char*)`)
+/// that allow implicit conversions in C++.
+///
+/// `CastFrom<T>` is also implemented for `CppBox<Self>`, so you can convert the value
+/// using `cast_into()`.
+pub trait NewFrom<T>: CppDeletable {
+    /// Creates the object.
+    ///
+    /// ### Safety
+    ///
+    /// This operation is safe as long as `value` is valid.
+    unsafe fn new_from(value: T) -> CppBox<Self>;
+}
+
+/// Enables `CastFrom<T>` for `CppBox<Self>` based on the `NewFrom<T>` implementation.
+///
+/// This trait is implemented for converting constructors with argument types declared
+/// in other crates (e.g. `QString(const char*)`). The orphan rules don't allow
+/// to implement `CastFrom<T>` for `CppBox<Self>` directly in this case.
+pub trait ImplicitNewFrom<T>: NewFrom<T> {}
+
+impl<T, U: ImplicitNewFrom<T>> CastFrom<T> for CppBox<U> {
+    unsafe fn cast_from(value: T) -> Self {
+        U::new_from(value)
+    }
+}
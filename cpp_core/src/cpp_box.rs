@@ -1,4 +1,7 @@
-use crate::{DynamicCast, MutPtr, MutRef, Ptr, Ref, StaticDowncast, StaticUpcast};
+use crate::{
+    CrossCast, DynamicCast, MostDerived, MutPtr, MutRef, Ptr, Ref, StaticDowncast, StaticUpcast,
+};
+use std::ffi::c_void;
 use std::ops::{Deref, DerefMut};
 use std::{fmt, mem, ptr};
 
@@ -34,7 +37,9 @@ pub trait CppDeletable: Sized {
 ///
 /// `CppBox` implements `Deref` and `DerefMut`, allowing to call the object's methods
 /// directly. In addition, methods of the object's first base class are also directly available
-/// thanks to nested `Deref` implementations.
+/// thanks to nested `Deref` implementations. Methods of other base classes are available
+/// as forwarding methods of the derived class, unless they are hidden by methods
+/// with the same name.
 ///
 /// If the object provides an iterator interface through `begin()` and `end()` functions,
 /// `&CppBox<T>` and `&mut CppBox<T>` will implement `IntoIterator`,
@@ -211,6 +216,34 @@ impl<T: CppDeletable> CppBox<T> {
         DynamicCast::dynamic_cast(self.as_ptr()).as_ref()
     }
 
+    /// Returns a non-owning reference to the content converted to the type `U` that is
+    /// a sibling base class of the object's type.
+    /// `CppBox` retains the ownership of the object. Returns `None` if the object's type is not `U`
+    /// and doesn't inherit `U`.
+    ///
+    /// ### Safety
+    ///
+    /// This operation is safe as long as `self` is valid.
+    pub unsafe fn cross_cast<U>(&self) -> Option<Ref<U>>
+    where
+        T: CrossCast<U>,
+    {
+        CrossCast::cross_cast(self.as_ptr()).as_ref()
+    }
+
+    /// Returns the address of the most derived object. Pointers to different base classes
+    /// of the same object have the same most derived address.
+    ///
+    /// ### Safety
+    ///
+    /// This operation is safe as long as `self` is valid.
+    pub unsafe fn most_derived(&self) -> Ptr<c_void>
+    where
+        T: MostDerived,
+    {
+        MostDerived::most_derived(self.as_ptr())
+    }
+
     /// Returns a non-owning reference to the content converted to the base class type `U`.
     /// `CppBox` retains the ownership of the object.
     ///
@@ -256,6 +289,34 @@ impl<T: CppDeletable> CppBox<T> {
     {
         DynamicCast::dynamic_cast_mut(self.as_mut_ptr()).as_mut_ref()
     }
+
+    /// Returns a non-owning reference to the content converted to the type `U` that is
+    /// a sibling base class of the object's type.
+    /// `CppBox` retains the ownership of the object. Returns `None` if the object's type is not `U`
+    /// and doesn't inherit `U`.
+    ///
+    /// ### Safety
+    ///
+    /// This operation is safe as long as `self` is valid.
+    pub unsafe fn cross_cast_mut<U>(&mut self) -> Option<MutRef<U>>
+    where
+        T: CrossCast<U>,
+    {
+        CrossCast::cross_cast_mut(self.as_mut_ptr()).as_mut_ref()
+    }
+
+    /// Returns the address of the most derived object. Pointers to different base classes
+    /// of the same object have the same most derived address.
+    ///
+    /// ### Safety
+    ///
+    /// This operation is safe as long as `self` is valid.
+    pub unsafe fn most_derived_mut(&mut self) -> MutPtr<c_void>
+    where
+        T: MostDerived,
+    {
+        MostDerived::most_derived_mut(self.as_mut_ptr())
+    }
 }
 
 /// Allows to call member functions of `T` and its base classes directly on the pointer.
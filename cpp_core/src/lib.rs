@@ -22,10 +22,14 @@
 //!
 //! Pointer types implement operator traits and delegate them to the corresponding C++ operators.
 //! This means that you can use `ptr1 + ptr2` to access the object's `operator+`.
+//! C++'s `operator[]` and `operator()` are available through `ops::Index`, `ops::IndexMut`,
+//! `ops::Call` and `ops::CallMut` traits.
 //!
 //! Pointer types implement `Deref` and `DerefMut`, allowing to call the object's methods
 //! directly. In addition, methods of the object's first base class are also directly available
-//! thanks to nested `Deref` implementations.
+//! thanks to nested `Deref` implementations. Methods of other base classes are available
+//! as forwarding methods of the derived class, unless they are hidden by methods
+//! with the same name.
 //!
 //! If the object provides an iterator interface through `begin()` and `end()` functions,
 //! pointer types will implement `IntoIterator`, so you can iterate on them directly.
@@ -40,9 +44,13 @@
 //! (backed by C++'s `dynamic_cast`).
 //! - `StaticDowncast` converts from a base class to a derived class without a runtime
 //! check (also backed by C++'s `static_cast`).
+//! - `CrossCast` performs a checked conversion between two base classes of the object
+//! that are not derived from each other (backed by C++'s `dynamic_cast`).
+//! - `MostDerived` returns the address of the most derived object
+//! (backed by C++'s `dynamic_cast<void*>`).
 //!
 //! Instead of using these traits directly, it's more convenient to use `static_upcast`,
-//! `static_downcast`, `dynamic_cast` helpers on pointer types.
+//! `static_downcast`, `dynamic_cast`, `cross_cast`, `most_derived` helpers on pointer types.
 //!
 //! The `CastFrom` and `CastInto` traits represent some of the implicit coercions
 //! available in C++. For example, if a method accepts `impl CastInto<Ptr<SomeClass>>`,
@@ -50,13 +58,22 @@
 //! or even `Ptr<DerivedClass>` (where `DerivedClass` inherits `SomeClass`). You can also
 //! pass a null pointer object (`NullPtr`) if you don't have a value
 //! (`Ptr::null()` is also an option but it can cause type inference issues).
+//!
+//! `NewFrom` is implemented for C++ classes that have implicit converting constructors.
+//! For example, if `QString` has a `QString(const char*)` constructor, `CppBox<QString>`
+//! can be created with `NewFrom::new_from(text)` or `text.cast_into()`.
+//!
+//! C++ conversion operators are represented by `From` implementations. For example,
+//! if `QVariant` has `operator bool() const`, the value can be converted using
+//! `bool::from(&*variant)`.
 
 //#![deny(missing_docs)]
 
-pub use crate::casts::{DynamicCast, StaticDowncast, StaticUpcast};
-pub use crate::convert::{CastFrom, CastInto};
+pub use crate::casts::{CrossCast, DynamicCast, MostDerived, StaticDowncast, StaticUpcast};
+pub use crate::convert::{CastFrom, CastInto, ImplicitNewFrom, NewFrom};
 pub use crate::cpp_box::{CppBox, CppDeletable};
 pub use crate::iterator::{cpp_iter, CppIterator, SliceAsBeginEnd};
+pub use crate::numeric::{Float16, Int128, LongDouble, UInt128};
 pub use crate::ptr::{MutPtr, NullPtr, Ptr};
 pub use crate::ref_::{MutRef, Ref};
 pub use libc::wchar_t;
@@ -66,15 +83,20 @@ pub mod cmp;
 mod convert;
 mod cpp_box;
 mod iterator;
+mod numeric;
 pub mod ops;
 mod ops_impls;
 mod ptr;
 mod ref_;
 pub mod vector_ops;
 
-// C++ doesn't guarantee these types to be exactly u16 and u32,
+// C++ doesn't guarantee these types to be exactly u8, u16 and u32,
 // but they are on all supported platforms.
 
+/// Type for UTF-8 character representation, required to be large enough to represent
+/// any UTF-8 code unit (8 bits). Same as C++'s `char8_t` type.
+#[allow(non_camel_case_types)]
+pub type char8_t = u8;
 /// Type for UTF-16 character representation, required to be large enough to represent
 /// any UTF-16 code unit (16 bits). Same as C++'s `char16_6` type.
 #[allow(non_camel_case_types)]
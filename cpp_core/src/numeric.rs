@@ -0,0 +1,130 @@
+//! Opaque representations of C++ numeric types that have no Rust equivalent.
+
+use crate::{CastFrom, Ref};
+use std::fmt;
+
+/// Storage for a value of C++'s `long double` type.
+///
+/// Size and format of `long double` depend on the platform, and Rust doesn't have
+/// a corresponding type, so C++ functions accepting or returning `long double` by value
+/// are wrapped as functions accepting a reference to `LongDouble` or returning a `LongDouble`.
+/// The storage is large enough to hold `long double` on all supported platforms,
+/// but it may be larger than the C++ type, so pointers to `LongDouble` must not be
+/// used to access C++ arrays.
+///
+/// The value can't be inspected directly. Use C++ functions to convert it to other types.
+#[repr(C, align(16))]
+#[derive(Clone, Copy, Default)]
+pub struct LongDouble {
+    data: [u8; 16],
+}
+
+impl fmt::Debug for LongDouble {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LongDouble").finish()
+    }
+}
+
+impl<'a> CastFrom<&'a LongDouble> for Ref<LongDouble> {
+    unsafe fn cast_from(value: &'a LongDouble) -> Self {
+        Ref::from_raw_ref(value)
+    }
+}
+
+/// Storage for a value of C++'s `_Float16` type (IEEE 754 half-precision float).
+///
+/// Rust doesn't have a corresponding type, so C++ functions accepting or returning
+/// `_Float16` by value are wrapped as functions accepting a reference to `Float16`
+/// or returning a `Float16`.
+#[repr(transparent)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Float16(u16);
+
+impl Float16 {
+    /// Creates a value from its binary representation.
+    pub fn from_bits(bits: u16) -> Self {
+        Float16(bits)
+    }
+
+    /// Returns binary representation of the value.
+    pub fn to_bits(self) -> u16 {
+        self.0
+    }
+}
+
+impl fmt::Debug for Float16 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Float16({:#06x})", self.0)
+    }
+}
+
+impl<'a> CastFrom<&'a Float16> for Ref<Float16> {
+    unsafe fn cast_from(value: &'a Float16) -> Self {
+        Ref::from_raw_ref(value)
+    }
+}
+
+/// Storage for a value of C++'s `__int128` type.
+///
+/// Passing `i128` by value through `extern "C"` functions is not portable,
+/// and alignment of `i128` may differ from alignment of `__int128`,
+/// so C++ functions accepting or returning `__int128` by value are wrapped
+/// as functions accepting a reference to `Int128` or returning an `Int128`.
+#[repr(C, align(16))]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Int128(i128);
+
+impl Int128 {
+    /// Creates a storage containing `value`.
+    pub fn new(value: i128) -> Self {
+        Int128(value)
+    }
+
+    /// Returns the stored value.
+    pub fn get(self) -> i128 {
+        self.0
+    }
+}
+
+impl fmt::Debug for Int128 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Int128({})", self.0)
+    }
+}
+
+impl<'a> CastFrom<&'a Int128> for Ref<Int128> {
+    unsafe fn cast_from(value: &'a Int128) -> Self {
+        Ref::from_raw_ref(value)
+    }
+}
+
+/// Storage for a value of C++'s `unsigned __int128` type.
+///
+/// See `Int128` for details.
+#[repr(C, align(16))]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct UInt128(u128);
+
+impl UInt128 {
+    /// Creates a storage containing `value`.
+    pub fn new(value: u128) -> Self {
+        UInt128(value)
+    }
+
+    /// Returns the stored value.
+    pub fn get(self) -> u128 {
+        self.0
+    }
+}
+
+impl fmt::Debug for UInt128 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "UInt128({})", self.0)
+    }
+}
+
+impl<'a> CastFrom<&'a UInt128> for Ref<UInt128> {
+    unsafe fn cast_from(value: &'a UInt128) -> Self {
+        Ref::from_raw_ref(value)
+    }
+}
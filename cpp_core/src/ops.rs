@@ -66,3 +66,49 @@ pub trait EndMut {
     /// Returns a C++ mutable iterator object pointing to the end of the collection.
     unsafe fn end_mut(&mut self) -> Self::Output;
 }
+
+/// Represents C++'s `operator[]` of a const object (`a[index]`).
+///
+/// If the C++ operator returns a reference, `Output` is a `Ref` to the element.
+pub trait Index<Idx> {
+    /// Output type.
+    type Output;
+
+    /// Returns the element of `self` at `index`.
+    unsafe fn index(&self, index: Idx) -> Self::Output;
+}
+
+/// Represents C++'s non-const `operator[]` (`a[index]`).
+///
+/// If the C++ operator returns a reference, `Output` is a `MutRef` to the element.
+pub trait IndexMut<Idx> {
+    /// Output type.
+    type Output;
+
+    /// Returns the element of `self` at `index`.
+    unsafe fn index_mut(&mut self, index: Idx) -> Self::Output;
+}
+
+/// Represents C++'s const function call operator (`a(arg1, arg2)`).
+///
+/// Arguments are passed as a tuple, so `Args` is `()` for an operator without arguments
+/// and `(A,)` for an operator with one argument.
+pub trait Call<Args> {
+    /// Output type.
+    type Output;
+
+    /// Calls `self` with `args`.
+    unsafe fn call(&self, args: Args) -> Self::Output;
+}
+
+/// Represents C++'s non-const function call operator (`a(arg1, arg2)`).
+///
+/// Arguments are passed as a tuple, so `Args` is `()` for an operator without arguments
+/// and `(A,)` for an operator with one argument.
+pub trait CallMut<Args> {
+    /// Output type.
+    type Output;
+
+    /// Calls `self` with `args`.
+    unsafe fn call_mut(&mut self, args: Args) -> Self::Output;
+}
@@ -1,5 +1,6 @@
 use crate::{
     cmp::{Ge, Gt, Le, Lt},
+    ops::{Call, CallMut, Index, IndexMut},
     CppBox, CppDeletable, MutPtr, MutRef, Ptr, Ref,
 };
 use std::cmp::{Ordering, PartialEq, PartialOrd};
@@ -216,3 +217,91 @@ where
         unsafe { (**self).ge(other) }
     }
 }
+
+macro_rules! define_const_method_op {
+    ($trait1:ident, $func:ident, $container:ident) => {
+        impl<T, U> $trait1<U> for $container<T>
+        where
+            T: $trait1<U>,
+        {
+            type Output = <T as $trait1<U>>::Output;
+
+            unsafe fn $func(&self, arg: U) -> Self::Output {
+                $trait1::$func(&*self.as_raw_ptr(), arg)
+            }
+        }
+    };
+}
+
+define_const_method_op!(Index, index, Ptr);
+define_const_method_op!(Index, index, MutPtr);
+define_const_method_op!(Index, index, Ref);
+define_const_method_op!(Index, index, MutRef);
+define_const_method_op!(Call, call, Ptr);
+define_const_method_op!(Call, call, MutPtr);
+define_const_method_op!(Call, call, Ref);
+define_const_method_op!(Call, call, MutRef);
+
+macro_rules! define_mut_method_op {
+    ($trait1:ident, $func:ident, $container:ident) => {
+        impl<T, U> $trait1<U> for $container<T>
+        where
+            T: $trait1<U>,
+        {
+            type Output = <T as $trait1<U>>::Output;
+
+            unsafe fn $func(&mut self, arg: U) -> Self::Output {
+                $trait1::$func(&mut *self.as_mut_raw_ptr(), arg)
+            }
+        }
+    };
+}
+
+define_mut_method_op!(IndexMut, index_mut, MutPtr);
+define_mut_method_op!(IndexMut, index_mut, MutRef);
+define_mut_method_op!(CallMut, call_mut, MutPtr);
+define_mut_method_op!(CallMut, call_mut, MutRef);
+
+impl<T: CppDeletable, U> Index<U> for CppBox<T>
+where
+    T: Index<U>,
+{
+    type Output = <T as Index<U>>::Output;
+
+    unsafe fn index(&self, index: U) -> Self::Output {
+        (**self).index(index)
+    }
+}
+
+impl<T: CppDeletable, U> IndexMut<U> for CppBox<T>
+where
+    T: IndexMut<U>,
+{
+    type Output = <T as IndexMut<U>>::Output;
+
+    unsafe fn index_mut(&mut self, index: U) -> Self::Output {
+        (**self).index_mut(index)
+    }
+}
+
+impl<T: CppDeletable, U> Call<U> for CppBox<T>
+where
+    T: Call<U>,
+{
+    type Output = <T as Call<U>>::Output;
+
+    unsafe fn call(&self, args: U) -> Self::Output {
+        Call::call(&**self, args)
+    }
+}
+
+impl<T: CppDeletable, U> CallMut<U> for CppBox<T>
+where
+    T: CallMut<U>,
+{
+    type Output = <T as CallMut<U>>::Output;
+
+    unsafe fn call_mut(&mut self, args: U) -> Self::Output {
+        CallMut::call_mut(&mut **self, args)
+    }
+}
@@ -1,5 +1,8 @@
-use crate::{CppBox, CppDeletable, DynamicCast, MutRef, Ref, StaticDowncast, StaticUpcast};
-use std::ffi::CStr;
+use crate::{
+    CppBox, CppDeletable, CrossCast, DynamicCast, MostDerived, MutRef, Ref, StaticDowncast,
+    StaticUpcast,
+};
+use std::ffi::{c_void, CStr};
 use std::fmt;
 use std::ops::{Deref, DerefMut};
 use std::os::raw::c_char;
@@ -19,7 +22,9 @@ use std::os::raw::c_char;
 ///
 /// `MutPtr` implements `Deref` and `DerefMut`, allowing to call the object's methods
 /// directly. In addition, methods of the object's first base class are also directly available
-/// thanks to nested `Deref` implementations.
+/// thanks to nested `Deref` implementations. Methods of other base classes are available
+/// as forwarding methods of the derived class, unless they are hidden by methods
+/// with the same name.
 ///
 /// `MutPtr` can contain a null pointer. `Deref` will panic if attempted to dereference
 /// a null pointer.
@@ -159,6 +164,33 @@ impl<T> MutPtr<T> {
         DynamicCast::dynamic_cast(self.as_ptr())
     }
 
+    /// Converts the pointer to the type `U` that is a sibling base class
+    /// of the object's type. Returns a null pointer if the object's type
+    /// is not `U` and doesn't inherit `U`.
+    ///
+    /// ### Safety
+    ///
+    /// This operation is safe as long as `self` is valid or null.
+    pub unsafe fn cross_cast<U>(self) -> Ptr<U>
+    where
+        T: CrossCast<U>,
+    {
+        CrossCast::cross_cast(self.as_ptr())
+    }
+
+    /// Returns the address of the most derived object. Pointers to different base classes
+    /// of the same object have the same most derived address.
+    ///
+    /// ### Safety
+    ///
+    /// This operation is safe as long as `self` is valid or null.
+    pub unsafe fn most_derived(self) -> Ptr<c_void>
+    where
+        T: MostDerived,
+    {
+        MostDerived::most_derived(self.as_ptr())
+    }
+
     /// Converts the pointer to the base class type `U`.
     ///
     /// ### Safety
@@ -198,6 +230,33 @@ impl<T> MutPtr<T> {
     {
         DynamicCast::dynamic_cast_mut(self)
     }
+
+    /// Converts the pointer to the type `U` that is a sibling base class
+    /// of the object's type. Returns a null pointer if the object's type
+    /// is not `U` and doesn't inherit `U`.
+    ///
+    /// ### Safety
+    ///
+    /// This operation is safe as long as `self` is valid or null.
+    pub unsafe fn cross_cast_mut<U>(self) -> MutPtr<U>
+    where
+        T: CrossCast<U>,
+    {
+        CrossCast::cross_cast_mut(self)
+    }
+
+    /// Returns the address of the most derived object. Pointers to different base classes
+    /// of the same object have the same most derived address.
+    ///
+    /// ### Safety
+    ///
+    /// This operation is safe as long as `self` is valid or null.
+    pub unsafe fn most_derived_mut(self) -> MutPtr<c_void>
+    where
+        T: MostDerived,
+    {
+        MostDerived::most_derived_mut(self)
+    }
 }
 
 impl<T: CppDeletable> MutPtr<T> {
@@ -275,7 +334,9 @@ impl MutPtr<c_char> {
 ///
 /// `Ptr` implements `Deref`, allowing to call the object's methods
 /// directly. In addition, methods of the object's first base class are also directly available
-/// thanks to nested `Deref` implementations.
+/// thanks to nested `Deref` implementations. Methods of other base classes are available
+/// as forwarding methods of the derived class, unless they are hidden by methods
+/// with the same name.
 ///
 /// `Ptr` can contain a null pointer. `Deref` will panic if attempted to dereference
 /// a null pointer.
@@ -391,6 +452,33 @@ impl<T> Ptr<T> {
     {
         DynamicCast::dynamic_cast(self)
     }
+
+    /// Converts the pointer to the type `U` that is a sibling base class
+    /// of the object's type. Returns a null pointer if the object's type
+    /// is not `U` and doesn't inherit `U`.
+    ///
+    /// ### Safety
+    ///
+    /// This operation is safe as long as `self` is valid or null.
+    pub unsafe fn cross_cast<U>(self) -> Ptr<U>
+    where
+        T: CrossCast<U>,
+    {
+        CrossCast::cross_cast(self)
+    }
+
+    /// Returns the address of the most derived object. Pointers to different base classes
+    /// of the same object have the same most derived address.
+    ///
+    /// ### Safety
+    ///
+    /// This operation is safe as long as `self` is valid or null.
+    pub unsafe fn most_derived(self) -> Ptr<c_void>
+    where
+        T: MostDerived,
+    {
+        MostDerived::most_derived(self)
+    }
 }
 
 /// Allows to call member functions of `T` and its base classes directly on the pointer.
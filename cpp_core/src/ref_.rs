@@ -1,4 +1,5 @@
-use crate::{DynamicCast, MutPtr, Ptr, StaticDowncast, StaticUpcast};
+use crate::{CrossCast, DynamicCast, MostDerived, MutPtr, Ptr, StaticDowncast, StaticUpcast};
+use std::ffi::c_void;
 use std::ops::{Deref, DerefMut};
 use std::{fmt, ptr};
 
@@ -155,6 +156,33 @@ impl<T> MutRef<T> {
         DynamicCast::dynamic_cast(self.as_ptr()).as_ref()
     }
 
+    /// Converts the pointer to the type `U` that is a sibling base class
+    /// of the object's type. Returns `None` if the object's type
+    /// is not `U` and doesn't inherit `U`.
+    ///
+    /// ### Safety
+    ///
+    /// This operation is safe as long as `self` is valid.
+    pub unsafe fn cross_cast<U>(self) -> Option<Ref<U>>
+    where
+        T: CrossCast<U>,
+    {
+        CrossCast::cross_cast(self.as_ptr()).as_ref()
+    }
+
+    /// Returns the address of the most derived object. Pointers to different base classes
+    /// of the same object have the same most derived address.
+    ///
+    /// ### Safety
+    ///
+    /// This operation is safe as long as `self` is valid.
+    pub unsafe fn most_derived(self) -> Ptr<c_void>
+    where
+        T: MostDerived,
+    {
+        MostDerived::most_derived(self.as_ptr())
+    }
+
     /// Converts the pointer to the base class type `U`.
     ///
     /// ### Safety
@@ -197,6 +225,33 @@ impl<T> MutRef<T> {
     {
         DynamicCast::dynamic_cast_mut(self.as_mut_ptr()).as_mut_ref()
     }
+
+    /// Converts the pointer to the type `U` that is a sibling base class
+    /// of the object's type. Returns `None` if the object's type
+    /// is not `U` and doesn't inherit `U`.
+    ///
+    /// ### Safety
+    ///
+    /// This operation is safe as long as `self` is valid.
+    pub unsafe fn cross_cast_mut<U>(self) -> Option<MutRef<U>>
+    where
+        T: CrossCast<U>,
+    {
+        CrossCast::cross_cast_mut(self.as_mut_ptr()).as_mut_ref()
+    }
+
+    /// Returns the address of the most derived object. Pointers to different base classes
+    /// of the same object have the same most derived address.
+    ///
+    /// ### Safety
+    ///
+    /// This operation is safe as long as `self` is valid.
+    pub unsafe fn most_derived_mut(self) -> MutPtr<c_void>
+    where
+        T: MostDerived,
+    {
+        MostDerived::most_derived_mut(self.as_mut_ptr())
+    }
 }
 
 /// Allows to call member functions of `T` and its base classes directly on the pointer.
@@ -353,6 +408,33 @@ impl<T> Ref<T> {
     {
         DynamicCast::dynamic_cast(self.as_ptr()).as_ref()
     }
+
+    /// Converts the pointer to the type `U` that is a sibling base class
+    /// of the object's type. Returns `None` if the object's type
+    /// is not `U` and doesn't inherit `U`.
+    ///
+    /// ### Safety
+    ///
+    /// This operation is safe as long as `self` is valid.
+    pub unsafe fn cross_cast<U>(self) -> Option<Ref<U>>
+    where
+        T: CrossCast<U>,
+    {
+        CrossCast::cross_cast(self.as_ptr()).as_ref()
+    }
+
+    /// Returns the address of the most derived object. Pointers to different base classes
+    /// of the same object have the same most derived address.
+    ///
+    /// ### Safety
+    ///
+    /// This operation is safe as long as `self` is valid.
+    pub unsafe fn most_derived(self) -> Ptr<c_void>
+    where
+        T: MostDerived,
+    {
+        MostDerived::most_derived(self.as_ptr())
+    }
 }
 
 /// Allows to call member functions of `T` and its base classes directly on the pointer.
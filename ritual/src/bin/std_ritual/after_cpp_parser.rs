@@ -2,7 +2,7 @@ use ritual::cpp_data::{
     CppItem, CppPath, CppPathItem, CppTypeDeclaration, CppTypeDeclarationKind, CppVisibility,
 };
 use ritual::cpp_function::{
-    CppFunction, CppFunctionArgument, CppFunctionKind, CppFunctionMemberData,
+    CppFunction, CppFunctionArgument, CppFunctionKind, CppFunctionMemberData, CppRefQualifier,
 };
 use ritual::cpp_parser::CppParserOutput;
 use ritual::cpp_type::{
@@ -47,6 +47,10 @@ fn add_vector_from_pointers_functions(data: &mut ProcessorData<'_>) -> Result<()
                 visibility: CppVisibility::Public,
                 is_signal: false,
                 is_slot: false,
+                ref_qualifier: CppRefQualifier::None,
+                is_defaulted: false,
+                is_explicit: false,
+                inherited_from: None,
             }),
             operator: None,
             return_type: CppType::Void,
@@ -69,6 +73,9 @@ fn add_vector_from_pointers_functions(data: &mut ProcessorData<'_>) -> Result<()
             ],
             allows_variadic_arguments: false,
             cast: None,
+            is_noexcept: false,
+            is_deleted: false,
+            is_friend: false,
             declaration_code: None,
         }),
     )?;
@@ -97,6 +104,10 @@ fn add_vector_from_pointers_functions(data: &mut ProcessorData<'_>) -> Result<()
                 visibility: CppVisibility::Public,
                 is_signal: false,
                 is_slot: false,
+                ref_qualifier: CppRefQualifier::None,
+                is_defaulted: false,
+                is_explicit: false,
+                inherited_from: None,
             }),
             operator: None,
             return_type: CppType::Void,
@@ -114,6 +125,9 @@ fn add_vector_from_pointers_functions(data: &mut ProcessorData<'_>) -> Result<()
             ],
             allows_variadic_arguments: false,
             cast: None,
+            is_noexcept: false,
+            is_deleted: false,
+            is_friend: false,
             declaration_code: None,
         }),
     )?;
@@ -199,6 +213,7 @@ fn add_vector_instantiations(data: &mut ProcessorData<'_>) -> Result<()> {
                     name: "vector".into(),
                     template_arguments: Some(vec![arg.clone(), allocator_type]),
                 }),
+                special_members: None,
             }),
         )?;
     }
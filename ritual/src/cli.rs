@@ -3,7 +3,7 @@
 //! See [README](https://github.com/rust-qt/ritual)
 //! for more information.
 
-use crate::config::GlobalConfig;
+use crate::config::{GlobalConfig, TemplateInstantiation};
 use crate::database::ItemId;
 use crate::processor;
 use crate::workspace::Workspace;
@@ -35,6 +35,10 @@ pub struct Options {
     #[structopt(long = "cluster")]
     /// Cluster configuration
     pub cluster: Option<PathBuf>,
+    #[structopt(long = "template-instantiations", parse(from_os_str))]
+    /// JSON file with additional template instantiations
+    /// (e.g. `[{"template": "std::vector", "arguments": ["std::string"]}]`)
+    pub template_instantiations: Option<PathBuf>,
     #[structopt(long = "trace")]
     /// ID of item to trace
     pub trace: Option<String>,
@@ -114,6 +118,11 @@ pub fn run(options: Options, mut config: GlobalConfig) -> Result<()> {
             config.set_cluster_config(load_json(cluster_config_path)?);
         }
 
+        if let Some(path) = &options.template_instantiations {
+            let instantiations: Vec<TemplateInstantiation> = load_json(path)?;
+            config.add_template_instantiations(instantiations);
+        }
+
         if let Some(local_paths) = options.local_paths {
             config.set_write_dependencies_local_paths(local_paths);
         }
@@ -2,7 +2,9 @@
 
 use crate::cpp_checker::PreliminaryTest;
 use crate::cpp_data::{CppItem, CppPath};
+use crate::cpp_function::CppFunction;
 use crate::cpp_parser::CppParserOutput;
+use crate::cpp_type::{CppSpecificNumericType, CppSpecificNumericTypeKind, CppType};
 use crate::processor::{ProcessingSteps, ProcessorData};
 use crate::rust_info::{NameType, RustPathScope};
 use crate::rust_type::RustPath;
@@ -11,6 +13,7 @@ use ritual_common::errors::{bail, Result};
 use ritual_common::target::Target;
 use ritual_common::toml;
 use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -176,6 +179,8 @@ pub type RustPathHook =
 pub type AfterCppParserHook =
     dyn Fn(&mut ProcessorData<'_>, &CppParserOutput) -> Result<()> + 'static;
 pub type FfiGeneratorHook = dyn Fn(&CppItem) -> Result<bool> + 'static;
+pub type DefaultArgumentsModeHook =
+    dyn Fn(&CppFunction) -> Result<Option<DefaultArgumentsMode>> + 'static;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkerLibraryConfig {
@@ -210,9 +215,12 @@ pub struct Config {
     cpp_build_config: CppBuildConfig,
     cpp_build_paths: CppBuildPaths,
     cpp_parser_arguments: Vec<String>,
+    is_c_library: bool,
     processing_steps: ProcessingSteps,
     movable_types_hook: Option<Box<dyn Fn(&CppPath) -> Result<MovableTypesHookOutput>>>,
+    detect_movable_types: bool,
     cpp_parser_path_hook: Option<Box<dyn Fn(&CppPath) -> Result<bool>>>,
+    cpp_parser_hooks_version: u32,
     rust_path_scope_hook: Option<Box<RustPathScopeHook>>,
     rust_path_hook: Option<Box<RustPathHook>>,
     after_cpp_parser_hooks: Vec<Box<AfterCppParserHook>>,
@@ -220,6 +228,63 @@ pub struct Config {
     cluster_config: Option<ClusterConfig>,
     cpp_checker_tests: Vec<PreliminaryTest>,
     write_dependencies_local_paths: bool,
+    special_typedefs: HashMap<String, CppType>,
+    template_instantiations: Vec<TemplateInstantiation>,
+    default_arguments_mode: DefaultArgumentsMode,
+    default_arguments_mode_hook: Option<Box<DefaultArgumentsModeHook>>,
+    overridable_classes: Vec<CppPath>,
+    member_pointer_getters: Vec<CppPath>,
+}
+
+/// An instantiation of a class template requested by the user.
+///
+/// Template arguments are specified as C++ code and are resolved by the C++ parser,
+/// so they may contain typedefs (e.g. `std::string`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TemplateInstantiation {
+    /// Full name of the class template (e.g. `std::vector`).
+    pub template: String,
+    /// C++ code of the template arguments (e.g. `["std::string"]`).
+    pub arguments: Vec<String>,
+}
+
+impl TemplateInstantiation {
+    pub fn new<S: Into<String>>(template: S, arguments: Vec<String>) -> Self {
+        TemplateInstantiation {
+            template: template.into(),
+            arguments,
+        }
+    }
+
+    /// Returns C++ code of the instantiated type.
+    pub fn to_cpp_code(&self) -> String {
+        let arguments = self.arguments.join(", ");
+        // `> >` avoids parsing `>>` as an operator in old C++ standards
+        if arguments.ends_with('>') {
+            format!("{}<{} >", self.template, arguments)
+        } else {
+            format!("{}<{}>", self.template, arguments)
+        }
+    }
+}
+
+/// Determines how C++ functions with default argument values are exposed in Rust.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefaultArgumentsMode {
+    /// Generate a separate function for each number of omitted trailing arguments
+    /// (e.g. `new_3a`, `new_2a` and `new_1a`).
+    Overloads,
+    /// Generate a single function that accepts arguments with default values
+    /// in an argument struct with `Option` fields. Default values are used
+    /// for unset fields. The generated function panics if a field is set
+    /// while any of the previous fields is unset.
+    OptionalArguments,
+}
+
+impl Default for DefaultArgumentsMode {
+    fn default() -> Self {
+        DefaultArgumentsMode::Overloads
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -240,11 +305,14 @@ impl Config {
             target_include_paths: Default::default(),
             include_directives: Default::default(),
             cpp_parser_arguments: Default::default(),
+            is_c_library: false,
             cpp_build_config: Default::default(),
             movable_types_hook: Default::default(),
+            detect_movable_types: false,
             processing_steps: Default::default(),
             cpp_lib_version: Default::default(),
             cpp_parser_path_hook: Default::default(),
+            cpp_parser_hooks_version: 0,
             rust_path_scope_hook: Default::default(),
             rust_path_hook: Default::default(),
             after_cpp_parser_hooks: Default::default(),
@@ -252,6 +320,12 @@ impl Config {
             cluster_config: None,
             cpp_checker_tests: Default::default(),
             write_dependencies_local_paths: true,
+            special_typedefs: default_special_typedefs(),
+            template_instantiations: Vec::new(),
+            default_arguments_mode: Default::default(),
+            default_arguments_mode_hook: Default::default(),
+            overridable_classes: Vec::new(),
+            member_pointer_getters: Vec::new(),
         }
     }
 
@@ -299,6 +373,21 @@ impl Config {
         }
     }
 
+    /// Enables processing of a plain C library. In this mode,
+    /// the parser reads the headers as C code, free functions are
+    /// called directly from Rust without a C++ wrapper function,
+    /// and structs with supported field types are generated as `#[repr(C)]`
+    /// Rust structs with public fields. The generated crate doesn't contain
+    /// a wrapper library, and its build script only links the target library.
+    ///
+    /// Headers must be usable from C++ (i.e. declare functions within `extern "C"`),
+    /// as they are still used by the C++ checker.
+    ///
+    /// Default value is `false`.
+    pub fn set_is_c_library(&mut self, value: bool) {
+        self.is_c_library = value;
+    }
+
     /// Sets `CppBuildPaths` value for this config. These paths
     /// are used for testing C++ methods while processing the library,
     /// but they are not used when building the generated crate.
@@ -370,6 +459,11 @@ impl Config {
         &self.cpp_parser_arguments
     }
 
+    /// Returns value set by `Config::set_is_c_library`.
+    pub fn is_c_library(&self) -> bool {
+        self.is_c_library
+    }
+
     /// Returns values added by `Config::set_cpp_build_paths`.
     pub fn cpp_build_paths(&self) -> &CppBuildPaths {
         &self.cpp_build_paths
@@ -390,6 +484,11 @@ impl Config {
         &self.cpp_build_config
     }
 
+    /// Sets the hook that decides whether a class is movable.
+    /// If the hook returns `MovableTypesHookOutput::Unknown` (or is not set),
+    /// `ritual:movable` and `ritual:immovable` annotations in the headers are used.
+    /// If there are no annotations, the class is immovable unless
+    /// automatic detection is enabled with `Config::set_detect_movable_types`.
     pub fn set_movable_types_hook(
         &mut self,
         hook: impl Fn(&CppPath) -> Result<MovableTypesHookOutput> + 'static,
@@ -403,6 +502,19 @@ impl Config {
         self.movable_types_hook.as_ref().map(|b| &**b)
     }
 
+    /// Enables automatic detection of movable classes. If enabled, classes
+    /// not decided by `movable_types_hook` or annotations are movable
+    /// if their type traits allow it. Disabled by default because it changes
+    /// the API of every class that passes the checks.
+    pub fn set_detect_movable_types(&mut self, value: bool) {
+        self.detect_movable_types = value;
+    }
+
+    /// Returns value set by `Config::set_detect_movable_types`.
+    pub fn detect_movable_types(&self) -> bool {
+        self.detect_movable_types
+    }
+
     /// Adds a C++ identifier that should be skipped
     /// by the C++ parser. Identifier can contain namespaces
     /// and nested classes, with `::` separator (like in
@@ -413,6 +525,8 @@ impl Config {
     /// will also be skipped.
     /// All class methods with names matching the blocked name
     /// will be skipped, regardless of class name.
+    /// Entities annotated with `ritual:skip` in the headers are skipped
+    /// even if the hook allows them.
     pub fn set_cpp_parser_path_hook(&mut self, hook: impl Fn(&CppPath) -> Result<bool> + 'static) {
         self.cpp_parser_path_hook = Some(Box::new(hook));
     }
@@ -421,6 +535,21 @@ impl Config {
         self.cpp_parser_path_hook.as_ref().map(|b| &**b)
     }
 
+    /// Sets the version of hooks that affect the C++ parser's output
+    /// (see `Config::set_cpp_parser_path_hook`). The parser can't detect
+    /// changes in the hooks' behavior, so the version must be bumped
+    /// each time they change to prevent reusing outdated results.
+    ///
+    /// Default value is 0.
+    pub fn set_cpp_parser_hooks_version(&mut self, version: u32) {
+        self.cpp_parser_hooks_version = version;
+    }
+
+    /// Returns value set by `Config::set_cpp_parser_hooks_version`.
+    pub fn cpp_parser_hooks_version(&self) -> u32 {
+        self.cpp_parser_hooks_version
+    }
+
     pub fn set_rust_path_scope_hook(
         &mut self,
         hook: impl Fn(&CppPath) -> Result<Option<RustPathScope>> + 'static,
@@ -432,6 +561,9 @@ impl Config {
         self.rust_path_scope_hook.as_ref().map(|b| &**b)
     }
 
+    /// Sets the hook that overrides Rust paths of generated items.
+    /// If the hook returns `None` (or is not set), the name requested with
+    /// a `ritual:rename=name` annotation in the headers is used, if any.
     pub fn set_rust_path_hook(
         &mut self,
         hook: impl Fn(&CppPath, NameType<'_>, &ProcessorData<'_>) -> Result<Option<RustPath>> + 'static,
@@ -485,6 +617,185 @@ impl Config {
     pub fn write_dependencies_local_paths(&self) -> bool {
         self.write_dependencies_local_paths
     }
+
+    /// Adds a typedef that should be treated as a fixed size or pointer sized integer
+    /// instead of being replaced with its canonical type. `cpp_type` must be
+    /// `CppType::SpecificNumeric` or `CppType::PointerSizedInteger`.
+    ///
+    /// `name` is the typedef name as it appears in the C++ code (e.g. `qint64`
+    /// or `std::vector::size_type`). Commonly used typedefs are added by default.
+    pub fn add_special_typedef<S: Into<String>>(
+        &mut self,
+        name: S,
+        cpp_type: CppType,
+    ) -> Result<()> {
+        match cpp_type {
+            CppType::SpecificNumeric(_) | CppType::PointerSizedInteger { .. } => {}
+            _ => bail!("unsupported type for a special typedef: {:?}", cpp_type),
+        }
+        self.special_typedefs.insert(name.into(), cpp_type);
+        Ok(())
+    }
+
+    /// Removes a typedef added by default or with `Config::add_special_typedef`.
+    pub fn remove_special_typedef(&mut self, name: &str) {
+        self.special_typedefs.remove(name);
+    }
+
+    /// Returns typedefs added with `Config::add_special_typedef`.
+    pub fn special_typedefs(&self) -> &HashMap<String, CppType> {
+        &self.special_typedefs
+    }
+
+    /// Requests an instantiation of a class template that is not necessarily
+    /// used in the library's API (e.g. `std::vector<std::string>`).
+    /// Template classes and functions can only be wrapped for specific
+    /// template arguments, so by default only instantiations encountered in the API
+    /// are available.
+    ///
+    /// The instantiation is processed in the same way as the discovered ones.
+    /// Instantiations that fail to compile are skipped and recorded
+    /// in the parser's report.
+    pub fn add_template_instantiation(&mut self, instantiation: TemplateInstantiation) {
+        if !self.template_instantiations.contains(&instantiation) {
+            self.template_instantiations.push(instantiation);
+        }
+    }
+
+    /// Adds multiple template instantiations (see `Config::add_template_instantiation`).
+    pub fn add_template_instantiations(
+        &mut self,
+        items: impl IntoIterator<Item = TemplateInstantiation>,
+    ) {
+        for item in items {
+            self.add_template_instantiation(item);
+        }
+    }
+
+    /// Returns template instantiations added with `Config::add_template_instantiation`.
+    pub fn template_instantiations(&self) -> &[TemplateInstantiation] {
+        &self.template_instantiations
+    }
+
+    /// Sets how functions with default argument values are exposed in Rust.
+    /// The default is `DefaultArgumentsMode::Overloads`.
+    pub fn set_default_arguments_mode(&mut self, mode: DefaultArgumentsMode) {
+        self.default_arguments_mode = mode;
+    }
+
+    pub fn default_arguments_mode(&self) -> DefaultArgumentsMode {
+        self.default_arguments_mode
+    }
+
+    /// Sets the hook that selects `DefaultArgumentsMode` for individual functions.
+    /// If the hook returns `None` (or is not set), the value set with
+    /// `Config::set_default_arguments_mode` is used.
+    pub fn set_default_arguments_mode_hook(
+        &mut self,
+        hook: impl Fn(&CppFunction) -> Result<Option<DefaultArgumentsMode>> + 'static,
+    ) {
+        self.default_arguments_mode_hook = Some(Box::new(hook));
+    }
+
+    pub fn default_arguments_mode_hook(&self) -> Option<&DefaultArgumentsModeHook> {
+        self.default_arguments_mode_hook.as_ref().map(|b| &**b)
+    }
+
+    /// Returns `DefaultArgumentsMode` selected for `function`.
+    pub fn default_arguments_mode_for(
+        &self,
+        function: &CppFunction,
+    ) -> Result<DefaultArgumentsMode> {
+        if let Some(hook) = self.default_arguments_mode_hook() {
+            if let Some(mode) = hook(function)? {
+                return Ok(mode);
+            }
+        }
+        Ok(self.default_arguments_mode)
+    }
+
+    /// Allows to override virtual methods of the class `path` from Rust.
+    ///
+    /// A C++ subclass that forwards virtual methods to Rust callbacks is generated
+    /// for the class. On the Rust side, a trait with a method for each virtual method
+    /// is generated. Default implementations of the trait methods call the base class
+    /// implementation.
+    ///
+    /// The subclass is created with the default constructor of the class,
+    /// so the class must have a public or protected default constructor.
+    pub fn add_overridable_class(&mut self, path: CppPath) {
+        if !self.overridable_classes.contains(&path) {
+            self.overridable_classes.push(path);
+        }
+    }
+
+    /// Returns classes added with `Config::add_overridable_class`.
+    pub fn overridable_classes(&self) -> &[CppPath] {
+        &self.overridable_classes
+    }
+
+    /// Requests an FFI function that returns a pointer to the method `path`
+    /// (all overloads of the method are included).
+    ///
+    /// Such functions are also generated without this setting if the type
+    /// of the pointer to the method is used in the API of the library.
+    pub fn add_member_pointer_getter(&mut self, path: CppPath) {
+        if !self.member_pointer_getters.contains(&path) {
+            self.member_pointer_getters.push(path);
+        }
+    }
+
+    /// Returns methods added with `Config::add_member_pointer_getter`.
+    pub fn member_pointer_getters(&self) -> &[CppPath] {
+        &self.member_pointer_getters
+    }
+}
+
+fn default_special_typedefs() -> HashMap<String, CppType> {
+    let mut result = HashMap::new();
+    let integers: &[(&[&str], usize, bool)] = &[
+        (&["qint8", "int8_t", "GLbyte"], 8, true),
+        (&["quint8", "uint8_t", "GLubyte"], 8, false),
+        (&["qint16", "int16_t", "GLshort"], 16, true),
+        (&["quint16", "uint16_t", "GLushort"], 16, false),
+        (&["qint32", "int32_t", "GLint"], 32, true),
+        (&["quint32", "uint32_t", "GLuint"], 32, false),
+        (&["qint64", "int64_t", "qlonglong", "GLint64"], 64, true),
+        (
+            &["quint64", "uint64_t", "qulonglong", "GLuint64"],
+            64,
+            false,
+        ),
+    ];
+    for &(names, bits, is_signed) in integers {
+        for &name in names {
+            let cpp_type = CppType::SpecificNumeric(CppSpecificNumericType {
+                path: CppPath::from_good_str(name),
+                bits,
+                kind: CppSpecificNumericTypeKind::Integer { is_signed },
+            });
+            result.insert(name.to_string(), cpp_type);
+        }
+    }
+    let pointer_sized: &[(&str, &str, bool)] = &[
+        ("qintptr", "qintptr", true),
+        ("qptrdiff", "qptrdiff", true),
+        ("QList::difference_type", "QList::difference_type", true),
+        ("quintptr", "quintptr", false),
+        ("size_t", "size_t", false),
+        ("std::size_t", "std::size_t", false),
+        ("std::initializer_list::size_type", "size_t", false),
+        ("std::__cxx11::basic_string::size_type", "size_t", false),
+        ("std::vector::size_type", "size_t", false),
+    ];
+    for &(name, path, is_signed) in pointer_sized {
+        let cpp_type = CppType::PointerSizedInteger {
+            path: CppPath::from_good_str(path),
+            is_signed,
+        };
+        result.insert(name.to_string(), cpp_type);
+    }
+    result
 }
 
 #[derive(Default)]
@@ -0,0 +1,96 @@
+//! Binding options specified directly in C++ headers using
+//! `__attribute__((annotate("ritual:...")))`.
+
+use ritual_common::errors::{bail, Result};
+use serde_derive::{Deserialize, Serialize};
+
+/// Prefix of annotation texts recognized by ritual.
+pub const ANNOTATION_PREFIX: &str = "ritual:";
+
+/// A single `ritual:` annotation of a C++ declaration.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum CppAnnotation {
+    /// `ritual:skip`: the declaration is ignored by the parser
+    /// (unless the decision is made by `Config::set_cpp_parser_path_hook`).
+    Skip,
+    /// `ritual:rename=name`: the declaration is exposed under the specified Rust name.
+    Rename(String),
+    /// `ritual:movable`: the class can be moved in memory.
+    Movable,
+    /// `ritual:immovable`: the class can't be moved in memory.
+    Immovable,
+    /// `ritual:safe`: the function is safe to call from Rust.
+    Safe,
+    /// `ritual:transfer_ownership`: when applied to a function, the caller takes ownership
+    /// of the returned pointer. When applied to a function's argument, the function
+    /// takes ownership of the passed pointer.
+    TransferOwnership,
+}
+
+impl CppAnnotation {
+    /// Parses text of a clang annotation. Returns `None` if the annotation
+    /// doesn't have the `ritual:` prefix.
+    pub fn parse(text: &str) -> Result<Option<CppAnnotation>> {
+        if !text.starts_with(ANNOTATION_PREFIX) {
+            return Ok(None);
+        }
+        let text = text[ANNOTATION_PREFIX.len()..].trim();
+        let mut parts = text.splitn(2, '=');
+        let name = parts.next().unwrap_or("").trim();
+        let value = parts.next().map(str::trim);
+        let annotation = match (name, value) {
+            ("skip", None) => CppAnnotation::Skip,
+            ("rename", Some(value)) => {
+                if value.is_empty()
+                    || value.starts_with(|c: char| c.is_ascii_digit())
+                    || !value.chars().all(|c| c.is_alphanumeric() || c == '_')
+                {
+                    bail!("invalid name in annotation: {:?}", text);
+                }
+                CppAnnotation::Rename(value.to_string())
+            }
+            ("movable", None) => CppAnnotation::Movable,
+            ("immovable", None) => CppAnnotation::Immovable,
+            ("safe", None) => CppAnnotation::Safe,
+            ("transfer_ownership", None) => CppAnnotation::TransferOwnership,
+            _ => bail!("unknown annotation: {:?}", text),
+        };
+        Ok(Some(annotation))
+    }
+}
+
+/// Binding options collected from annotations of a C++ declaration.
+/// These options are only used when the corresponding `Config` hook
+/// doesn't make a decision for the item.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct CppAnnotations {
+    /// Rust name of the item
+    pub rename: Option<String>,
+    /// Whether the class was annotated as movable or immovable
+    pub is_movable: Option<bool>,
+    /// True if the Rust function should not be marked unsafe
+    pub is_safe: bool,
+    /// True if the caller takes ownership of the returned pointer
+    pub returns_ownership: bool,
+    /// Indexes of arguments whose ownership is passed to the function
+    pub ownership_transferring_arguments: Vec<usize>,
+}
+
+impl CppAnnotations {
+    /// Adds information from `annotation` of the declaration itself.
+    pub fn add(&mut self, annotation: CppAnnotation) {
+        match annotation {
+            // skipped declarations are not added to the database
+            CppAnnotation::Skip => {}
+            CppAnnotation::Rename(name) => self.rename = Some(name),
+            CppAnnotation::Movable => self.is_movable = Some(true),
+            CppAnnotation::Immovable => self.is_movable = Some(false),
+            CppAnnotation::Safe => self.is_safe = true,
+            CppAnnotation::TransferOwnership => self.returns_ownership = true,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self == &CppAnnotations::default()
+    }
+}
@@ -1,10 +1,12 @@
-use crate::cpp_data::{CppBaseSpecifier, CppItem, CppPath, CppPathItem};
+use crate::cpp_data::{CppBaseSpecifier, CppItem, CppPath, CppPathItem, CppVisibility};
 use crate::cpp_ffi_data::CppCast;
 use crate::cpp_function::{CppFunction, CppFunctionArgument};
 use crate::cpp_type::{CppPointerLikeTypeKind, CppType};
-use crate::database::ItemWithSource;
+use crate::database::{ItemId, ItemWithSource};
 use crate::processor::ProcessorData;
+use log::trace;
 use ritual_common::errors::Result;
+use std::collections::{HashMap, HashSet};
 
 /// Convenience function to create `CppMethod` object for
 /// `static_cast` or `dynamic_cast` from type `from` to type `to`.
@@ -27,10 +29,21 @@ fn create_cast_method(cast: CppCast, from: &CppType, to: &CppType) -> Result<Cpp
         allows_variadic_arguments: false,
         declaration_code: None,
         cast: Some(cast),
+        is_noexcept: false,
+        is_deleted: false,
+        is_friend: false,
     };
     Ok(CppItem::Function(function))
 }
 
+fn pointer_type(path: &CppPath) -> CppType {
+    CppType::PointerLike {
+        is_const: false,
+        kind: CppPointerLikeTypeKind::Pointer,
+        target: Box::new(CppType::Class(path.clone())),
+    }
+}
+
 /// Performs a portion of `generate_casts` operation.
 /// Adds casts between `target_type` and `base_type` and calls
 /// `generate_casts_one` recursively to add casts between `target_type`
@@ -41,16 +54,8 @@ fn generate_casts_one(
     direct_base_index: Option<usize>,
     data: &ProcessorData<'_>,
 ) -> Result<Vec<CppItem>> {
-    let target_ptr_type = CppType::PointerLike {
-        is_const: false,
-        kind: CppPointerLikeTypeKind::Pointer,
-        target: Box::new(CppType::Class(target_type.clone())),
-    };
-    let base_ptr_type = CppType::PointerLike {
-        is_const: false,
-        kind: CppPointerLikeTypeKind::Pointer,
-        target: Box::new(CppType::Class(base_type.clone())),
-    };
+    let target_ptr_type = pointer_type(target_type);
+    let base_ptr_type = pointer_type(base_type);
     let mut new_methods = Vec::new();
     new_methods.push(create_cast_method(
         CppCast::Static {
@@ -99,6 +104,182 @@ fn generate_casts(base: &CppBaseSpecifier, data: &ProcessorData<'_>) -> Result<V
     )
 }
 
+/// Information about public bases of all known classes.
+struct Hierarchy<'a> {
+    bases: HashMap<&'a CppPath, Vec<(ItemId, &'a CppBaseSpecifier)>>,
+    classes_with_virtual_functions: HashSet<CppPath>,
+}
+
+impl<'a> Hierarchy<'a> {
+    fn new(data: &'a ProcessorData<'_>) -> Self {
+        let mut bases = HashMap::<_, Vec<_>>::new();
+        let mut classes_with_virtual_functions = HashSet::new();
+        for item in data.db.all_cpp_items() {
+            if let Some(base) = item.item.as_base_ref() {
+                if base.visibility == CppVisibility::Public {
+                    bases
+                        .entry(&base.derived_class_type)
+                        .or_default()
+                        .push((item.id, base));
+                }
+            }
+            if let Some(function) = item.item.as_function_ref() {
+                if function.is_virtual() {
+                    if let Some(member) = &function.member {
+                        if member.inherited_from.is_none() {
+                            if let Ok(parent) = function.path.parent() {
+                                classes_with_virtual_functions.insert(parent);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        for class_bases in bases.values_mut() {
+            class_bases.sort_by_key(|(_, base)| base.base_index);
+        }
+        Hierarchy {
+            bases,
+            classes_with_virtual_functions,
+        }
+    }
+
+    /// Adds `class_path` and all its public bases to `result`.
+    fn add_with_bases(&self, class_path: &'a CppPath, result: &mut Vec<&'a CppPath>) {
+        if result.contains(&class_path) {
+            return;
+        }
+        result.push(class_path);
+        for (_, base) in self.bases.get(class_path).into_iter().flatten() {
+            self.add_with_bases(&base.base_class_type, result);
+        }
+    }
+
+    fn with_bases(&self, class_path: &'a CppPath) -> Vec<&'a CppPath> {
+        let mut result = Vec::new();
+        self.add_with_bases(class_path, &mut result);
+        result
+    }
+
+    /// Returns true if `class_path` or any of its bases has virtual functions.
+    fn is_polymorphic(&self, class_path: &'a CppPath) -> bool {
+        self.with_bases(class_path)
+            .iter()
+            .any(|path| self.classes_with_virtual_functions.contains(*path))
+    }
+}
+
+/// Adds `dynamic_cast` functions between polymorphic bases of classes
+/// with multiple bases. Casts are only added between classes that are not derived
+/// from each other. At least one of the classes must belong to the current crate.
+fn generate_cross_casts<'a>(
+    hierarchy: &Hierarchy<'a>,
+    data: &'a ProcessorData<'_>,
+) -> Result<Vec<ItemWithSource<CppItem>>> {
+    let local_classes = data
+        .db
+        .cpp_items()
+        .filter_map(|item| item.item.as_type_ref())
+        .map(|type1| &type1.path)
+        .collect::<HashSet<_>>();
+
+    let mut pairs = HashSet::new();
+    let mut results = Vec::new();
+    for item in data.db.cpp_items() {
+        let type1 = match item.item.as_type_ref() {
+            Some(type1) => type1,
+            None => continue,
+        };
+        if CppType::Class(type1.path.clone()).is_or_contains_template_parameter() {
+            continue;
+        }
+        let direct_bases = match hierarchy.bases.get(&type1.path) {
+            Some(bases) if bases.len() > 1 => bases,
+            _ => continue,
+        };
+        let groups = direct_bases
+            .iter()
+            .map(|(id, base)| (id, hierarchy.with_bases(&base.base_class_type)))
+            .collect::<Vec<_>>();
+
+        for (index, (source_id, group)) in groups.iter().enumerate() {
+            for (other_index, (_, other_group)) in groups.iter().enumerate() {
+                if index == other_index {
+                    continue;
+                }
+                for &from in group {
+                    for &to in other_group {
+                        if group.contains(&to) || other_group.contains(&from) {
+                            // the classes are derived from each other
+                            // or from a common base
+                            continue;
+                        }
+                        if !local_classes.contains(from) && !local_classes.contains(to)
+                            || !hierarchy.is_polymorphic(from)
+                            || !hierarchy.is_polymorphic(to)
+                        {
+                            continue;
+                        }
+                        if !pairs.insert((from, to)) {
+                            continue;
+                        }
+                        trace!(
+                            "adding cross cast from {} to {} (via {})",
+                            from.to_cpp_pseudo_code(),
+                            to.to_cpp_pseudo_code(),
+                            type1.path.to_cpp_pseudo_code()
+                        );
+                        let function = create_cast_method(
+                            CppCast::CrossCast,
+                            &pointer_type(from),
+                            &pointer_type(to),
+                        )?;
+                        results.push(ItemWithSource::new(source_id, function));
+                    }
+                }
+            }
+        }
+    }
+    Ok(results)
+}
+
+/// Adds `dynamic_cast<void*>` functions returning the most derived object
+/// for polymorphic classes of the current crate.
+fn generate_most_derived_casts<'a>(
+    hierarchy: &Hierarchy<'a>,
+    data: &'a ProcessorData<'_>,
+) -> Result<Vec<ItemWithSource<CppItem>>> {
+    let void_ptr_type = CppType::PointerLike {
+        is_const: false,
+        kind: CppPointerLikeTypeKind::Pointer,
+        target: Box::new(CppType::Void),
+    };
+    let mut results = Vec::new();
+    for item in data.db.cpp_items() {
+        let type1 = match item.item.as_type_ref() {
+            Some(type1) => type1,
+            None => continue,
+        };
+        if !type1.kind.is_class()
+            || CppType::Class(type1.path.clone()).is_or_contains_template_parameter()
+            || !hierarchy.is_polymorphic(&type1.path)
+        {
+            continue;
+        }
+        trace!(
+            "adding most derived cast for {}",
+            type1.path.to_cpp_pseudo_code()
+        );
+        let function = create_cast_method(
+            CppCast::MostDerived,
+            &pointer_type(&type1.path),
+            &void_ptr_type,
+        )?;
+        results.push(ItemWithSource::new(&item.id, function));
+    }
+    Ok(results)
+}
+
 pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
     let mut results = Vec::new();
     let bases = data
@@ -111,6 +292,9 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
             results.push(ItemWithSource::new(&item.id, value));
         }
     }
+    let hierarchy = Hierarchy::new(data);
+    results.extend(generate_cross_casts(&hierarchy, data)?);
+    results.extend(generate_most_derived_casts(&hierarchy, data)?);
     for item in results {
         data.db.add_cpp_item(Some(item.source_id), item.item)?;
     }
@@ -1,8 +1,9 @@
-use crate::config::Config;
+use crate::config::{ClusterConfig, Config};
 use crate::cpp_checks::CppChecksItem;
-use crate::cpp_data::CppPath;
+use crate::cpp_data::{CppItem, CppPath};
 use crate::cpp_ffi_data::CppFfiItem;
-use crate::cpp_type::CppType;
+use crate::cpp_implicit_methods::{Availability, ImplicitMethodsPredictor, SpecialMemberKind};
+use crate::cpp_type::{CppMemberPointerTarget, CppType};
 use crate::database::{DatabaseClient, DbItem, ItemId};
 use crate::processor::ProcessorData;
 use crate::{cluster_api, cpp_code_generator};
@@ -36,6 +37,10 @@ fn snippet_for_item(item: DbItem<&CppFfiItem>, database: &DatabaseClient) -> Res
     match &item.item {
         CppFfiItem::Function(_) => {
             let item = item.map(|item| item.as_function_ref().unwrap());
+            if item.item.is_direct_call {
+                let name = item.item.path.to_cpp_code()?;
+                return Ok(Snippet::new_in_main(format!("(void) &{};", name), false));
+            }
             let item_code = cpp_code_generator::function_implementation(database, item.clone())?;
             let mut needs_moc = false;
 
@@ -56,6 +61,10 @@ fn snippet_for_item(item: DbItem<&CppFfiItem>, database: &DatabaseClient) -> Res
             item.item.source_item_cpp_code(database)?,
             true,
         )),
+        CppFfiItem::Overrider(_) => Ok(Snippet::new_global(
+            item.item.source_item_cpp_code(database)?,
+            false,
+        )),
     }
 }
 
@@ -219,13 +228,22 @@ pub struct SnippetTask<T> {
 }
 
 pub struct SnippetTaskLocalData {
-    pub ffi_item_id: ItemId,
+    pub item_id: ItemId,
     pub crate_name: String,
     pub library_target: LibraryTarget,
 }
 
 pub type LocalSnippetTask = SnippetTask<SnippetTaskLocalData>;
 
+/// Checks of implicit special member functions predicted to be available.
+/// They are only performed if availability of their class can't be confirmed
+/// by other items of the class.
+#[derive(Default)]
+struct DeferredTasks {
+    tasks: Vec<(CppPath, LocalSnippetTask)>,
+    class_items: HashMap<CppPath, Vec<ItemId>>,
+}
+
 impl Snippet {
     pub fn new_in_main<S: Into<String>>(code: S, needs_moc: bool) -> Self {
         Snippet {
@@ -242,6 +260,10 @@ impl Snippet {
             needs_moc,
         }
     }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -344,7 +366,7 @@ impl LocalCppChecker {
     }
 }
 
-struct InstanceStorage {
+pub struct InstanceStorage {
     instances: Arc<Mutex<HashMap<ThreadId, Arc<Mutex<CppCheckerInstance>>>>>,
     provider: LocalCppChecker,
 }
@@ -418,27 +440,14 @@ fn builtin_tests() -> Vec<PreliminaryTest> {
     ]
 }
 
-impl CppChecker<'_, '_> {
-    fn env(&self) -> LibraryTarget {
-        LibraryTarget {
-            target: current_target(),
-            cpp_library_version: self.data.config.cpp_lib_version().map(ToString::to_string),
-        }
-    }
-
-    fn run(&mut self) -> Result<()> {
-        if self.data.config.cluster_config().is_some() {
-            self.run_cluster()
-        } else {
-            self.run_local()
-        }
-    }
-
-    fn run_cluster(&mut self) -> Result<()> {
-        let cluster_config = self.data.config.cluster_config().unwrap();
-        let crate_name = self.data.db.crate_name().to_string();
-
-        let environments = cluster_config
+/// Returns environments the snippets are checked in.
+///
+/// If a cluster is configured, all environments provided by its workers are used.
+/// Otherwise, the snippets are only checked for the current target.
+pub fn environments(data: &ProcessorData<'_>) -> Vec<LibraryTarget> {
+    if let Some(cluster_config) = data.config.cluster_config() {
+        let crate_name = data.db.crate_name();
+        cluster_config
             .workers
             .iter()
             .flat_map(|worker| {
@@ -451,67 +460,170 @@ impl CppChecker<'_, '_> {
                         cpp_library_version: lib.lib_version.clone(),
                     })
             })
-            .collect_vec();
+            .collect_vec()
+    } else {
+        vec![LibraryTarget {
+            target: current_target(),
+            cpp_library_version: data.config.cpp_lib_version().map(ToString::to_string),
+        }]
+    }
+}
 
-        for env in &environments {
-            self.data.db.add_environment(env.clone());
-        }
+/// Compiles snippets either locally or on the cluster.
+pub enum SnippetRunner<'a> {
+    Local {
+        provider: LocalCppChecker,
+        instances: InstanceStorage,
+        preliminary_tests_passed: bool,
+    },
+    Cluster(&'a ClusterConfig),
+}
 
-        let mut snippets = self.create_tasks(&environments)?;
-        if snippets.is_empty() {
-            return Ok(());
+impl<'a> SnippetRunner<'a> {
+    pub fn new(data: &ProcessorData<'a>) -> Result<Self> {
+        if let Some(cluster_config) = data.config.cluster_config() {
+            return Ok(SnippetRunner::Cluster(cluster_config));
         }
-
-        cluster_api::run_checks(cluster_config, &mut snippets)?;
-
-        self.save_results(snippets)?;
-
-        Ok(())
+        let provider =
+            LocalCppChecker::new(data.workspace.tmp_path().join("cpp_checker"), &data.config)?;
+        Ok(SnippetRunner::Local {
+            instances: InstanceStorage::new(provider.clone()),
+            provider,
+            preliminary_tests_passed: false,
+        })
     }
 
-    fn run_local(&mut self) -> Result<()> {
-        let instance_provider = LocalCppChecker::new(
-            self.data.workspace.tmp_path().join("cpp_checker"),
-            &self.data.config,
-        )?;
-
-        let env = self.env();
-
-        self.data.db.add_environment(env.clone());
+    /// Compiles and runs `snippets` and saves the results to their `output` fields.
+    pub fn check(&mut self, snippets: &mut [LocalSnippetTask]) -> Result<()> {
+        match self {
+            SnippetRunner::Local {
+                provider,
+                instances,
+                preliminary_tests_passed,
+            } => {
+                if !*preliminary_tests_passed {
+                    let mut instance = provider.get("tests")?;
+                    instance.check_preliminary_tests()?;
+                    *preliminary_tests_passed = true;
+                }
 
-        let mut snippets = self.create_tasks(&[env])?;
-        if snippets.is_empty() {
-            return Ok(());
+                let progress_bar = ProgressBar::new(snippets.len() as u64, "Checking items");
+                snippets
+                    .par_chunks_mut(CHUNK_SIZE)
+                    .map(|chunk| {
+                        let progress_bar = progress_bar.clone();
+                        let instance = instances.current()?;
+                        let mut instance = instance.lock().unwrap();
+                        instance.binary_check(chunk, Some(&progress_bar))
+                    })
+                    .collect::<Result<_>>()
+            }
+            SnippetRunner::Cluster(cluster_config) => {
+                cluster_api::run_checks(cluster_config, snippets)
+            }
         }
+    }
+}
 
-        let mut instance = instance_provider.get("tests")?;
-        instance.check_preliminary_tests()?;
-
-        let progress_bar = ProgressBar::new(snippets.len() as u64, "Checking items");
+impl CppChecker<'_, '_> {
+    fn run(&mut self) -> Result<()> {
+        let environments = environments(self.data);
+        let mut runner = SnippetRunner::new(self.data)?;
+        self.run_checks(&environments, |snippets| runner.check(snippets))
+    }
 
-        let instances = InstanceStorage::new(instance_provider.clone());
+    /// Checks all unchecked items in `environments` using `check` to compile the snippets.
+    ///
+    /// Implicit special member functions predicted to be available are not checked
+    /// in environments where other items of their class were checked successfully.
+    fn run_checks(
+        &mut self,
+        environments: &[LibraryTarget],
+        mut check: impl FnMut(&mut [LocalSnippetTask]) -> Result<()>,
+    ) -> Result<()> {
+        for env in environments {
+            self.data.db.add_environment(env.clone());
+        }
 
-        snippets
-            .par_chunks_mut(CHUNK_SIZE)
-            .map(|chunk| {
-                let progress_bar = progress_bar.clone();
-                let instance = instances.current()?;
-                let mut instance = instance.lock().unwrap();
-                instance.binary_check(chunk, Some(&progress_bar))
-            })
-            .collect::<Result<_>>()?;
-        self.save_results(snippets)?;
+        let (mut snippets, deferred) = self.create_tasks(environments)?;
+        if !snippets.is_empty() {
+            check(&mut snippets)?;
+            self.save_results(snippets)?;
+        }
 
+        let mut snippets = self.resolve_deferred_tasks(deferred)?;
+        if !snippets.is_empty() {
+            info!(
+                "Checking {} implicit methods with unconfirmed classes",
+                snippets.len()
+            );
+            check(&mut snippets)?;
+            self.save_results(snippets)?;
+        }
         Ok(())
     }
 
-    fn create_tasks(&self, library_targets: &[LibraryTarget]) -> Result<Vec<LocalSnippetTask>> {
+    /// Returns the class `ffi_item` belongs to and whether it's an implicit
+    /// special member function predicted to be available.
+    fn item_class(
+        &self,
+        predictor: &mut ImplicitMethodsPredictor<'_>,
+        ffi_item: &DbItem<&CppFfiItem>,
+    ) -> Result<Option<(CppPath, bool)>> {
+        let cpp_item = match self.data.db.source_cpp_item(&ffi_item.id)? {
+            Some(item) => item,
+            None => return Ok(None),
+        };
+        match &cpp_item.item {
+            CppItem::Function(function) => {
+                let class_path = match function.class_path() {
+                    Ok(path) => path,
+                    Err(_) => return Ok(None),
+                };
+                let is_predicted = match &cpp_item.source_id {
+                    Some(source_id) => {
+                        let is_implicit = self
+                            .data
+                            .db
+                            .item(source_id)?
+                            .item
+                            .as_cpp_item()
+                            .map_or(false, |item| item.as_type_ref().is_some());
+                        is_implicit
+                            && SpecialMemberKind::of_function(function).map_or(false, |kind| {
+                                predictor.predict(&class_path, kind) == Availability::Available
+                            })
+                    }
+                    None => false,
+                };
+                Ok(Some((class_path, is_predicted)))
+            }
+            CppItem::ClassField(field) => Ok(field.path.parent().ok().map(|path| (path, false))),
+            _ => Ok(None),
+        }
+    }
+
+    fn create_tasks(
+        &self,
+        library_targets: &[LibraryTarget],
+    ) -> Result<(Vec<LocalSnippetTask>, DeferredTasks)> {
         let crate_name = self.data.db.crate_name().to_string();
+        let mut predictor = ImplicitMethodsPredictor::new(&self.data.db);
 
         let mut snippets = Vec::new();
+        let mut deferred = DeferredTasks::default();
         let mut old_items_count = 0;
 
         for ffi_item in self.data.db.ffi_items() {
+            let class = self.item_class(&mut predictor, &ffi_item)?;
+            if let Some((class_path, false)) = &class {
+                deferred
+                    .class_items
+                    .entry(class_path.clone())
+                    .or_default()
+                    .push(ffi_item.id.clone());
+            }
+
             let checks = self.data.db.cpp_checks(&ffi_item.id)?;
             if checks.has_all_envs(library_targets) {
                 old_items_count += 1;
@@ -524,15 +636,20 @@ impl CppChecker<'_, '_> {
                         if checks.has_env(library_target) {
                             continue;
                         }
-                        snippets.push(SnippetTask {
+                        let task = SnippetTask {
                             data: SnippetTaskLocalData {
-                                ffi_item_id: ffi_item.id.clone(),
+                                item_id: ffi_item.id.clone(),
                                 crate_name: crate_name.clone(),
                                 library_target: library_target.clone(),
                             },
                             snippet: snippet.clone(),
                             output: None,
-                        });
+                        };
+                        if let Some((class_path, true)) = &class {
+                            deferred.tasks.push((class_path.clone(), task));
+                        } else {
+                            snippets.push(task);
+                        }
                     }
                 }
                 Err(err) => {
@@ -545,21 +662,68 @@ impl CppChecker<'_, '_> {
             }
         }
 
+        let count = snippets.len() + deferred.tasks.len();
         if old_items_count == 0 {
-            if !snippets.is_empty() {
-                info!("Checking {} items", snippets.len());
+            if count > 0 {
+                info!("Checking {} items", count);
             }
-        } else if snippets.is_empty() {
+        } else if count == 0 {
             info!("Ignoring {} old items", old_items_count);
         } else {
             info!(
                 "Checking {} items, ignoring {} old items",
-                snippets.len(),
-                old_items_count
+                count, old_items_count
+            );
+        }
+        if !deferred.tasks.is_empty() {
+            info!(
+                "{} implicit methods are predicted to be available",
+                deferred.tasks.len()
             );
         }
 
-        Ok(snippets)
+        Ok((snippets, deferred))
+    }
+
+    /// Marks deferred tasks as successful if their class was confirmed by a successful
+    /// check of another item of the class in the same environment.
+    /// Returns the rest of the tasks that still need to be checked.
+    fn resolve_deferred_tasks(&mut self, deferred: DeferredTasks) -> Result<Vec<LocalSnippetTask>> {
+        let mut remaining = Vec::new();
+        let mut confirmed_count = 0;
+        for (class_path, task) in deferred.tasks {
+            let mut is_confirmed = false;
+            if let Some(ids) = deferred.class_items.get(&class_path) {
+                for id in ids {
+                    if self
+                        .data
+                        .db
+                        .cpp_checks(id)?
+                        .is_success(&task.data.library_target)
+                    {
+                        is_confirmed = true;
+                        break;
+                    }
+                }
+            }
+            if is_confirmed {
+                confirmed_count += 1;
+                self.data.db.add_cpp_checks_item(
+                    task.data.item_id,
+                    CppChecksItem {
+                        env: task.data.library_target,
+                        is_success: true,
+                        is_predicted: true,
+                    },
+                );
+            } else {
+                remaining.push(task);
+            }
+        }
+        if confirmed_count > 0 {
+            info!("Skipped checking {} implicit methods", confirmed_count);
+        }
+        Ok(remaining)
     }
 
     fn save_results(&mut self, snippets: Vec<LocalSnippetTask>) -> Result<()> {
@@ -567,7 +731,7 @@ impl CppChecker<'_, '_> {
         let mut error_count = 0;
 
         for snippet in snippets {
-            let ffi_item = self.data.db.ffi_item_mut(&snippet.data.ffi_item_id)?;
+            let ffi_item = self.data.db.ffi_item_mut(&snippet.data.item_id)?;
             if let Some(output) = snippet.output {
                 if output.is_success() {
                     debug!("success: {}", ffi_item.item.short_text());
@@ -582,6 +746,7 @@ impl CppChecker<'_, '_> {
                     CppChecksItem {
                         env: snippet.data.library_target,
                         is_success: output.is_success(),
+                        is_predicted: false,
                     },
                 );
             } else {
@@ -619,7 +784,22 @@ fn type_paths(type1: &CppType) -> Vec<&CppPath> {
             .chain(once(&*function.return_type))
             .flat_map(|type1| type_paths(type1))
             .collect(),
+        CppType::MemberPointer(type1) => {
+            let mut paths = vec![&type1.class_path];
+            match &type1.target {
+                CppMemberPointerTarget::Field(field_type) => paths.extend(type_paths(field_type)),
+                CppMemberPointerTarget::Method { function_type, .. } => paths.extend(
+                    function_type
+                        .arguments
+                        .iter()
+                        .chain(once(&*function_type.return_type))
+                        .flat_map(|type1| type_paths(type1)),
+                ),
+            }
+            paths
+        }
         CppType::PointerLike { target, .. } => type_paths(target),
+        CppType::Array { element_type, .. } => type_paths(element_type),
     }
 }
 
@@ -6,6 +6,10 @@ use serde_derive::{Deserialize, Serialize};
 pub struct CppChecksItem {
     pub env: LibraryTarget,
     pub is_success: bool,
+    /// True if the item wasn't compiled and its availability was predicted
+    /// based on other items of the same class
+    #[serde(default)]
+    pub is_predicted: bool,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
@@ -1,6 +1,6 @@
 use crate::cpp_checks::Condition;
 use crate::cpp_ffi_data::{
-    CppFfiArgumentMeaning, CppFfiFunctionKind, CppFfiType, CppFieldAccessorType,
+    CppFfiArgumentMeaning, CppFfiFunctionKind, CppFfiType, CppFieldAccessorType, CppOverrider,
     CppToFfiTypeConversion, QtSlotWrapper,
 };
 use crate::cpp_ffi_data::{CppFfiFunction, CppFfiItem};
@@ -21,6 +21,51 @@ use std::path::{Path, PathBuf};
 
 struct Generator<'a>(&'a DatabaseClient);
 
+/// Generates declaration of a variable or an argument `name` of type `type1`.
+fn variable_declaration(type1: &CppType, name: &str) -> Result<String> {
+    if type1.is_function_pointer() {
+        type1.to_cpp_code(Some(name))
+    } else {
+        Ok(format!("{} {}", type1.to_cpp_code(None)?, name))
+    }
+}
+
+/// Generates code converting FFI argument `name` (a pointer to the innermost
+/// element of the array) to the array type `original_type` or a reference to it.
+fn array_argument_value(original_type: &CppType, name: &str) -> Result<String> {
+    if let CppType::PointerLike {
+        kind,
+        is_const,
+        target,
+    } = original_type
+    {
+        if *kind != CppPointerLikeTypeKind::Reference {
+            bail!("unsupported original type for ArrayToPointer conversion");
+        }
+        return Ok(format!(
+            "*reinterpret_cast<{}{}>({})",
+            if *is_const { "const " } else { "" },
+            target.to_cpp_code(Some("(*)"))?,
+            name
+        ));
+    }
+    match original_type {
+        // array arguments decay to a pointer to the first element
+        CppType::Array { element_type, .. } => {
+            if element_type.is_array() {
+                Ok(format!(
+                    "reinterpret_cast<{}>({})",
+                    element_type.to_cpp_code(Some("(*)"))?,
+                    name
+                ))
+            } else {
+                Ok(name.to_string())
+            }
+        }
+        _ => bail!("unsupported original type for ArrayToPointer conversion"),
+    }
+}
+
 impl Generator<'_> {
     /// Generates function name, return type and arguments list
     /// as it appears in both function declaration and implementation.
@@ -73,6 +118,99 @@ impl Generator<'_> {
         ))
     }
 
+    /// Generates code for a subclass that forwards virtual methods to Rust callbacks
+    fn overrider(&self, overrider: &CppOverrider) -> Result<String> {
+        let class_name = overrider.class_path.to_cpp_code()?;
+        let base_name = overrider.base_class_path.to_cpp_code()?;
+
+        let mut methods = String::new();
+        let mut fields = String::new();
+        for (index, method) in overrider.methods.iter().enumerate() {
+            let callback_field = format!("m_callback_{}", index);
+            let func_type = CppType::FunctionPointer(method.function_type.clone());
+            let const_text = if method.is_const { " const" } else { "" };
+            let return_type = method.return_type.original_type().to_cpp_code(None)?;
+            let args = method
+                .arguments
+                .iter()
+                .enumerate()
+                .map_if_ok(|(num, t)| {
+                    variable_declaration(t.original_type(), &format!("arg{}", num))
+                })?
+                .join(", ");
+            let arg_names = (0..method.arguments.len())
+                .map(|num| format!("arg{}", num))
+                .join(", ");
+            let callback_args =
+                once("m_data".to_string())
+                    .chain(once("this".to_string()))
+                    .chain(
+                        method.arguments.iter().enumerate().map_if_ok(|(num, t)| {
+                            self.convert_type_to_ffi(t, format!("arg{}", num))
+                        })?,
+                    )
+                    .join(", ");
+            let callback_call = format!("{}({})", callback_field, callback_args);
+            let callback_result = match method.return_type.conversion() {
+                CppToFfiTypeConversion::NoChange => callback_call,
+                CppToFfiTypeConversion::ReferenceToPointer => format!("*{}", callback_call),
+                _ => bail!(
+                    "unsupported return type conversion: {:?}",
+                    method.return_type.conversion()
+                ),
+            };
+            let base_call = format!("{}::{}({})", base_name, method.name, arg_names);
+
+            methods.push_str(&format!(
+                "    void set_{name}_callback({func_arg}) {{\n        \
+                 {field} = callback;\n    }}\n",
+                name = method.unique_name,
+                func_arg = func_type.to_cpp_code(Some("callback"))?,
+                field = callback_field,
+            ));
+            methods.push_str(&format!(
+                "    {ret} {name}({args}){const_text} override {{\n        \
+                 if ({field}) {{\n            return {callback_result};\n        }}\n        \
+                 {fallback}\n    }}\n",
+                ret = return_type,
+                name = method.name,
+                args = args,
+                const_text = const_text,
+                field = callback_field,
+                callback_result = callback_result,
+                fallback = if method.is_pure_virtual {
+                    // the object can't be used without callbacks for pure virtual methods
+                    "std::abort();".to_string()
+                } else {
+                    format!("return {};", base_call)
+                },
+            ));
+            if !method.is_pure_virtual {
+                methods.push_str(&format!(
+                    "    {ret} base_{name}({args}){const_text} {{\n        \
+                     return {base_call};\n    }}\n",
+                    ret = return_type,
+                    name = method.unique_name,
+                    args = args,
+                    const_text = const_text,
+                    base_call = base_call,
+                ));
+            }
+            fields.push_str(&format!(
+                "    {} = nullptr;\n",
+                func_type.to_cpp_code(Some(&callback_field))?
+            ));
+        }
+
+        Ok(format!(
+            include_str!("../templates/c_lib/overrider.h"),
+            class_name = class_name,
+            base_name = base_name,
+            methods = methods,
+            fields = fields,
+        ))
+    }
+
     /// Generates code that wraps `expression` of type `type1.original_type` and
     /// converts it to type `type1.ffi_type`
     fn convert_type_to_ffi(&self, type1: &CppFfiType, expression: String) -> Result<String> {
@@ -87,6 +225,12 @@ impl Generator<'_> {
             ),
             CppToFfiTypeConversion::ReferenceToPointer => format!("&{}", expression),
             CppToFfiTypeConversion::QFlagsToInt => format!("int({})", expression),
+            CppToFfiTypeConversion::ArrayToPointer { .. } => {
+                bail!("arrays are not supported in this context");
+            }
+            CppToFfiTypeConversion::MemberPointerToVoidPointer => {
+                bail!("member pointers are not supported in this context");
+            }
         })
     }
 
@@ -138,6 +282,18 @@ impl Generator<'_> {
             CppToFfiTypeConversion::QFlagsToInt => {
                 result = format!("int({})", result);
             }
+            CppToFfiTypeConversion::ArrayToPointer { .. } => {
+                // the array decays to a pointer to its first element (or first sub-array),
+                // so it's reinterpreted as a pointer to the innermost element
+                result = format!(
+                    "reinterpret_cast<{}>({})",
+                    method.return_type.ffi_type().to_cpp_code(None)?,
+                    result
+                );
+            }
+            CppToFfiTypeConversion::MemberPointerToVoidPointer => {
+                bail!("member pointers can't be returned by value");
+            }
         }
 
         if method.allocation_place == ReturnValueAllocationPlace::Stack && !is_constructor {
@@ -183,6 +339,20 @@ impl Generator<'_> {
                         };
                         result = format!("{}({})", type_text, result);
                     }
+                    CppToFfiTypeConversion::ArrayToPointer { .. } => {
+                        result =
+                            array_argument_value(argument.argument_type.original_type(), &result)?;
+                    }
+                    CppToFfiTypeConversion::MemberPointerToVoidPointer => {
+                        result = format!(
+                            "*static_cast<{}>({})",
+                            argument
+                                .argument_type
+                                .original_type()
+                                .to_cpp_code(Some("const*"))?,
+                            result
+                        );
+                    }
                 }
                 Ok(result)
             })?;
@@ -251,7 +421,47 @@ impl Generator<'_> {
                     .iter()
                     .find(|x| x.meaning == CppFfiArgumentMeaning::This)
                 {
-                    format!("{}->{}", arg.name, path.last().to_cpp_code()?)
+                    if arg.argument_type.original_type().is_rvalue_reference() {
+                        // `&&`-qualified methods can only be called on an rvalue
+                        format!(
+                            "static_cast<{}>(*{}).{}",
+                            arg.argument_type.original_type().to_cpp_code(None)?,
+                            arg.name,
+                            path.last().to_cpp_code()?
+                        )
+                    } else if let Some(base) = cpp_item
+                        .item
+                        .as_function_ref()
+                        .and_then(|f| f.member())
+                        .and_then(|info| info.inherited_from.as_ref())
+                    {
+                        // the name may be ambiguous in the derived class,
+                        // so the method is called on the base class subobject
+                        let base_ptr_type = CppType::PointerLike {
+                            kind: CppPointerLikeTypeKind::Pointer,
+                            is_const: cpp_item
+                                .item
+                                .as_function_ref()
+                                .and_then(|f| f.member())
+                                .map_or(false, |info| info.is_const),
+                            target: Box::new(CppType::Class(base.clone())),
+                        };
+                        format!(
+                            "static_cast<{}>({})->{}",
+                            base_ptr_type.to_cpp_code(None)?,
+                            arg.name,
+                            path.last().to_cpp_code()?
+                        )
+                    } else {
+                        format!("{}->{}", arg.name, path.last().to_cpp_code()?)
+                    }
+                } else if cpp_item
+                    .item
+                    .as_function_ref()
+                    .map_or(false, |f| f.is_friend)
+                {
+                    // hidden friends can only be found by argument-dependent lookup
+                    path.last().to_cpp_code()?
                 } else {
                     path.to_cpp_code()?
                 }
@@ -290,6 +500,20 @@ impl Generator<'_> {
             .map_or(false, |f| f.is_destructor());
 
         let method = item.item;
+        if method.kind == CppFfiFunctionKind::MemberPointerGetter {
+            let cpp_function = cpp_item
+                .item
+                .as_function_ref()
+                .ok_or_else(|| err_msg("invalid source cpp item type"))?;
+            return Ok(format!(
+                "static {} = &{};\n  return &value;\n",
+                method
+                    .return_type
+                    .original_type()
+                    .to_cpp_code(Some("const value"))?,
+                cpp_function.path.to_cpp_code()?
+            ));
+        }
         if is_destructor && method.allocation_place == ReturnValueAllocationPlace::Heap {
             if let Some(arg) = method
                 .arguments
@@ -383,21 +607,34 @@ impl Generator<'_> {
 
         let mut any_slot_wrappers = false;
         for ffi_item in &ffi_items {
-            if let CppFfiItem::QtSlotWrapper(qt_slot_wrapper) = &ffi_item.item {
-                let checks = self.0.cpp_checks(&ffi_item.id)?;
-                if !checks.any_success() {
-                    continue;
+            if !ffi_item.item.is_source_item() {
+                continue;
+            }
+            let checks = self.0.cpp_checks(&ffi_item.id)?;
+            if !checks.any_success() {
+                continue;
+            }
+            let code = match &ffi_item.item {
+                CppFfiItem::Function(_) => unreachable!(),
+                CppFfiItem::QtSlotWrapper(qt_slot_wrapper) => {
+                    self.qt_slot_wrapper(qt_slot_wrapper)?
                 }
+                CppFfiItem::Overrider(overrider) => self.overrider(overrider)?,
+            };
+            if ffi_item.item.is_slot_wrapper() {
                 any_slot_wrappers = true;
-                let condition = checks.condition(self.0.environments());
-                let code = self.qt_slot_wrapper(qt_slot_wrapper)?;
-                write!(cpp_file, "{}", self.wrap_with_condition(&code, &condition))?;
             }
+            let condition = checks.condition(self.0.environments());
+            write!(cpp_file, "{}", self.wrap_with_condition(&code, &condition))?;
         }
 
         writeln!(cpp_file, "extern \"C\" {{")?;
         for ffi_item in &ffi_items {
             if let Some(item) = ffi_item.clone().filter_map(|item| item.as_function_ref()) {
+                if item.item.is_direct_call {
+                    // the original function is called without a wrapper
+                    continue;
+                }
                 let checks = self.0.cpp_checks(&ffi_item.id)?;
                 if !checks.any_success() {
                     continue;
@@ -469,6 +706,10 @@ pub fn qt_slot_wrapper(db: &DatabaseClient, wrapper: &QtSlotWrapper) -> Result<S
     Generator(db).qt_slot_wrapper(wrapper)
 }
 
+pub fn overrider(db: &DatabaseClient, overrider: &CppOverrider) -> Result<String> {
+    Generator(db).overrider(overrider)
+}
+
 pub fn generate_cpp_file(
     db: &DatabaseClient,
     file_path: &Path,
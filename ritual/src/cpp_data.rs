@@ -57,6 +57,8 @@ pub struct CppClassField {
     /// Visibility
     pub visibility: CppVisibility,
     pub is_static: bool,
+    /// Width in bits if this field is a bit-field
+    pub bit_field_width: Option<usize>,
 }
 
 impl CppClassField {
@@ -65,6 +67,7 @@ impl CppClassField {
             && self.field_type == other.field_type
             && self.visibility == other.visibility
             && self.is_static == other.is_static
+            && self.bit_field_width == other.bit_field_width
     }
 
     pub fn short_text(&self) -> String {
@@ -73,11 +76,16 @@ impl CppClassField {
             CppVisibility::Protected => "protected ",
             CppVisibility::Private => "private ",
         };
+        let bit_field_text = match self.bit_field_width {
+            Some(width) => format!(" : {}", width),
+            None => String::new(),
+        };
         format!(
-            "{}{} {}",
+            "{}{} {}{}",
             visibility_text,
             self.field_type.to_cpp_pseudo_code(),
             self.path.to_cpp_pseudo_code(),
+            bit_field_text,
         )
     }
 }
@@ -370,6 +378,49 @@ impl fmt::Debug for CppPathItem {
 pub enum CppTypeDeclarationKind {
     Enum,
     Class,
+    Union,
+}
+
+/// Information about a special member function (destructor, default constructor,
+/// copy constructor or copy assignment operator) declared in a class body.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize, Hash)]
+pub struct CppSpecialMemberDeclaration {
+    pub visibility: CppVisibility,
+    /// True if the function is declared `= delete`
+    pub is_deleted: bool,
+    /// True if the function is declared `= default` (it may still be implicitly deleted)
+    pub is_defaulted: bool,
+    /// False if the signature differs from the signature of the implicitly declared function
+    /// (e.g. `MyClass(MyClass&)` instead of `MyClass(const MyClass&)`)
+    pub has_usual_signature: bool,
+}
+
+/// Information about special member functions of a class collected by the parser.
+/// It's used to predict which functions are implicitly declared by the compiler.
+/// Declarations that failed to parse are also taken into account.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Serialize, Deserialize, Hash)]
+pub struct CppClassSpecialMembers {
+    /// User-declared destructor
+    pub destructor: Option<CppSpecialMemberDeclaration>,
+    /// User-declared constructor that can be called without arguments
+    pub default_constructor: Option<CppSpecialMemberDeclaration>,
+    /// User-declared copy constructor
+    pub copy_constructor: Option<CppSpecialMemberDeclaration>,
+    /// User-declared copy assignment operator
+    pub copy_assignment: Option<CppSpecialMemberDeclaration>,
+    /// True if the class has any user-declared constructors (including templates)
+    pub has_constructors: bool,
+    /// True if the class has a user-declared move constructor or move assignment operator
+    pub has_move_operations: bool,
+    /// True if the class declares pure virtual methods
+    pub has_pure_virtual_methods: bool,
+    /// True if the class has non-static fields of reference types
+    pub has_reference_fields: bool,
+    /// True if the class has non-static const fields
+    pub has_const_fields: bool,
+    /// True if some fields or bases of the class are not present in the database
+    /// because they failed to parse or are members of anonymous structs or unions
+    pub has_unknown_members: bool,
 }
 
 /// Information about a C++ type declaration
@@ -378,6 +429,10 @@ pub struct CppTypeDeclaration {
     /// Identifier, including namespaces and nested classes
     pub path: CppPath,
     pub kind: CppTypeDeclarationKind,
+    /// Special member functions declared in the class body.
+    /// `None` for enums, class templates and template instantiations.
+    #[serde(default)]
+    pub special_members: Option<CppClassSpecialMembers>,
 }
 
 impl CppTypeDeclaration {
@@ -387,10 +442,17 @@ impl CppTypeDeclaration {
 }
 
 impl CppTypeDeclarationKind {
-    /// Checks if the type is a class type.
+    /// Checks if the type is a class type. Unions are also considered class types.
     pub fn is_class(&self) -> bool {
         match self {
-            CppTypeDeclarationKind::Class { .. } => true,
+            CppTypeDeclarationKind::Class { .. } | CppTypeDeclarationKind::Union => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_union(&self) -> bool {
+        match self {
+            CppTypeDeclarationKind::Union => true,
             _ => false,
         }
     }
@@ -403,6 +465,21 @@ impl CppTypeDeclarationKind {
     }
 }
 
+/// Information about a C++ typedef or a type alias (`using A = B;`)
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct CppTypeAlias {
+    /// Identifier, including namespaces and nested classes
+    pub path: CppPath,
+    /// Type the alias refers to
+    pub target_type: CppType,
+}
+
+impl CppTypeAlias {
+    pub fn is_same(&self, other: &CppTypeAlias) -> bool {
+        self.path == other.path
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CppNamespace {
     pub path: CppPath,
@@ -417,6 +494,7 @@ pub enum CppItem {
     Function(CppFunction),
     ClassField(CppClassField),
     ClassBase(CppBaseSpecifier),
+    TypeAlias(CppTypeAlias),
 }
 
 impl CppItem {
@@ -466,6 +544,13 @@ impl CppItem {
                     false
                 }
             }
+            TypeAlias(v) => {
+                if let TypeAlias(v2) = &other {
+                    v.is_same(v2)
+                } else {
+                    false
+                }
+            }
         }
     }
 
@@ -476,6 +561,7 @@ impl CppItem {
             CppItem::EnumValue(data) => &data.path,
             CppItem::Function(data) => &data.path,
             CppItem::ClassField(data) => &data.path,
+            CppItem::TypeAlias(data) => &data.path,
             CppItem::ClassBase(_) => return None,
         };
         Some(path)
@@ -487,7 +573,9 @@ impl CppItem {
                 CppTypeDeclarationKind::Enum => vec![CppType::Enum {
                     path: t.path.clone(),
                 }],
-                CppTypeDeclarationKind::Class { .. } => vec![CppType::Class(t.path.clone())],
+                CppTypeDeclarationKind::Class { .. } | CppTypeDeclarationKind::Union => {
+                    vec![CppType::Class(t.path.clone())]
+                }
             },
             CppItem::EnumValue(enum_value) => vec![CppType::Enum {
                 path: enum_value
@@ -506,6 +594,7 @@ impl CppItem {
                 CppType::Class(base.base_class_type.clone()),
                 CppType::Class(base.derived_class_type.clone()),
             ],
+            CppItem::TypeAlias(alias) => vec![alias.target_type.clone()],
         }
     }
 
@@ -551,6 +640,13 @@ impl CppItem {
             None
         }
     }
+    pub fn as_type_alias_ref(&self) -> Option<&CppTypeAlias> {
+        if let CppItem::TypeAlias(data) = self {
+            Some(data)
+        } else {
+            None
+        }
+    }
     pub fn as_type_mut(&mut self) -> Option<&mut CppTypeDeclaration> {
         if let CppItem::Type(data) = self {
             Some(data)
@@ -575,6 +671,9 @@ impl fmt::Display for CppItem {
                 CppTypeDeclarationKind::Class { .. } => {
                     format!("class {}", type1.path.to_cpp_pseudo_code())
                 }
+                CppTypeDeclarationKind::Union => {
+                    format!("union {}", type1.path.to_cpp_pseudo_code())
+                }
             },
             CppItem::Function(method) => method.short_text(),
             CppItem::EnumValue(value) => format!(
@@ -608,6 +707,11 @@ impl fmt::Display for CppItem {
                     index_text
                 )
             }
+            CppItem::TypeAlias(alias) => format!(
+                "using {} = {}",
+                alias.path.to_cpp_pseudo_code(),
+                alias.target_type.to_cpp_pseudo_code()
+            ),
         };
 
         f.write_str(&s)
@@ -31,6 +31,12 @@ pub enum CppCast {
         base_index: Option<usize>,
     },
     Dynamic,
+    /// `dynamic_cast` between two polymorphic classes that are not derived
+    /// from each other but have a common derived class.
+    CrossCast,
+    /// `dynamic_cast<void*>` that returns the address of the most derived object
+    /// of a polymorphic class.
+    MostDerived,
     QObject,
 }
 
@@ -38,7 +44,7 @@ impl CppCast {
     pub fn cpp_method_name(&self) -> &'static str {
         match *self {
             CppCast::Static { .. } => "static_cast",
-            CppCast::Dynamic => "dynamic_cast",
+            CppCast::Dynamic | CppCast::CrossCast | CppCast::MostDerived => "dynamic_cast",
             CppCast::QObject => "qobject_cast",
         }
     }
@@ -69,6 +75,9 @@ pub enum CppFfiFunctionKind {
         /// Type of the accessor
         accessor_type: CppFieldAccessorType,
     },
+    /// This is a non-existing function that returns an opaque pointer to
+    /// a constant holding the pointer to the source member function.
+    MemberPointerGetter,
 }
 
 /// Relation between original C++ method's argument value
@@ -80,7 +89,7 @@ pub enum CppToFfiTypeConversion {
     /// C++ argument is a class value (like QPoint)
     /// and FFI argument is a pointer (like QPoint*)
     ValueToPointer { is_ffi_const: bool },
-    /// C++ argument is a reference (like QPoint&)
+    /// C++ argument is a reference (like QPoint& or QPoint&&)
     /// and FFI argument is a pointer (like QPoint*)
     ReferenceToPointer,
     /// C++ argument is QFlags<T>
@@ -88,6 +97,12 @@ pub enum CppToFfiTypeConversion {
     QFlagsToInt,
     /// Implicit conversion is used.
     ImplicitCast { ffi_type: CppType },
+    /// C++ argument is a fixed-size array (like int[16]) or a reference to it
+    /// and FFI argument is a pointer to its first element (like int*)
+    ArrayToPointer { is_ffi_const: bool },
+    /// C++ argument is a pointer to member (like `int (QWidget::*)()`)
+    /// and FFI argument is an opaque pointer to a value of that type (`const void*`)
+    MemberPointerToVoidPointer,
 }
 
 /// Information that indicates how an FFI function argument
@@ -162,6 +177,10 @@ pub struct CppFfiFunction {
     pub path: CppPath,
 
     pub kind: CppFfiFunctionKind,
+
+    /// True if `path` is the original C function that can be called
+    /// from Rust directly, without a wrapper function in the C++ library
+    pub is_direct_call: bool,
 }
 
 impl CppFfiFunction {
@@ -198,6 +217,9 @@ impl CppFfiFunction {
                     false
                 }
             }
+            CppFfiFunctionKind::MemberPointerGetter => {
+                other.kind == CppFfiFunctionKind::MemberPointerGetter
+            }
         }
     }
 }
@@ -241,6 +263,32 @@ impl CppFfiType {
                 original_type,
                 conversion,
             }),
+            CppToFfiTypeConversion::ArrayToPointer { is_ffi_const } => {
+                let array_type = if let CppType::PointerLike { target, .. } = &original_type {
+                    target
+                } else {
+                    &original_type
+                };
+                let (element_type, dimensions) = array_type.array_dimensions();
+                if dimensions.is_empty() {
+                    bail!("not an array type: {:?}", original_type);
+                }
+                Ok(CppFfiType {
+                    ffi_type: CppType::new_pointer(is_ffi_const, element_type.clone()),
+                    original_type,
+                    conversion,
+                })
+            }
+            CppToFfiTypeConversion::MemberPointerToVoidPointer => {
+                if !original_type.is_member_pointer() {
+                    bail!("not a member pointer type: {:?}", original_type);
+                }
+                Ok(CppFfiType {
+                    ffi_type: CppType::new_pointer(true, CppType::Void),
+                    original_type,
+                    conversion,
+                })
+            }
             CppToFfiTypeConversion::ImplicitCast { ffi_type } => Ok(CppFfiType {
                 ffi_type,
                 original_type,
@@ -284,11 +332,45 @@ pub struct QtSlotWrapper {
     pub function_type: CppFunctionPointerType,
 }
 
+/// Information about a virtual method forwarded to a Rust callback
+/// by a generated subclass
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CppOverriddenMethod {
+    /// Name of the virtual method
+    pub name: String,
+    /// Name unique within the subclass, used for names of the callback setter
+    /// (`set_<name>_callback`) and the base implementation caller (`base_<name>`)
+    pub unique_name: String,
+    /// True if this is a const method
+    pub is_const: bool,
+    /// True if the method doesn't have a base class implementation
+    pub is_pure_virtual: bool,
+    /// Arguments of the method
+    pub arguments: Vec<CppFfiType>,
+    /// Return type of the method
+    pub return_type: CppFfiType,
+    /// The function pointer type of the callback
+    pub function_type: CppFunctionPointerType,
+}
+
+/// Information about a generated subclass that allows to
+/// override virtual methods of a class from Rust
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CppOverrider {
+    /// Class that has the virtual methods
+    pub base_class_path: CppPath,
+    /// Generated name of the subclass
+    pub class_path: CppPath,
+    /// Virtual methods of the base class and its bases
+    pub methods: Vec<CppOverriddenMethod>,
+}
+
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum CppFfiItem {
     Function(CppFfiFunction),
     QtSlotWrapper(QtSlotWrapper),
+    Overrider(CppOverrider),
 }
 
 impl CppFfiItem {
@@ -324,6 +406,14 @@ impl CppFfiItem {
         }
     }
 
+    pub fn as_overrider_ref(&self) -> Option<&CppOverrider> {
+        if let CppFfiItem::Overrider(data) = self {
+            Some(data)
+        } else {
+            None
+        }
+    }
+
     pub fn short_text(&self) -> String {
         match self {
             CppFfiItem::Function(function) => function.path.to_cpp_pseudo_code(),
@@ -335,6 +425,10 @@ impl CppFfiItem {
                     .map(CppType::to_cpp_pseudo_code)
                     .join(", ")
             ),
+            CppFfiItem::Overrider(overrider) => format!(
+                "overrider for {}",
+                overrider.base_class_path.to_cpp_pseudo_code()
+            ),
         }
     }
 
@@ -354,6 +448,13 @@ impl CppFfiItem {
                     false
                 }
             }
+            CppFfiItem::Overrider(overrider) => {
+                if let CppFfiItem::Overrider(other_overrider) = other {
+                    overrider.base_class_path == other_overrider.base_class_path
+                } else {
+                    false
+                }
+            }
         }
     }
 
@@ -361,13 +462,14 @@ impl CppFfiItem {
         match self {
             CppFfiItem::Function(f) => &f.path,
             CppFfiItem::QtSlotWrapper(s) => &s.class_path,
+            CppFfiItem::Overrider(s) => &s.class_path,
         }
     }
 
     pub fn is_source_item(&self) -> bool {
         match self {
             CppFfiItem::Function(_) => false,
-            CppFfiItem::QtSlotWrapper(_) => true,
+            CppFfiItem::QtSlotWrapper(_) | CppFfiItem::Overrider(_) => true,
         }
     }
 
@@ -377,6 +479,7 @@ impl CppFfiItem {
             CppFfiItem::QtSlotWrapper(slot_wrapper) => {
                 cpp_code_generator::qt_slot_wrapper(db, slot_wrapper)
             }
+            CppFfiItem::Overrider(overrider) => cpp_code_generator::overrider(db, overrider),
         }
     }
 }
@@ -12,11 +12,16 @@ use crate::cpp_function::{CppFunction, CppFunctionArgument, CppFunctionKind};
 use crate::cpp_type::CppPointerLikeTypeKind;
 use crate::cpp_type::CppType;
 use crate::cpp_type::CppTypeRole;
-use crate::cpp_type::{is_qflags, CppFunctionPointerType};
+use crate::cpp_type::{
+    is_qflags, CppBuiltInNumericType, CppFunctionPointerType, CppSpecificNumericType,
+    CppSpecificNumericTypeKind,
+};
 use crate::processor::ProcessorData;
+use crate::type_allocation_places;
 use itertools::Itertools;
 use log::{debug, trace};
-use ritual_common::errors::{bail, Result, ResultExt};
+use ritual_common::errors::{bail, err_msg, Result, ResultExt};
+use ritual_common::utils::MapIfOk;
 use std::collections::HashSet;
 
 /// Converts this C++ type to its adaptation for FFI interface,
@@ -53,6 +58,18 @@ pub fn ffi_type(original_type: &CppType, role: CppTypeRole) -> Result<CppFfiType
                                  supported"
                             );
                         }
+                        CppType::Array { .. } => {
+                            bail!("Function pointers containing arrays are not supported");
+                        }
+                        CppType::MemberPointer(..) => {
+                            bail!("Function pointers containing member pointers are not supported");
+                        }
+                        CppType::BuiltInNumeric(ref numeric) if numeric.is_opaque_in_rust() => {
+                            bail!(
+                                "Function pointers containing {} by value are not supported",
+                                numeric.to_cpp_code()
+                            );
+                        }
                         _ => {}
                     }
                     if arg.contains_reference() {
@@ -70,11 +87,38 @@ pub fn ffi_type(original_type: &CppType, role: CppTypeRole) -> Result<CppFfiType
                     }
                 }
             }
+            CppType::BuiltInNumeric(numeric) if numeric.is_opaque_in_rust() => {
+                // Rust has no equivalent type, so the value is passed by pointer
+                CppToFfiTypeConversion::ValueToPointer {
+                    is_ffi_const: role != CppTypeRole::ReturnType,
+                }
+            }
+            CppType::Array { .. } => {
+                if role == CppTypeRole::ReturnType {
+                    bail!("arrays can't be returned by value");
+                }
+                check_array_element_type(original_type)?;
+                CppToFfiTypeConversion::ArrayToPointer {
+                    is_ffi_const: false,
+                }
+            }
+            CppType::MemberPointer(..) => {
+                if role == CppTypeRole::ReturnType {
+                    bail!("member pointers can't be returned by value");
+                }
+                CppToFfiTypeConversion::MemberPointerToVoidPointer
+            }
             CppType::PointerLike {
                 kind,
                 is_const,
                 target,
             } => {
+                if target.is_array() && *kind != CppPointerLikeTypeKind::Reference {
+                    bail!("pointers to arrays are not supported");
+                }
+                if target.is_member_pointer() {
+                    bail!("pointers and references to member pointers are not supported");
+                }
                 match *kind {
                     CppPointerLikeTypeKind::Pointer => CppToFfiTypeConversion::NoChange,
                     CppPointerLikeTypeKind::Reference => {
@@ -83,6 +127,12 @@ pub fn ffi_type(original_type: &CppType, role: CppTypeRole) -> Result<CppFfiType
                                 // TODO: use a separate conversion type (QFlagsConstRefToUInt)?
                                 CppToFfiTypeConversion::QFlagsToInt
                             }
+                            CppType::Array { .. } => {
+                                check_array_element_type(target)?;
+                                CppToFfiTypeConversion::ArrayToPointer {
+                                    is_ffi_const: *is_const,
+                                }
+                            }
                             _ => CppToFfiTypeConversion::ReferenceToPointer,
                         }
                     }
@@ -98,6 +148,23 @@ pub fn ffi_type(original_type: &CppType, role: CppTypeRole) -> Result<CppFfiType
     Ok(inner().with_context(|_| format!("Can't express type to FFI: {:?}", original_type))?)
 }
 
+/// Checks that elements of the array type can be accessed through
+/// a pointer to the first element on the Rust side.
+fn check_array_element_type(array_type: &CppType) -> Result<()> {
+    let (element_type, _) = array_type.array_dimensions();
+    match element_type {
+        CppType::BuiltInNumeric(CppBuiltInNumericType::LongDouble) => {
+            bail!("size of long double is platform-dependent");
+        }
+        CppType::BuiltInNumeric(..)
+        | CppType::SpecificNumeric(..)
+        | CppType::PointerSizedInteger { .. }
+        | CppType::Enum { .. }
+        | CppType::PointerLike { .. } => Ok(()),
+        _ => bail!("unsupported array element type: {:?}", element_type),
+    }
+}
+
 pub struct FfiNameProvider {
     names: HashSet<String>,
     prefix: String,
@@ -142,10 +209,37 @@ impl FfiNameProvider {
     }
 }
 
+/// Returns all pointer to member types used by functions and fields
+/// of the library and its dependencies.
+fn used_member_pointer_types(data: &ProcessorData<'_>) -> HashSet<CppType> {
+    let mut result = HashSet::new();
+    let mut add = |type1: &CppType| {
+        let mut current = type1;
+        while !current.is_member_pointer() {
+            current = match current {
+                CppType::PointerLike { target, .. } => target,
+                CppType::Array { element_type, .. } => element_type,
+                _ => return,
+            };
+        }
+        result.insert(current.clone());
+    };
+    for item in data.db.all_cpp_items() {
+        if let Some(function) = item.item.as_function_ref() {
+            for type1 in function.all_involved_types() {
+                add(&type1);
+            }
+        } else if let Some(field) = item.item.as_field_ref() {
+            add(&field.field_type);
+        }
+    }
+    result
+}
+
 /// Runs the FFI generator
 pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
-    // movable types are not supported for now
-    let movable_types = Vec::new();
+    let movable_types = type_allocation_places::movable_types(data)?;
+    let used_member_pointer_types = used_member_pointer_types(data);
 
     let mut name_provider = FfiNameProvider::new(data);
 
@@ -164,9 +258,35 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
             }
         }
         let result = match &item.item {
+            CppItem::Function(function) if data.config.is_c_library() => {
+                if function.member.is_some() {
+                    trace!(
+                        "skipping {} (member functions are not expected in C)",
+                        item.item
+                    );
+                    continue;
+                }
+                to_direct_ffi_function(function).map(|f| vec![CppFfiItem::Function(f)])
+            }
+            CppItem::ClassField(_) if data.config.is_c_library() => {
+                // fields of C structs are accessed directly on the Rust side
+                continue;
+            }
             CppItem::Function(method) => {
-                generate_ffi_methods_for_method(method, &movable_types, &mut name_provider)
-                    .map(|v| v.into_iter().collect_vec())
+                let needs_member_pointer_getter = method.member.is_some()
+                    && !method.is_static_member()
+                    && (data.config.member_pointer_getters().contains(&method.path)
+                        || method
+                            .member_pointer_type()
+                            .ok()
+                            .map_or(false, |t| used_member_pointer_types.contains(&t)));
+                generate_ffi_methods_for_method(
+                    method,
+                    needs_member_pointer_getter,
+                    &movable_types,
+                    &mut name_provider,
+                )
+                .map(|v| v.into_iter().collect_vec())
             }
             CppItem::ClassField(field) => {
                 generate_field_accessors(field, &movable_types, &mut name_provider)
@@ -175,7 +295,8 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
             CppItem::ClassBase(_)
             | CppItem::Type(_)
             | CppItem::EnumValue(_)
-            | CppItem::Namespace(_) => {
+            | CppItem::Namespace(_)
+            | CppItem::TypeAlias(_) => {
                 // no FFI methods for these items
                 continue;
             }
@@ -198,6 +319,7 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
 
 fn generate_ffi_methods_for_method(
     method: &CppFunction,
+    needs_member_pointer_getter: bool,
     movable_types: &[CppPath],
     name_provider: &mut FfiNameProvider,
 ) -> Result<Vec<CppFfiItem>> {
@@ -209,10 +331,43 @@ fn generate_ffi_methods_for_method(
         movable_types,
         name_provider,
     )?));
+    if needs_member_pointer_getter {
+        match member_pointer_getter(method, name_provider) {
+            Ok(getter) => methods.push(CppFfiItem::Function(getter)),
+            Err(err) => trace!(
+                "no member pointer getter for {}: {}",
+                method.short_text(),
+                err
+            ),
+        }
+    }
 
     Ok(methods)
 }
 
+/// Creates FFI function that returns an opaque pointer to a constant
+/// holding the pointer to `method`. The value can be passed to
+/// FFI functions accepting member pointers.
+fn member_pointer_getter(
+    method: &CppFunction,
+    name_provider: &mut FfiNameProvider,
+) -> Result<CppFfiFunction> {
+    let member_pointer_type = method.member_pointer_type()?;
+    // check that the type can be expressed in C++ code
+    member_pointer_type.to_cpp_code(None)?;
+    Ok(CppFfiFunction {
+        arguments: Vec::new(),
+        return_type: CppFfiType::new(
+            member_pointer_type,
+            CppToFfiTypeConversion::MemberPointerToVoidPointer,
+        )?,
+        path: name_provider.create_path(&format!("{}_member_ptr", method.path.ascii_caption())),
+        allocation_place: ReturnValueAllocationPlace::NotApplicable,
+        kind: CppFfiFunctionKind::MemberPointerGetter,
+        is_direct_call: false,
+    })
+}
+
 pub enum NewFfiFunctionKind {
     Function {
         cpp_function: CppFunction,
@@ -261,13 +416,23 @@ pub fn to_ffi_method(
                 CppFfiFunctionKind::FieldAccessor { accessor_type }
             }
         },
+        is_direct_call: false,
     };
 
     let this_arg_type = match &kind {
         NewFfiFunctionKind::Function { cpp_function, .. } => match &cpp_function.member {
             Some(info) if !info.is_static && info.kind != CppFunctionKind::Constructor => {
                 let class_type = CppType::Class(cpp_function.class_path().unwrap());
-                Some(CppType::new_pointer(info.is_const, class_type))
+                if info.ref_qualifier.is_rvalue() {
+                    // `&&`-qualified methods can only be called on an rvalue
+                    Some(CppType::PointerLike {
+                        kind: CppPointerLikeTypeKind::RValueReference,
+                        is_const: info.is_const,
+                        target: Box::new(class_type),
+                    })
+                } else {
+                    Some(CppType::new_pointer(info.is_const, class_type))
+                }
             }
             _ => None,
         },
@@ -289,9 +454,14 @@ pub fn to_ffi_method(
     };
 
     if let Some(this_arg_type) = this_arg_type {
+        let argument_type = if this_arg_type.is_rvalue_reference() {
+            CppFfiType::new(this_arg_type, CppToFfiTypeConversion::ReferenceToPointer)?
+        } else {
+            ffi_type(&this_arg_type, CppTypeRole::NotReturnType)?
+        };
         r.arguments.push(CppFfiFunctionArgument {
             name: "this_ptr".to_string(),
-            argument_type: ffi_type(&this_arg_type, CppTypeRole::NotReturnType)?,
+            argument_type,
             meaning: CppFfiArgumentMeaning::This,
         });
     }
@@ -377,14 +547,81 @@ pub fn to_ffi_method(
                 r.allocation_place = ReturnValueAllocationPlace::Heap;
             }
         }
+        // written to a value provided by the caller
+        CppType::BuiltInNumeric(numeric) if numeric.is_opaque_in_rust() => {
+            r.arguments.push(CppFfiFunctionArgument {
+                name: "output".to_string(),
+                argument_type: real_return_type_ffi,
+                meaning: CppFfiArgumentMeaning::ReturnValue,
+            });
+            r.allocation_place = ReturnValueAllocationPlace::Stack;
+        }
         _ => {
             r.return_type = real_return_type_ffi;
         }
     }
 
+    if let NewFfiFunctionKind::FieldAccessor {
+        field,
+        accessor_type,
+    } = &kind
+    {
+        if let Some(width) = field.bit_field_width {
+            let value_type = bit_field_ffi_type(&field.field_type, width)?;
+            match *accessor_type {
+                CppFieldAccessorType::CopyGetter => r.return_type = value_type,
+                CppFieldAccessorType::Setter => {
+                    let arg = r
+                        .arguments
+                        .iter_mut()
+                        .find(|arg| arg.meaning.is_argument())
+                        .ok_or_else(|| err_msg("setter has no value argument"))?;
+                    arg.argument_type = value_type;
+                }
+                CppFieldAccessorType::ConstRefGetter | CppFieldAccessorType::MutRefGetter => {
+                    bail!("bit-fields can't be accessed by reference");
+                }
+            }
+        }
+    }
+
     Ok(r)
 }
 
+/// Returns FFI type used to get and set value of a bit-field of type `field_type`.
+/// Integer bit-fields are represented by the smallest fixed-size integer
+/// that can hold `width` bits.
+fn bit_field_ffi_type(field_type: &CppType, width: usize) -> Result<CppFfiType> {
+    let is_signed = match field_type {
+        CppType::BuiltInNumeric(t) if t.is_signed_integer() => true,
+        CppType::BuiltInNumeric(t) if t.is_unsigned_integer() => false,
+        CppType::SpecificNumeric(CppSpecificNumericType {
+            kind: CppSpecificNumericTypeKind::Integer { is_signed },
+            ..
+        })
+        | CppType::PointerSizedInteger { is_signed, .. } => *is_signed,
+        // bool, enum and char bit-fields keep their original type
+        _ => return ffi_type(field_type, CppTypeRole::NotReturnType),
+    };
+    let bits = match width {
+        0..=8 => 8,
+        9..=16 => 16,
+        17..=32 => 32,
+        33..=64 => 64,
+        _ => bail!("unsupported bit-field width: {}", width),
+    };
+    let name = format!("{}int{}_t", if is_signed { "" } else { "u" }, bits);
+    let ffi_type = CppType::SpecificNumeric(CppSpecificNumericType {
+        path: CppPath::from_good_str(&name),
+        bits,
+        kind: CppSpecificNumericTypeKind::Integer { is_signed },
+    });
+    CppFfiType::new(
+        field_type.clone(),
+        CppToFfiTypeConversion::ImplicitCast { ffi_type },
+    )
+}
+
 /// Adds fictional getter and setter methods for each known public field of each class.
 fn generate_field_accessors(
     field: &CppClassField,
@@ -404,21 +641,83 @@ fn generate_field_accessors(
     if field.visibility == CppVisibility::Public {
         // Classes may be non-copyable, so copy getters may not be possible for them,
         // so we generate reference getters instead.
-        if field.field_type.is_class() {
+        // Bit-fields are not addressable, so only copy getters and setters are possible.
+        if field.bit_field_width.is_none()
+            && (field.field_type.is_class() || field.field_type.is_array())
+        {
             new_methods.push(create_method(CppFieldAccessorType::ConstRefGetter)?);
             new_methods.push(create_method(CppFieldAccessorType::MutRefGetter)?);
         } else {
             new_methods.push(create_method(CppFieldAccessorType::CopyGetter)?);
         }
-        new_methods.push(create_method(CppFieldAccessorType::Setter)?);
+        // Arrays can't be assigned, so they are modified through the mutable getter.
+        if !field.field_type.is_array() {
+            new_methods.push(create_method(CppFieldAccessorType::Setter)?);
+        }
     }
 
     Ok(new_methods)
 }
 
+/// Converts a type of a C function to its FFI type. Unlike `ffi_type`,
+/// structs are passed by value because they have the same layout on the Rust side.
+fn direct_ffi_type(original_type: &CppType, role: CppTypeRole) -> Result<CppFfiType> {
+    if let CppType::Class(_) = original_type {
+        return CppFfiType::new(original_type.clone(), CppToFfiTypeConversion::NoChange);
+    }
+    let ffi_type = ffi_type(original_type, role)?;
+    match ffi_type.conversion() {
+        CppToFfiTypeConversion::NoChange | CppToFfiTypeConversion::ArrayToPointer { .. } => {
+            Ok(ffi_type)
+        }
+        conversion => bail!(
+            "type {:?} requires conversion incompatible with a direct call: {:?}",
+            original_type,
+            conversion
+        ),
+    }
+}
+
+/// Creates FFI signature for a C function that is called from Rust directly,
+/// without generating a wrapper function.
+pub fn to_direct_ffi_function(function: &CppFunction) -> Result<CppFfiFunction> {
+    if function.member.is_some() {
+        bail!("member functions can't be called directly");
+    }
+    if function.allows_variadic_arguments {
+        bail!("Variable arguments are not supported");
+    }
+    if function.path.items().len() != 1 || function.path.last().template_arguments.is_some() {
+        bail!("only free C functions can be called directly");
+    }
+    let arguments =
+        function
+            .arguments
+            .iter()
+            .enumerate()
+            .map_if_ok(|(index, arg)| -> Result<_> {
+                Ok(CppFfiFunctionArgument {
+                    name: arg.name.clone(),
+                    argument_type: direct_ffi_type(&arg.argument_type, CppTypeRole::NotReturnType)?,
+                    meaning: CppFfiArgumentMeaning::Argument(index),
+                })
+            })?;
+    Ok(CppFfiFunction {
+        arguments,
+        return_type: direct_ffi_type(&function.return_type, CppTypeRole::ReturnType)?,
+        path: function.path.clone(),
+        allocation_place: ReturnValueAllocationPlace::NotApplicable,
+        kind: CppFfiFunctionKind::Function,
+        is_direct_call: true,
+    })
+}
+
 fn check_preconditions(item: &CppItem) -> Result<()> {
     match item {
         CppItem::Function(function) => {
+            if function.is_deleted {
+                bail!("function is deleted");
+            }
             if let Some(membership) = &function.member {
                 if membership.visibility == CppVisibility::Private {
                     bail!("function is private");
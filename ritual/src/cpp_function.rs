@@ -3,7 +3,10 @@
 use crate::cpp_data::{CppPath, CppPathItem, CppVisibility};
 use crate::cpp_ffi_data::CppCast;
 pub use crate::cpp_operator::{CppOperator, CppOperatorInfo};
-use crate::cpp_type::{CppPointerLikeTypeKind, CppType};
+use crate::cpp_type::{
+    CppFunctionPointerType, CppMemberPointerTarget, CppMemberPointerType, CppPointerLikeTypeKind,
+    CppType,
+};
 use crate::rust_info::RustQtReceiverType;
 use itertools::Itertools;
 use ritual_common::errors::{bail, err_msg, Result, ResultExt};
@@ -51,6 +54,35 @@ pub enum CppFunctionKind {
     Destructor,
 }
 
+/// Ref-qualifier of a C++ class member method
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Serialize, Deserialize)]
+pub enum CppRefQualifier {
+    /// The method doesn't have a ref-qualifier
+    None,
+    /// The method is declared with `&` and can only be called on lvalues
+    LValue,
+    /// The method is declared with `&&` and can only be called on rvalues
+    RValue,
+}
+
+impl CppRefQualifier {
+    /// Returns C++ code of the qualifier as it appears after the argument list
+    pub fn to_cpp_code(self) -> &'static str {
+        match self {
+            CppRefQualifier::None => "",
+            CppRefQualifier::LValue => "&",
+            CppRefQualifier::RValue => "&&",
+        }
+    }
+
+    pub fn is_rvalue(self) -> bool {
+        match self {
+            CppRefQualifier::RValue => true,
+            _ => false,
+        }
+    }
+}
+
 /// Information about a C++ class member method
 #[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
 pub struct CppFunctionMemberData {
@@ -71,6 +103,16 @@ pub struct CppFunctionMemberData {
     pub is_signal: bool,
     /// True if the method is a Qt slot
     pub is_slot: bool,
+    /// Ref-qualifier of the method (`&` or `&&` after the argument list)
+    pub ref_qualifier: CppRefQualifier,
+    /// True if the method is explicitly defaulted (`= default`)
+    pub is_defaulted: bool,
+    /// True if the constructor (or conversion operator) is declared `explicit`
+    pub is_explicit: bool,
+    /// If the method is a forwarding method added for a method of a non-primary
+    /// base class, contains path of the base class that declares the method.
+    #[serde(default)]
+    pub inherited_from: Option<CppPath>,
 }
 
 impl CppFunctionMemberData {
@@ -78,6 +120,7 @@ impl CppFunctionMemberData {
         self.kind == other.kind
             && self.is_const == other.is_const
             && self.is_static == other.is_static
+            && self.ref_qualifier == other.ref_qualifier
     }
 }
 
@@ -118,6 +161,15 @@ pub struct CppFunction {
     /// Whether the argument list is terminated with "..."
     pub allows_variadic_arguments: bool,
     pub cast: Option<CppCast>,
+    /// True if the function can't throw exceptions
+    /// (declared with `noexcept` or `throw()`)
+    pub is_noexcept: bool,
+    /// True if the function is explicitly deleted (`= delete`)
+    pub is_deleted: bool,
+    /// True if the function is declared as a friend inside a class body.
+    /// Such functions may only be visible through argument-dependent lookup,
+    /// so they must be called without a namespace qualifier.
+    pub is_friend: bool,
     /// C++ code of the method's declaration.
     /// None if the method was not explicitly declared.
     pub declaration_code: Option<String>,
@@ -213,6 +265,39 @@ impl CppFunction {
         }
     }
 
+    /// Returns type of a pointer to this member function,
+    /// like `int (QWidget::*)(bool) const`.
+    pub fn member_pointer_type(&self) -> Result<CppType> {
+        let info = self
+            .member
+            .as_ref()
+            .ok_or_else(|| err_msg("not a member function"))?;
+        if info.is_static {
+            bail!("pointers to static member functions are regular function pointers");
+        }
+        if info.kind != CppFunctionKind::Regular {
+            bail!("can't take address of a constructor or a destructor");
+        }
+        if info.ref_qualifier != CppRefQualifier::None {
+            bail!("ref-qualified member function pointers are not supported");
+        }
+        Ok(CppType::MemberPointer(CppMemberPointerType {
+            class_path: self.class_path()?,
+            target: CppMemberPointerTarget::Method {
+                function_type: CppFunctionPointerType {
+                    return_type: Box::new(self.return_type.clone()),
+                    arguments: self
+                        .arguments
+                        .iter()
+                        .map(|arg| arg.argument_type.clone())
+                        .collect(),
+                    allows_variadic_arguments: self.allows_variadic_arguments,
+                },
+                is_const: info.is_const,
+            },
+        }))
+    }
+
     pub fn pseudo_declaration(&self) -> String {
         let mut s = String::new();
         if let Some(info) = &self.member {
@@ -222,6 +307,9 @@ impl CppFunction {
             if info.is_static {
                 write!(s, " static").unwrap();
             }
+            if info.is_explicit {
+                write!(s, " explicit").unwrap();
+            }
             if info.visibility == CppVisibility::Protected {
                 write!(s, " protected").unwrap();
             }
@@ -255,6 +343,15 @@ impl CppFunction {
             if info.is_const {
                 write!(s, " const").unwrap();
             }
+            if info.ref_qualifier != CppRefQualifier::None {
+                write!(s, " {}", info.ref_qualifier.to_cpp_code()).unwrap();
+            }
+        }
+        if self.is_noexcept {
+            write!(s, " noexcept").unwrap();
+        }
+        if self.is_deleted {
+            write!(s, " = delete").unwrap();
         }
         s.trim().to_string()
     }
@@ -274,6 +371,9 @@ impl CppFunction {
             if info.is_static {
                 s = format!("{} static", s);
             }
+            if info.is_explicit {
+                s = format!("{} explicit", s);
+            }
             if info.visibility == CppVisibility::Protected {
                 s = format!("{} protected", s);
             }
@@ -318,6 +418,18 @@ impl CppFunction {
             if info.is_const {
                 s = format!("{} const", s);
             }
+            if info.ref_qualifier != CppRefQualifier::None {
+                s = format!("{} {}", s, info.ref_qualifier.to_cpp_code());
+            }
+            if info.is_defaulted {
+                s = format!("{} = default", s);
+            }
+        }
+        if self.is_noexcept {
+            s = format!("{} noexcept", s);
+        }
+        if self.is_deleted {
+            s = format!("{} = delete", s);
         }
         s.trim().to_string()
     }
@@ -341,6 +453,37 @@ impl CppFunction {
         arg == self.arguments[0].argument_type
     }
 
+    /// Returns true if this is a non-explicit single-argument constructor
+    /// that allows implicit conversion from its argument type to the class
+    /// (excluding copy and move constructors).
+    pub fn is_converting_constructor(&self) -> bool {
+        let info = match &self.member {
+            Some(info) => info,
+            None => return false,
+        };
+        if !info.kind.is_constructor() || info.is_explicit || self.arguments.len() != 1 {
+            return false;
+        }
+        let class_type = match self.class_path() {
+            Ok(path) => CppType::Class(path),
+            Err(_) => return false,
+        };
+        let source_type = match &self.arguments[0].argument_type {
+            CppType::PointerLike {
+                kind: CppPointerLikeTypeKind::Reference,
+                target,
+                ..
+            }
+            | CppType::PointerLike {
+                kind: CppPointerLikeTypeKind::RValueReference,
+                target,
+                ..
+            } => &**target,
+            other => other,
+        };
+        source_type != &class_type
+    }
+
     /// Returns true if this method is a destructor.
     pub fn is_destructor(&self) -> bool {
         match &self.member {
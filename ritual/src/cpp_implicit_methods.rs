@@ -1,18 +1,457 @@
-use crate::cpp_data::{CppItem, CppPathItem, CppVisibility};
+use crate::cpp_data::{
+    CppBaseSpecifier, CppClassField, CppItem, CppPath, CppPathItem, CppTypeDeclaration,
+    CppVisibility,
+};
 use crate::cpp_function::{
-    CppFunction, CppFunctionArgument, CppFunctionKind, CppFunctionMemberData,
+    CppFunction, CppFunctionArgument, CppFunctionKind, CppFunctionMemberData, CppRefQualifier,
 };
 use crate::cpp_operator::CppOperator;
-use crate::cpp_type::CppType;
-use crate::database::ItemWithSource;
+use crate::cpp_type::{CppPointerLikeTypeKind, CppType};
+use crate::database::{DatabaseClient, ItemWithSource};
 use crate::processor::ProcessorData;
+use log::trace;
 use ritual_common::errors::Result;
+use std::collections::HashMap;
+
+/// Special member functions that can be implicitly declared by the compiler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SpecialMemberKind {
+    Destructor,
+    DefaultConstructor,
+    CopyConstructor,
+    CopyAssignment,
+}
+
+impl SpecialMemberKind {
+    pub fn all() -> &'static [SpecialMemberKind] {
+        &[
+            SpecialMemberKind::Destructor,
+            SpecialMemberKind::DefaultConstructor,
+            SpecialMemberKind::CopyConstructor,
+            SpecialMemberKind::CopyAssignment,
+        ]
+    }
+
+    /// Returns kind of `function` if it has the signature of an implicitly declared
+    /// special member function of its class.
+    pub fn of_function(function: &CppFunction) -> Option<SpecialMemberKind> {
+        let class_path = function.class_path().ok()?;
+        let is_copy_argument = |function: &CppFunction| {
+            function.arguments.len() == 1
+                && function.arguments[0].argument_type
+                    == CppType::new_reference(true, CppType::Class(class_path.clone()))
+        };
+        if function.is_destructor() {
+            Some(SpecialMemberKind::Destructor)
+        } else if function.is_constructor() {
+            if function.arguments.is_empty() {
+                Some(SpecialMemberKind::DefaultConstructor)
+            } else if is_copy_argument(function) {
+                Some(SpecialMemberKind::CopyConstructor)
+            } else {
+                None
+            }
+        } else if function.operator == Some(CppOperator::Assignment) && is_copy_argument(function) {
+            Some(SpecialMemberKind::CopyAssignment)
+        } else {
+            None
+        }
+    }
+
+    fn is_constructor(self) -> bool {
+        match self {
+            SpecialMemberKind::DefaultConstructor | SpecialMemberKind::CopyConstructor => true,
+            SpecialMemberKind::Destructor | SpecialMemberKind::CopyAssignment => false,
+        }
+    }
+}
+
+/// Predicted availability of a special member function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Availability {
+    /// The function is certainly declared and can be called.
+    Available,
+    /// The function is certainly not declared, deleted or inaccessible.
+    Unavailable,
+    /// There is not enough information, so the function must be checked by compiling it.
+    Uncertain,
+}
+
+impl Availability {
+    /// Returns availability of a function that requires both `self` and `other`.
+    fn and(self, other: Availability) -> Availability {
+        match (self, other) {
+            (Availability::Unavailable, _) | (_, Availability::Unavailable) => {
+                Availability::Unavailable
+            }
+            (Availability::Uncertain, _) | (_, Availability::Uncertain) => Availability::Uncertain,
+            _ => Availability::Available,
+        }
+    }
+}
+
+/// Predicts which special member functions are implicitly declared by the compiler
+/// based on the parsed declarations of classes, their bases and fields.
+pub struct ImplicitMethodsPredictor<'a> {
+    types: HashMap<&'a CppPath, &'a CppTypeDeclaration>,
+    bases: HashMap<&'a CppPath, Vec<&'a CppBaseSpecifier>>,
+    fields: HashMap<CppPath, Vec<&'a CppClassField>>,
+    cache: HashMap<(CppPath, SpecialMemberKind, bool), Availability>,
+}
+
+impl<'a> ImplicitMethodsPredictor<'a> {
+    pub fn new(db: &'a DatabaseClient) -> Self {
+        let mut types = HashMap::new();
+        let mut bases = HashMap::<_, Vec<_>>::new();
+        let mut fields = HashMap::<_, Vec<_>>::new();
+        for item in db.all_cpp_items() {
+            match item.item {
+                CppItem::Type(type1) => {
+                    types.insert(&type1.path, type1);
+                }
+                CppItem::ClassBase(base) => {
+                    bases
+                        .entry(&base.derived_class_type)
+                        .or_default()
+                        .push(base);
+                }
+                CppItem::ClassField(field) if !field.is_static => {
+                    if let Ok(parent) = field.path.parent() {
+                        fields.entry(parent).or_default().push(field);
+                    }
+                }
+                _ => {}
+            }
+        }
+        ImplicitMethodsPredictor {
+            types,
+            bases,
+            fields,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Predicts whether the implicitly declared `kind` function of `class_path`
+    /// can be called from outside of the class. If the class has a user-declared
+    /// function of this kind, it's reported as uncertain unless it's deleted or not public.
+    pub fn predict(&mut self, class_path: &CppPath, kind: SpecialMemberKind) -> Availability {
+        let special_members = match self
+            .types
+            .get(class_path)
+            .and_then(|t| t.special_members.as_ref())
+        {
+            Some(special_members) => special_members,
+            None => return Availability::Uncertain,
+        };
+        if kind.is_constructor() {
+            // abstract classes can't be constructed
+            if special_members.has_pure_virtual_methods {
+                return Availability::Unavailable;
+            }
+            if self.has_abstract_bases(class_path, 0) {
+                return Availability::Uncertain;
+            }
+        }
+        if declaration(special_members, kind).is_some() {
+            // the parsed declaration is used instead unless it can't be called
+            match self.member(class_path, kind, false) {
+                Availability::Unavailable => Availability::Unavailable,
+                _ => Availability::Uncertain,
+            }
+        } else {
+            self.implicit(class_path, kind)
+        }
+    }
+
+    /// Returns true if any of the direct or indirect bases of the class has pure
+    /// virtual methods. These methods may be overridden by the class, so it's not
+    /// clear whether the class is abstract.
+    fn has_abstract_bases(&self, class_path: &CppPath, depth: usize) -> bool {
+        if depth > 32 {
+            return true;
+        }
+        self.bases.get(class_path).map_or(false, |bases| {
+            bases.iter().any(|base| {
+                match self
+                    .types
+                    .get(&base.base_class_type)
+                    .and_then(|t| t.special_members.as_ref())
+                {
+                    Some(special_members) => {
+                        special_members.has_pure_virtual_methods
+                            || self.has_abstract_bases(&base.base_class_type, depth + 1)
+                    }
+                    None => true,
+                }
+            })
+        })
+    }
+
+    /// Availability of `kind` function of `class_path` (user-declared or implicit)
+    /// for a derived class (if `from_derived` is true) or for any other code.
+    fn member(
+        &mut self,
+        class_path: &CppPath,
+        kind: SpecialMemberKind,
+        from_derived: bool,
+    ) -> Availability {
+        let key = (class_path.clone(), kind, from_derived);
+        if let Some(value) = self.cache.get(&key) {
+            return *value;
+        }
+        // protects against recursion in invalid or unusual class hierarchies
+        self.cache.insert(key.clone(), Availability::Uncertain);
+
+        let special_members = match self
+            .types
+            .get(class_path)
+            .and_then(|t| t.special_members.as_ref())
+        {
+            Some(special_members) => special_members,
+            None => return Availability::Uncertain,
+        };
+        let result = if let Some(declaration) = declaration(special_members, kind) {
+            let is_accessible = match declaration.visibility {
+                CppVisibility::Public => true,
+                CppVisibility::Protected => from_derived,
+                CppVisibility::Private => false,
+            };
+            if declaration.is_deleted || !is_accessible {
+                Availability::Unavailable
+            } else if !declaration.has_usual_signature {
+                Availability::Uncertain
+            } else if declaration.is_defaulted {
+                // explicitly defaulted functions are deleted under the same conditions
+                // as implicit ones
+                self.implicit_with_members(class_path, kind)
+            } else {
+                Availability::Available
+            }
+        } else {
+            self.implicit(class_path, kind)
+        };
+        trace!(
+            "predicted {:?} of {} (from_derived = {}): {:?}",
+            kind,
+            class_path.to_cpp_pseudo_code(),
+            from_derived,
+            result
+        );
+        self.cache.insert(key, result);
+        result
+    }
+
+    /// Availability of `kind` function of `class_path` that is not user-declared.
+    fn implicit(&mut self, class_path: &CppPath, kind: SpecialMemberKind) -> Availability {
+        let special_members = self.types[class_path].special_members.as_ref().unwrap();
+        match kind {
+            SpecialMemberKind::DefaultConstructor if special_members.has_constructors => {
+                return Availability::Unavailable;
+            }
+            SpecialMemberKind::CopyConstructor | SpecialMemberKind::CopyAssignment
+                if special_members.has_move_operations =>
+            {
+                return Availability::Unavailable;
+            }
+            _ => {}
+        }
+        self.implicit_with_members(class_path, kind)
+    }
+
+    /// Availability of implicitly defined `kind` function of `class_path`
+    /// based on its bases and fields.
+    fn implicit_with_members(
+        &mut self,
+        class_path: &CppPath,
+        kind: SpecialMemberKind,
+    ) -> Availability {
+        let type1 = self.types[class_path];
+        let special_members = type1.special_members.as_ref().unwrap();
+        if special_members.has_unknown_members {
+            return Availability::Uncertain;
+        }
+        let mut result = match kind {
+            SpecialMemberKind::Destructor => Availability::Available,
+            SpecialMemberKind::DefaultConstructor => {
+                // reference and const fields may have default member initializers
+                if special_members.has_reference_fields || special_members.has_const_fields {
+                    Availability::Uncertain
+                } else {
+                    Availability::Available
+                }
+            }
+            SpecialMemberKind::CopyConstructor => {
+                // rvalue reference fields can't be copied
+                if special_members.has_reference_fields {
+                    Availability::Uncertain
+                } else {
+                    Availability::Available
+                }
+            }
+            SpecialMemberKind::CopyAssignment => {
+                if special_members.has_reference_fields || special_members.has_const_fields {
+                    Availability::Unavailable
+                } else {
+                    Availability::Available
+                }
+            }
+        };
+
+        let bases = self.bases.get(class_path).cloned().unwrap_or_default();
+        for base in bases {
+            result = result.and(self.subobject(&base.base_class_type, kind, true));
+        }
+
+        let fields = self.fields.get(class_path).cloned().unwrap_or_default();
+        for field in fields {
+            let (element_type, _) = field.field_type.array_dimensions();
+            match element_type {
+                CppType::Class(path) => {
+                    if type1.kind.is_union() {
+                        // special members of unions with non-trivial members are deleted
+                        result = result.and(Availability::Uncertain);
+                        continue;
+                    }
+                    let mut availability = self.subobject(path, kind, false);
+                    if kind == SpecialMemberKind::DefaultConstructor
+                        && availability == Availability::Unavailable
+                    {
+                        // the field may have a default member initializer
+                        availability = Availability::Uncertain;
+                    }
+                    result = result.and(availability);
+                }
+                CppType::PointerLike {
+                    kind: CppPointerLikeTypeKind::RValueReference,
+                    ..
+                } => {
+                    if kind == SpecialMemberKind::CopyConstructor {
+                        result = Availability::Unavailable;
+                    }
+                }
+                CppType::TemplateParameter(_) => {
+                    result = result.and(Availability::Uncertain);
+                }
+                _ => {}
+            }
+        }
+        result
+    }
+
+    /// Availability of `kind` function of a base or a field of class type `path`.
+    /// Constructors of subobjects also require their destructors.
+    fn subobject(
+        &mut self,
+        path: &CppPath,
+        kind: SpecialMemberKind,
+        from_derived: bool,
+    ) -> Availability {
+        let mut result = self.member(path, kind, from_derived);
+        if kind.is_constructor() {
+            result = result.and(self.member(path, SpecialMemberKind::Destructor, from_derived));
+        }
+        result
+    }
+}
+
+fn declaration(
+    special_members: &crate::cpp_data::CppClassSpecialMembers,
+    kind: SpecialMemberKind,
+) -> Option<&crate::cpp_data::CppSpecialMemberDeclaration> {
+    match kind {
+        SpecialMemberKind::Destructor => special_members.destructor.as_ref(),
+        SpecialMemberKind::DefaultConstructor => special_members.default_constructor.as_ref(),
+        SpecialMemberKind::CopyConstructor => special_members.copy_constructor.as_ref(),
+        SpecialMemberKind::CopyAssignment => special_members.copy_assignment.as_ref(),
+    }
+}
+
+/// Returns implicitly declared `kind` function of the class `class_path`.
+fn implicit_method(class_path: &CppPath, kind: SpecialMemberKind) -> CppFunction {
+    let copy_arg = CppFunctionArgument {
+        argument_type: CppType::new_reference(true, CppType::Class(class_path.clone())),
+        name: "other".to_string(),
+        has_default_value: false,
+    };
+    let constructor_path = class_path.join(CppPathItem::from_good_str(&class_path.last().name));
+    let (path, function_kind, operator, return_type, arguments) = match kind {
+        SpecialMemberKind::Destructor => (
+            class_path.join(CppPathItem::from_good_str(&format!(
+                "~{}",
+                class_path.last().name
+            ))),
+            CppFunctionKind::Destructor,
+            None,
+            CppType::Void,
+            vec![],
+        ),
+        SpecialMemberKind::DefaultConstructor => (
+            constructor_path,
+            CppFunctionKind::Constructor,
+            None,
+            CppType::Void,
+            vec![],
+        ),
+        SpecialMemberKind::CopyConstructor => (
+            constructor_path,
+            CppFunctionKind::Constructor,
+            None,
+            CppType::Void,
+            vec![copy_arg],
+        ),
+        SpecialMemberKind::CopyAssignment => (
+            class_path.join(CppPathItem::from_good_str("operator=")),
+            CppFunctionKind::Regular,
+            Some(CppOperator::Assignment),
+            CppType::new_reference(false, CppType::Class(class_path.clone())),
+            vec![copy_arg],
+        ),
+    };
+    CppFunction {
+        path,
+        member: Some(CppFunctionMemberData {
+            is_virtual: false, // the destructor can actually be virtual but we don't care about it here
+            is_pure_virtual: false,
+            is_const: false,
+            is_static: false,
+            visibility: CppVisibility::Public,
+            is_signal: false,
+            is_slot: false,
+            ref_qualifier: CppRefQualifier::None,
+            is_defaulted: false,
+            is_explicit: false,
+            inherited_from: None,
+            kind: function_kind,
+        }),
+        operator,
+        return_type,
+        arguments,
+        allows_variadic_arguments: false,
+        cast: None,
+        is_noexcept: false,
+        is_deleted: false,
+        is_friend: false,
+        declaration_code: None,
+    }
+}
 
 /// Adds constructors and destructors for every class that does not have explicitly
 /// defined constructor or destructor, allowing to create wrappings for
 /// constructors and destructors implicitly available in C++.
+///
+/// Functions that are predicted to be unavailable (e.g. constructors of abstract classes
+/// or copy constructors of classes with non-copyable fields) are not added.
+/// The checker doesn't compile functions predicted to be available unless
+/// their availability can't be confirmed by other items of the class.
+/// Such checks are marked with `CppChecksItem::is_predicted`.
 pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
+    if data.config.is_c_library() {
+        // C structs don't have constructors or destructors
+        return Ok(());
+    }
+
     let mut methods = Vec::new();
+    let mut predictor = ImplicitMethodsPredictor::new(data.db);
 
     let classes = data
         .db
@@ -21,110 +460,20 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
         .filter(|item| item.item.kind.is_class());
 
     for type1 in classes {
-        if type1.item.kind.is_class() {
-            let class_path = &type1.item.path;
-
-            let destructor = CppFunction {
-                path: class_path.join(CppPathItem::from_good_str(&format!(
-                    "~{}",
-                    class_path.last().name
-                ))),
-                member: Some(CppFunctionMemberData {
-                    is_virtual: false, // the destructor can actually be virtual but we don't care about it here
-                    is_pure_virtual: false,
-                    is_const: false,
-                    is_static: false,
-                    visibility: CppVisibility::Public,
-                    is_signal: false,
-                    is_slot: false,
-                    kind: CppFunctionKind::Destructor,
-                }),
-                operator: None,
-                return_type: CppType::Void,
-                arguments: vec![],
-                allows_variadic_arguments: false,
-                cast: None,
-                declaration_code: None,
-            };
-            methods.push(ItemWithSource::new(&type1.id, destructor));
-
-            let default_constructor = CppFunction {
-                path: type1
-                    .item
-                    .path
-                    .join(CppPathItem::from_good_str(&class_path.last().name)),
-                member: Some(CppFunctionMemberData {
-                    is_virtual: false,
-                    is_pure_virtual: false,
-                    is_const: false,
-                    is_static: false,
-                    visibility: CppVisibility::Public,
-                    is_signal: false,
-                    is_slot: false,
-                    kind: CppFunctionKind::Constructor,
-                }),
-                operator: None,
-                return_type: CppType::Void,
-                arguments: vec![],
-                allows_variadic_arguments: false,
-                declaration_code: None,
-                cast: None,
-            };
-            methods.push(ItemWithSource::new(&type1.id, default_constructor));
-
-            let copy_arg = CppFunctionArgument {
-                argument_type: CppType::new_reference(true, CppType::Class(class_path.clone())),
-                name: "other".to_string(),
-                has_default_value: false,
-            };
-
-            let copy_constructor = CppFunction {
-                path: type1
-                    .item
-                    .path
-                    .join(CppPathItem::from_good_str(&class_path.last().name)),
-                member: Some(CppFunctionMemberData {
-                    is_virtual: false,
-                    is_pure_virtual: false,
-                    is_const: false,
-                    is_static: false,
-                    visibility: CppVisibility::Public,
-                    is_signal: false,
-                    is_slot: false,
-                    kind: CppFunctionKind::Constructor,
-                }),
-                operator: None,
-                return_type: CppType::Void,
-                arguments: vec![copy_arg.clone()],
-                allows_variadic_arguments: false,
-                cast: None,
-                declaration_code: None,
-            };
-            methods.push(ItemWithSource::new(&type1.id, copy_constructor));
-
-            let assignment_operator = CppFunction {
-                path: type1
-                    .item
-                    .path
-                    .join(CppPathItem::from_good_str("operator=")),
-                member: Some(CppFunctionMemberData {
-                    is_virtual: false,
-                    is_pure_virtual: false,
-                    is_const: false,
-                    is_static: false,
-                    visibility: CppVisibility::Public,
-                    is_signal: false,
-                    is_slot: false,
-                    kind: CppFunctionKind::Regular,
-                }),
-                operator: Some(CppOperator::Assignment),
-                return_type: CppType::new_reference(false, CppType::Class(class_path.clone())),
-                arguments: vec![copy_arg],
-                allows_variadic_arguments: false,
-                cast: None,
-                declaration_code: None,
-            };
-            methods.push(ItemWithSource::new(&type1.id, assignment_operator));
+        let class_path = &type1.item.path;
+        for &kind in SpecialMemberKind::all() {
+            if predictor.predict(class_path, kind) == Availability::Unavailable {
+                trace!(
+                    "skipping unavailable implicit {:?} of {}",
+                    kind,
+                    class_path.to_cpp_pseudo_code()
+                );
+                continue;
+            }
+            methods.push(ItemWithSource::new(
+                &type1.id,
+                implicit_method(class_path, kind),
+            ));
         }
     }
     for item in methods {
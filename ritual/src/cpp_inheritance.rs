@@ -1,13 +1,14 @@
-#![allow(dead_code)]
-
-use crate::cpp_data::{CppPath, CppVisibility};
-use crate::cpp_function::CppFunction;
+use crate::cpp_data::{CppBaseSpecifier, CppItem, CppPath, CppTypeDeclaration, CppVisibility};
+use crate::cpp_function::{CppFunction, CppFunctionKind};
+use crate::cpp_type::CppType;
+use crate::database::{DbItem, ItemWithSource};
 use crate::processor::ProcessorData;
-use itertools::Itertools;
 use log::trace;
-use ritual_common::errors::*;
+use ritual_common::errors::Result;
+use std::collections::{HashMap, HashSet};
 
 /// Checks if `class_name` types inherits `base_name` type directly or indirectly.
+#[allow(dead_code)]
 pub fn inherits(class_name: &CppPath, base_name: &CppPath, data: &ProcessorData<'_>) -> bool {
     let bases = data
         .db
@@ -28,53 +29,216 @@ pub fn inherits(class_name: &CppPath, base_name: &CppPath, data: &ProcessorData<
     false
 }
 
-fn detect_inherited_methods2(data: &ProcessorData<'_>) -> Result<Vec<CppFunction>> {
-    let mut remaining_classes = data
-        .db
-        .all_cpp_items()
-        .filter_map(|item| item.item.as_base_ref())
-        .filter(|b| b.visibility != CppVisibility::Private)
-        .collect_vec();
+/// Returns true if `function` of a base class should be made available
+/// as a forwarding method of derived classes.
+fn can_forward(function: &CppFunction) -> bool {
+    let member = match &function.member {
+        Some(member) => member,
+        None => return false,
+    };
+    member.kind == CppFunctionKind::Regular
+        && member.visibility == CppVisibility::Public
+        && !member.is_static
+        && !member.is_signal
+        && !member.ref_qualifier.is_rvalue()
+        && member.inherited_from.is_none()
+        && function.operator.is_none()
+        && !function.is_deleted
+        && !function
+            .all_involved_types()
+            .iter()
+            .any(CppType::is_or_contains_template_parameter)
+}
+
+/// Returns true if forwarding methods are added to `type1` by `run`.
+fn has_forwarding_methods(type1: &CppTypeDeclaration) -> bool {
+    type1.kind.is_class() && !CppType::Class(type1.path.clone()).is_or_contains_template_parameter()
+}
+
+/// Adds all direct and indirect public bases of `class_path` to `visited`.
+fn mark_bases_visited(
+    class_path: &CppPath,
+    bases: &HashMap<&CppPath, Vec<&CppBaseSpecifier>>,
+    visited: &mut HashSet<CppPath>,
+) {
+    for base in bases.get(class_path).into_iter().flatten() {
+        if base.visibility == CppVisibility::Public && visited.insert(base.base_class_type.clone())
+        {
+            mark_bases_visited(&base.base_class_type, bases, visited);
+        }
+    }
+}
+
+/// Adds to `result` public bases of `class_path` that are not reachable through
+/// nested `Deref` implementations, in depth-first order.
+///
+/// Secondary bases of a primary base are reachable through forwarding methods
+/// of the primary base itself, so they are only added if the primary base
+/// is not in `forwarding_classes`.
+fn collect_secondary_bases(
+    class_path: &CppPath,
+    is_primary: bool,
+    bases: &HashMap<&CppPath, Vec<&CppBaseSpecifier>>,
+    forwarding_classes: &HashSet<&CppPath>,
+    visited: &mut HashSet<CppPath>,
+    result: &mut Vec<CppPath>,
+) {
+    let class_bases = match bases.get(class_path) {
+        Some(class_bases) => class_bases,
+        None => return,
+    };
+    for base in class_bases {
+        if base.visibility != CppVisibility::Public {
+            continue;
+        }
+        if !visited.insert(base.base_class_type.clone()) {
+            continue;
+        }
+        let is_base_primary = is_primary && base.base_index == 0;
+        if !is_base_primary {
+            result.push(base.base_class_type.clone());
+        } else if forwarding_classes.contains(&base.base_class_type) {
+            mark_bases_visited(&base.base_class_type, bases, visited);
+            continue;
+        }
+        collect_secondary_bases(
+            &base.base_class_type,
+            is_base_primary,
+            bases,
+            forwarding_classes,
+            visited,
+            result,
+        );
+    }
+}
 
-    let mut ordered_classes = Vec::new();
-    while !remaining_classes.is_empty() {
-        let mut any_added = false;
-        let mut remaining_classes2 = Vec::new();
-        for class in &remaining_classes {
-            if remaining_classes
-                .iter()
-                .any(|c| c.derived_class_type == class.base_class_type)
-            {
-                remaining_classes2.push(*class);
-            } else {
-                ordered_classes.push(*class);
-                any_added = true;
+/// Adds forwarding methods for methods of non-primary public base classes.
+///
+/// Methods of the first base class (and its first base class, and so on) are available
+/// through nested `Deref` implementations, so forwarding methods are only added
+/// for other bases. Forwarding methods of the first base class are available
+/// in the same way, so its secondary bases are also skipped. A method is skipped if a method with the same name is declared
+/// in the class itself, in any of its primary bases or in another base visited earlier.
+/// Bases are visited in depth-first order of their declaration, so derived classes
+/// take precedence over their bases, and earlier bases take precedence over later ones.
+pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
+    let mut bases = HashMap::<_, Vec<_>>::new();
+    let mut methods = HashMap::<CppPath, Vec<DbItem<&CppFunction>>>::new();
+    let mut forwarding_classes = HashSet::new();
+    for item in data.db.all_cpp_items() {
+        if let Some(type1) = item.item.as_type_ref() {
+            if has_forwarding_methods(type1) {
+                forwarding_classes.insert(&type1.path);
             }
         }
-        remaining_classes = remaining_classes2;
-        if !any_added {
-            bail!("Cyclic dependency detected while detecting inherited methods");
+        if let Some(base) = item.item.as_base_ref() {
+            bases
+                .entry(&base.derived_class_type)
+                .or_default()
+                .push(base);
+        }
+        if let Some(function) = item.filter_map(|item| item.as_function_ref()) {
+            if let Ok(class_path) = function.item.class_path() {
+                methods.entry(class_path).or_default().push(function);
+            }
         }
     }
+    for class_bases in bases.values_mut() {
+        class_bases.sort_by_key(|base| base.base_index);
+    }
+
+    let classes = data
+        .db
+        .cpp_items()
+        .filter_map(|item| item.item.as_type_ref())
+        .filter(|type1| has_forwarding_methods(type1));
 
-    let mut result = Vec::new();
-    for class in ordered_classes {
-        trace!("Detecting inherited methods for {:?}\n", class);
-        let methods = data
-            .db
-            .all_cpp_items()
-            .filter_map(|item| item.item.as_function_ref())
-            .filter(|m| m.class_path_parts().ok() == Some(class.base_class_type.items()));
+    let mut new_methods = Vec::new();
+    for type1 in classes {
+        let mut secondary_bases = Vec::new();
+        collect_secondary_bases(
+            &type1.path,
+            true,
+            &bases,
+            &forwarding_classes,
+            &mut HashSet::new(),
+            &mut secondary_bases,
+        );
+        if secondary_bases.is_empty() {
+            continue;
+        }
 
-        for method in methods {
-            let mut new_method = (*method).clone();
-            new_method.path = class.base_class_type.join(method.path.last().clone());
-            new_method.declaration_code = None;
-            //new_method.is_fake_inherited_method = true;
-            trace!("Method added: {}", new_method.short_text());
-            trace!("Base method: {}\n", method.short_text(),);
-            result.push(new_method);
+        // names of methods declared in the class and its primary bases
+        // are not available for forwarding methods
+        let mut taken_names = HashMap::<&str, &CppPath>::new();
+        let mut primary_class = Some(&type1.path);
+        while let Some(class_path) = primary_class {
+            for method in methods.get(class_path).into_iter().flatten() {
+                // forwarding methods added earlier belong to their base classes
+                let owner = method
+                    .item
+                    .member
+                    .as_ref()
+                    .and_then(|member| member.inherited_from.as_ref())
+                    .unwrap_or(class_path);
+                taken_names
+                    .entry(&method.item.path.last().name)
+                    .or_insert(owner);
+            }
+            primary_class = bases
+                .get(class_path)
+                .and_then(|class_bases| class_bases.first())
+                .filter(|base| base.base_index == 0 && base.visibility == CppVisibility::Public)
+                .map(|base| &base.base_class_type);
         }
+
+        for base_path in &secondary_bases {
+            for method in methods.get(base_path).into_iter().flatten() {
+                if method
+                    .item
+                    .member
+                    .as_ref()
+                    .map_or(false, |member| member.inherited_from.is_some())
+                {
+                    // bases of `base_path` are visited separately
+                    continue;
+                }
+                let name = &method.item.path.last().name;
+                let owner = *taken_names.entry(name).or_insert(base_path);
+                if owner != base_path {
+                    trace!(
+                        "{} is hidden by {} in {}",
+                        method.item.short_text(),
+                        owner.to_cpp_pseudo_code(),
+                        type1.path.to_cpp_pseudo_code()
+                    );
+                    continue;
+                }
+                if !can_forward(method.item) {
+                    continue;
+                }
+                let mut function = method.item.clone();
+                function.path = type1.path.join(method.item.path.last().clone());
+                function.declaration_code = None;
+                if let Some(member) = &mut function.member {
+                    member.is_virtual = false;
+                    member.is_pure_virtual = false;
+                    member.is_slot = false;
+                    member.inherited_from = Some(base_path.clone());
+                }
+                trace!(
+                    "adding forwarding method: {} (from {})",
+                    function.short_text(),
+                    base_path.to_cpp_pseudo_code()
+                );
+                new_methods.push(ItemWithSource::new(&method.id, function));
+            }
+        }
+    }
+
+    for item in new_methods {
+        data.db
+            .add_cpp_item(Some(item.source_id), CppItem::Function(item.item))?;
     }
-    Ok(result)
+    Ok(())
 }
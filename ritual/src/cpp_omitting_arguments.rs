@@ -3,6 +3,10 @@ use crate::database::ItemWithSource;
 use crate::processor::ProcessorData;
 use ritual_common::errors::Result;
 
+/// Adds a copy of each function with default argument values for each number
+/// of omitted trailing arguments. If `DefaultArgumentsMode::OptionalArguments` is selected
+/// for the function, the Rust generator combines these functions into a single function
+/// with optional arguments.
 pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
     let mut results = Vec::new();
     for item in data.db.cpp_items() {
@@ -1,29 +1,40 @@
-use crate::config::Config;
+use crate::config::{Config, TemplateInstantiation};
+use crate::cpp_annotations::{CppAnnotation, CppAnnotations};
 use crate::cpp_data::{
-    CppBaseSpecifier, CppClassField, CppEnumValue, CppItem, CppNamespace, CppOriginLocation,
-    CppPath, CppPathItem, CppTypeDeclaration, CppTypeDeclarationKind, CppVisibility,
+    CppBaseSpecifier, CppClassField, CppClassSpecialMembers, CppEnumValue, CppItem, CppNamespace,
+    CppOriginLocation, CppPath, CppPathItem, CppSpecialMemberDeclaration, CppTypeAlias,
+    CppTypeDeclaration, CppTypeDeclarationKind, CppVisibility,
 };
 use crate::cpp_function::{
-    CppFunction, CppFunctionArgument, CppFunctionKind, CppFunctionMemberData,
+    CppFunction, CppFunctionArgument, CppFunctionKind, CppFunctionMemberData, CppRefQualifier,
 };
 use crate::cpp_operator::CppOperator;
+use crate::cpp_parser_report::{
+    CppParserEntityKind, CppParserReport, CppParserSkipReason, CppParserSkippedEntity,
+};
+use crate::cpp_template_instantiator::add_template_instantiations;
 use crate::cpp_type::{
-    CppBuiltInNumericType, CppFunctionPointerType, CppPointerLikeTypeKind, CppSpecificNumericType,
-    CppSpecificNumericTypeKind, CppTemplateParameter, CppType,
+    CppBuiltInNumericType, CppFunctionPointerType, CppMemberPointerTarget, CppMemberPointerType,
+    CppPointerLikeTypeKind, CppTemplateParameter, CppType,
 };
 use crate::database::ItemId;
 use crate::processor::ProcessorData;
 use clang::diagnostic::{Diagnostic, Severity};
 use clang::*;
 use itertools::Itertools;
-use log::{debug, trace, warn};
+use log::{debug, info, trace, warn};
 use regex::Regex;
 use ritual_common::env_var_names;
-use ritual_common::errors::{bail, err_msg, format_err, print_trace, Result, ResultExt};
+use ritual_common::errors::{bail, err_msg, format_err, print_trace, Error, Result, ResultExt};
 use ritual_common::file_utils::{
     canonicalize, create_file, open_file, os_str_to_str, path_to_str, remove_file,
 };
 use ritual_common::target::{current_target, LibraryTarget};
+use ritual_common::utils::MapIfOk;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::hash::{Hash, Hasher};
 use std::io::Write;
 use std::mem;
 use std::path::{Path, PathBuf};
@@ -48,6 +59,7 @@ fn convert_type_kind(kind: TypeKind) -> CppBuiltInNumericType {
         TypeKind::ULongLong => CppBuiltInNumericType::ULongLong,
         TypeKind::Int128 => CppBuiltInNumericType::Int128,
         TypeKind::UInt128 => CppBuiltInNumericType::UInt128,
+        TypeKind::Float16 => CppBuiltInNumericType::Float16,
         TypeKind::Float => CppBuiltInNumericType::Float,
         TypeKind::Double => CppBuiltInNumericType::Double,
         TypeKind::LongDouble => CppBuiltInNumericType::LongDouble,
@@ -55,7 +67,7 @@ fn convert_type_kind(kind: TypeKind) -> CppBuiltInNumericType {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CppParserOutputItem {
     pub id: ItemId,
     /// File name of the include file (without full path)
@@ -64,16 +76,137 @@ pub struct CppParserOutputItem {
     pub origin_location: CppOriginLocation,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CppParserOutput(pub Vec<CppParserOutputItem>);
 
+/// Information about inputs of a `cpp_parser` run. If the fingerprint
+/// of the current inputs matches the one saved in the database,
+/// the parser is not run again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CppParserFingerprint {
+    /// Hash of include directives, target include paths,
+    /// clang arguments, clang version and other parser settings
+    pub settings_hash: u64,
+    /// All files included in the parsed translation unit
+    /// and hashes of their content
+    pub files: Vec<(PathBuf, u64)>,
+    /// Output of the parser, passed to the after-parser hooks
+    /// when the parser is not run again
+    pub output: CppParserOutput,
+}
+
+impl CppParserFingerprint {
+    /// Returns true if content of all recorded files is unchanged.
+    pub fn files_unchanged(&self) -> Result<bool> {
+        for (path, hash) in &self.files {
+            if !path.exists() || file_content_hash(path)? != *hash {
+                debug!("changed header: {}", path.display());
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Version of the parser's own logic (including handling of `ritual:` annotations).
+/// Must be bumped when the parser's output for the same input changes.
+const CPP_PARSER_VERSION: u32 = 1;
+
+/// FNV-1a hasher. Unlike `DefaultHasher`, its algorithm is fixed,
+/// so hashes saved in the database stay valid across builds of the generator.
+pub struct StableHasher(u64);
+
+impl StableHasher {
+    pub fn new() -> Self {
+        StableHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+pub fn file_content_hash(path: &Path) -> Result<u64> {
+    let content = fs::read(path).with_context(|_| format!("failed to read {}", path.display()))?;
+    let mut hasher = StableHasher::new();
+    content.hash(&mut hasher);
+    Ok(hasher.finish())
+}
+
+/// Returns hash of all parser settings that affect its output.
+fn parser_settings_hash(config: &Config) -> Result<u64> {
+    let mut hasher = StableHasher::new();
+    CPP_PARSER_VERSION.hash(&mut hasher);
+    get_version().hash(&mut hasher);
+    config.include_directives().hash(&mut hasher);
+    config.target_include_paths().hash(&mut hasher);
+    clang_arguments(config)?.hash(&mut hasher);
+    config.template_instantiations().hash(&mut hasher);
+    config
+        .special_typedefs()
+        .iter()
+        .sorted_by(|a, b| a.0.cmp(b.0))
+        .collect_vec()
+        .hash(&mut hasher);
+    config.cpp_parser_path_hook().is_some().hash(&mut hasher);
+    config.cpp_parser_hooks_version().hash(&mut hasher);
+    Ok(hasher.finish())
+}
+
+/// Namespace containing typedefs for template instantiations requested in the config.
+const TEMPLATE_INSTANTIATIONS_NAMESPACE: &str = "ritual_template_instantiations";
+
+/// Returns C++ code that forces clang to resolve `instantiations`.
+/// Each instantiation is declared as a typedef in a special namespace,
+/// in the same order as in `instantiations`.
+fn template_instantiations_code(instantiations: &[TemplateInstantiation]) -> Option<String> {
+    if instantiations.is_empty() {
+        return None;
+    }
+    let mut code = format!("\nnamespace {} {{\n", TEMPLATE_INSTANTIATIONS_NAMESPACE);
+    for (index, instantiation) in instantiations.iter().enumerate() {
+        code += &format!(
+            "typedef {} instantiation{};\n",
+            instantiation.to_cpp_code(),
+            index
+        );
+    }
+    code += "}\n";
+    Some(code)
+}
+
+/// Returns paths of all files included (directly or indirectly)
+/// in the translation unit `entity`.
+fn included_files(entity: Entity<'_>) -> Vec<PathBuf> {
+    entity
+        .get_children()
+        .into_iter()
+        .filter(|child| child.get_kind() == EntityKind::InclusionDirective)
+        .filter_map(|child| child.get_file())
+        .map(|file| file.get_path())
+        .collect()
+}
+
 /// Implementation of the C++ parser that extracts information
 /// about the C++ library's API from its headers.
 struct CppParser<'b, 'a> {
     data: &'b mut ProcessorData<'a>,
     current_target_paths: Vec<PathBuf>,
     source_id: Option<ItemId>,
+    /// Template instantiations included in the parsed code
+    /// (see `template_instantiations_code`)
+    template_instantiations: Vec<TemplateInstantiation>,
     output: CppParserOutput,
+    report: CppParserReport,
 }
 
 /// Print representation of `entity` and its children to the log.
@@ -180,15 +313,18 @@ fn get_path(entity: Entity<'_>) -> Result<CppPath> {
             EntityKind::ClassDecl
             | EntityKind::ClassTemplate
             | EntityKind::StructDecl
+            | EntityKind::UnionDecl
             | EntityKind::Namespace
-            | EntityKind::EnumDecl
-            | EntityKind::ClassTemplatePartialSpecialization => {
+            | EntityKind::EnumDecl => {
                 parts.insert(0, get_path_item(p)?);
                 current_entity = p;
             }
             EntityKind::Method => {
                 bail!("Type nested in a method");
             }
+            EntityKind::ClassTemplatePartialSpecialization => {
+                bail!("Type nested in a template partial specialization");
+            }
             _ => bail!("get_full_name: unexpected parent kind: {:?}", p),
         }
     }
@@ -199,6 +335,295 @@ fn get_path(entity: Entity<'_>) -> Result<CppPath> {
     Ok(CppPath::from_items(parts))
 }
 
+/// Returns true if `entity` is an anonymous struct or union declared
+/// as a member of `parent` (e.g. `union { int a; float b; };`).
+/// Fields of such members are accessed as if they were fields of `parent`.
+fn is_anonymous_member(entity: Entity<'_>, parent: Entity<'_>) -> bool {
+    if entity.get_kind() != EntityKind::StructDecl && entity.get_kind() != EntityKind::UnionDecl {
+        return false;
+    }
+    if entity.get_name().is_some() || !entity.is_definition() {
+        return false;
+    }
+    // `struct { int x; } s;` declares a named field of an anonymous type
+    !parent.get_children().into_iter().any(|c| {
+        c.get_kind() == EntityKind::FieldDecl
+            && c.get_type().and_then(|t| t.get_declaration()) == Some(entity)
+    })
+}
+
+/// Returns true if `type1` is an array (possibly multidimensional)
+/// of const-qualified elements.
+fn is_const_array(type1: Type<'_>) -> bool {
+    let mut current = type1;
+    while let Some(element_type) = current.get_element_type() {
+        current = element_type;
+    }
+    current != type1 && current.is_const_qualified()
+}
+
+/// Returns true if the function declaration ends with `= delete`.
+/// libclang doesn't expose deleted functions, so the declaration code
+/// is examined instead.
+pub fn is_deleted_declaration(declaration_code: &str) -> bool {
+    let code = declaration_code.trim_end();
+    code.ends_with("delete")
+        && code[..code.len() - "delete".len()]
+            .trim_end()
+            .ends_with('=')
+}
+
+/// Returns true if the function `entity` has a non-throwing exception specification.
+fn is_noexcept_function(entity: Entity<'_>) -> bool {
+    match entity.get_exception_specification() {
+        Some(ExceptionSpecification::BasicNoexcept) | Some(ExceptionSpecification::DynamicNone) => {
+            true
+        }
+        // `noexcept(expr)` is conservatively treated as potentially throwing
+        _ => false,
+    }
+}
+
+/// Returns true if the function argument `entity` has a default value.
+fn has_default_value(entity: Entity<'_>) -> Result<bool> {
+    let range = entity
+        .get_range()
+        .ok_or_else(|| format_err!("failed to get range from argument entity: {:?}", entity))?;
+    for token in range.tokenize() {
+        let spelling = token.get_spelling();
+        if spelling == "=" {
+            return Ok(true);
+        }
+        if spelling == "{" {
+            // clang sometimes reports incorrect range for arguments
+            break;
+        }
+    }
+    Ok(false)
+}
+
+/// Returns the reference kind and constness of the referenced type
+/// if `type1` is a reference to the class declared by `class_entity`.
+fn class_reference_kind(
+    type1: Type<'_>,
+    class_entity: Entity<'_>,
+) -> Option<(CppPointerLikeTypeKind, bool)> {
+    let kind = match type1.get_kind() {
+        TypeKind::LValueReference => CppPointerLikeTypeKind::Reference,
+        TypeKind::RValueReference => CppPointerLikeTypeKind::RValueReference,
+        _ => return None,
+    };
+    let pointee = type1.get_pointee_type()?;
+    let declaration = pointee.get_canonical_type().get_declaration()?;
+    if declaration.get_canonical_entity() != class_entity.get_canonical_entity() {
+        return None;
+    }
+    Some((kind, pointee.is_const_qualified()))
+}
+
+/// Collects information about special member functions and fields declared
+/// in the body of the class `entity`, including declarations that can't be parsed.
+fn parse_special_members(entity: Entity<'_>) -> CppClassSpecialMembers {
+    fn declaration(entity: Entity<'_>, has_usual_signature: bool) -> CppSpecialMemberDeclaration {
+        let code = entity.get_range().map_or_else(String::new, |range| {
+            range
+                .tokenize()
+                .into_iter()
+                .map(|token| token.get_spelling())
+                .take_while(|text| text != "{" && text != ";")
+                .join(" ")
+        });
+        CppSpecialMemberDeclaration {
+            visibility: match entity.get_accessibility().unwrap_or(Accessibility::Public) {
+                Accessibility::Public => CppVisibility::Public,
+                Accessibility::Protected => CppVisibility::Protected,
+                Accessibility::Private => CppVisibility::Private,
+            },
+            is_deleted: is_deleted_declaration(&code),
+            is_defaulted: entity.is_defaulted(),
+            has_usual_signature,
+        }
+    }
+
+    fn set(target: &mut Option<CppSpecialMemberDeclaration>, value: CppSpecialMemberDeclaration) {
+        // prefer the overload with the usual signature
+        if target.as_ref().map_or(true, |old| !old.has_usual_signature) {
+            *target = Some(value);
+        }
+    }
+
+    let mut result = CppClassSpecialMembers::default();
+    for child in entity.get_children() {
+        match child.get_kind() {
+            EntityKind::Constructor => {
+                result.has_constructors = true;
+                let arguments = child.get_arguments().unwrap_or_default();
+                let has_defaults = |arguments: &[Entity<'_>]| {
+                    arguments
+                        .iter()
+                        .all(|arg| has_default_value(*arg).unwrap_or(false))
+                };
+                if has_defaults(&arguments) {
+                    set(
+                        &mut result.default_constructor,
+                        declaration(child, arguments.is_empty()),
+                    );
+                }
+                if let Some(first) = arguments.get(0) {
+                    let reference_kind = first
+                        .get_type()
+                        .and_then(|type1| class_reference_kind(type1, entity));
+                    if has_defaults(&arguments[1..]) {
+                        match reference_kind {
+                            Some((CppPointerLikeTypeKind::RValueReference, _)) => {
+                                result.has_move_operations = true;
+                            }
+                            Some((_, is_const)) => {
+                                let is_usual = is_const && arguments.len() == 1;
+                                set(&mut result.copy_constructor, declaration(child, is_usual));
+                            }
+                            None => {}
+                        }
+                    }
+                }
+            }
+            EntityKind::FunctionTemplate => {
+                if child.get_template_kind() == Some(EntityKind::Constructor) {
+                    result.has_constructors = true;
+                }
+            }
+            EntityKind::Destructor => {
+                if child.is_pure_virtual_method() {
+                    result.has_pure_virtual_methods = true;
+                }
+                result.destructor = Some(declaration(child, true));
+            }
+            EntityKind::Method => {
+                if child.is_pure_virtual_method() {
+                    result.has_pure_virtual_methods = true;
+                }
+                if child.get_name().as_ref().map(|s| s.as_str()) == Some("operator=") {
+                    let arguments = child.get_arguments().unwrap_or_default();
+                    if arguments.len() == 1 {
+                        let arg_type = arguments[0].get_type();
+                        let reference_kind =
+                            arg_type.and_then(|type1| class_reference_kind(type1, entity));
+                        match reference_kind {
+                            Some((CppPointerLikeTypeKind::RValueReference, _)) => {
+                                result.has_move_operations = true;
+                            }
+                            Some((_, is_const)) => {
+                                set(&mut result.copy_assignment, declaration(child, is_const));
+                            }
+                            None => {
+                                let is_by_value = arg_type
+                                    .and_then(|type1| type1.get_canonical_type().get_declaration())
+                                    .map_or(false, |arg_class| {
+                                        arg_class.get_canonical_entity()
+                                            == entity.get_canonical_entity()
+                                    });
+                                if is_by_value {
+                                    set(&mut result.copy_assignment, declaration(child, false));
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            EntityKind::FieldDecl => {
+                if has_skip_annotation(child) {
+                    result.has_unknown_members = true;
+                }
+                if let Some(type1) = child.get_type() {
+                    match type1.get_kind() {
+                        TypeKind::LValueReference | TypeKind::RValueReference => {
+                            result.has_reference_fields = true;
+                        }
+                        _ => {}
+                    }
+                    if type1.is_const_qualified() || is_const_array(type1) {
+                        result.has_const_fields = true;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    result
+}
+
+/// Returns true if the declaration code of a constructor or a conversion operator
+/// contains the `explicit` specifier (excluding `explicit(false)`).
+pub fn is_explicit_declaration(declaration_code: &str) -> bool {
+    let is_identifier_char = |c: char| c.is_alphanumeric() || c == '_';
+    let mut rest = declaration_code;
+    while let Some(index) = rest.find("explicit") {
+        let before = rest[..index].chars().next_back();
+        let after = &rest[index + "explicit".len()..];
+        rest = after;
+        if before.map_or(false, is_identifier_char)
+            || after.chars().next().map_or(false, is_identifier_char)
+        {
+            continue;
+        }
+        let after = after.trim_start();
+        if after.starts_with('(') {
+            let condition = after[1..].split(')').next().unwrap_or("");
+            return condition.trim() != "false";
+        }
+        return true;
+    }
+    false
+}
+
+/// Returns true if the constructor or conversion operator `entity` is `explicit`.
+fn is_explicit_function(entity: Entity<'_>, declaration_code: Option<&str>) -> bool {
+    // libclang only reports whether a constructor is a converting constructor
+    // (i.e. non-explicit and callable with one argument), so the declaration
+    // is examined for other constructors and conversion operators
+    if entity.get_kind() == EntityKind::Constructor && entity.is_converting_constructor() {
+        return false;
+    }
+    declaration_code.map_or(false, is_explicit_declaration)
+}
+
+/// Returns true if the function type spelling produced by clang
+/// (e.g. `int (bool) const`) has a `const` method qualifier.
+pub fn is_const_function_type(type_name: &str) -> bool {
+    type_name.rfind(')').map_or(false, |index| {
+        type_name[index + 1..]
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .any(|word| word == "const")
+    })
+}
+
+/// Returns `ritual:` annotations attached to `entity`.
+/// Invalid annotations are reported and ignored.
+fn get_annotations(entity: Entity<'_>) -> Vec<CppAnnotation> {
+    entity
+        .get_children()
+        .into_iter()
+        .filter(|child| child.get_kind() == EntityKind::AnnotateAttr)
+        .filter_map(|child| child.get_display_name())
+        .filter_map(|text| match CppAnnotation::parse(&text) {
+            Ok(annotation) => annotation,
+            Err(err) => {
+                warn!(
+                    "invalid annotation of {}: {}",
+                    get_full_name_display(entity),
+                    err
+                );
+                None
+            }
+        })
+        .collect()
+}
+
+/// Returns true if `entity` is annotated with `ritual:skip`.
+fn has_skip_annotation(entity: Entity<'_>) -> bool {
+    get_annotations(entity).contains(&CppAnnotation::Skip)
+}
+
 fn get_full_name_display(entity: Entity<'_>) -> String {
     match get_path(entity) {
         Ok(name) => name.to_cpp_pseudo_code(),
@@ -223,32 +648,16 @@ fn init_clang() -> Result<Clang> {
     Clang::new().map_err(|err| format_err!("clang init failed: {}", err))
 }
 
-/// Runs `clang` parser with `config`.
-/// If `cpp_code` is specified, it's written to the C++ file before parsing it.
-/// If successful, calls `f` and passes the topmost entity (the translation unit)
-/// as its argument. Returns output value of `f` or an error.
-fn run_clang<R, F: FnMut(Entity<'_>) -> Result<R>>(
-    config: &Config,
-    tmp_path: &Path,
-    cpp_code: Option<String>,
-    mut f: F,
-) -> Result<R> {
-    let clang = init_clang()?;
-    let index = Index::new(&clang, false, false);
-    let tmp_cpp_path = tmp_path.join("1.cpp");
-    {
-        let mut tmp_file = create_file(&tmp_cpp_path)?;
-        for directive in config.include_directives() {
-            writeln!(tmp_file, "#include \"{}\"", path_to_str(directive)?)?;
-        }
-        if let Some(cpp_code) = cpp_code {
-            write!(tmp_file, "{}", cpp_code)?;
-        }
-    }
+/// Returns command line arguments for `clang` based on `config`.
+fn clang_arguments(config: &Config) -> Result<Vec<String>> {
     let mut args = vec![
         "-Xclang".to_string(),
         "-detailed-preprocessing-record".to_string(),
     ];
+    if config.is_c_library() {
+        args.push("-x".to_string());
+        args.push("c".to_string());
+    }
     args.extend_from_slice(config.cpp_parser_arguments());
     let mut cpp_build_paths = config.cpp_build_paths().clone();
     cpp_build_paths.apply_env();
@@ -277,62 +686,188 @@ fn run_clang<R, F: FnMut(Entity<'_>) -> Result<R>>(
         args.push(str.to_string());
     }
     debug!("clang arguments: {:?}", args);
+    Ok(args)
+}
+
+/// Writes a C++ file containing `include_directives` followed by `cpp_code` (if any).
+fn write_cpp_file(
+    path: &Path,
+    include_directives: &[PathBuf],
+    cpp_code: Option<&str>,
+) -> Result<()> {
+    let mut file = create_file(path)?;
+    for directive in include_directives {
+        writeln!(file, "#include \"{}\"", path_to_str(directive)?)?;
+    }
+    if let Some(cpp_code) = cpp_code {
+        write!(file, "{}", cpp_code)?;
+    }
+    Ok(())
+}
+
+/// Logs diagnostics of the translation unit and returns them
+/// if any of them is an error.
+fn check_diagnostics<'i>(tu: &'i TranslationUnit<'i>) -> Option<String> {
+    let diagnostics = tu.get_diagnostics();
+    if !diagnostics.is_empty() {
+        trace!("[DebugParser] Diagnostics:");
+        for diag in &diagnostics {
+            trace!("[DebugParser] {}", diag);
+        }
+    }
+    let should_print_error = |d: &Diagnostic<'_>| {
+        d.get_severity() == Severity::Error || d.get_severity() == Severity::Fatal
+    };
+    if diagnostics.iter().any(should_print_error) {
+        Some(diagnostics.iter().map(ToString::to_string).join("\n"))
+    } else {
+        None
+    }
+}
+
+/// Runs `clang` parser with `config`.
+/// If `cpp_code` is specified, it's written to the C++ file before parsing it.
+/// If successful, calls `f` and passes the topmost entity (the translation unit)
+/// as its argument. Returns output value of `f` or an error.
+fn run_clang<R, F: FnMut(Entity<'_>) -> Result<R>>(
+    config: &Config,
+    tmp_path: &Path,
+    cpp_code: Option<String>,
+    f: F,
+) -> Result<R> {
+    try_run_clang(config, tmp_path, cpp_code, f)?
+        .map_err(|diagnostics| format_err!("fatal clang error:\n{}", diagnostics))
+}
+
+/// Same as `run_clang`, but if clang reports errors, `f` is not called
+/// and the diagnostics are returned as `Ok(Err(_))`.
+fn try_run_clang<R, F: FnMut(Entity<'_>) -> Result<R>>(
+    config: &Config,
+    tmp_path: &Path,
+    cpp_code: Option<String>,
+    mut f: F,
+) -> Result<std::result::Result<R, String>> {
+    let clang = init_clang()?;
+    let index = Index::new(&clang, false, false);
+    let tmp_cpp_path = tmp_path.join("1.cpp");
+    write_cpp_file(
+        &tmp_cpp_path,
+        config.include_directives(),
+        cpp_code.as_ref().map(String::as_str),
+    )?;
+    let args = clang_arguments(config)?;
 
     let tu = index
         .parser(&tmp_cpp_path)
         .arguments(&args)
         .parse()
         .with_context(|_| "clang parse failed")?;
+    if let Some(diagnostics) = check_diagnostics(&tu) {
+        remove_file(&tmp_cpp_path)?;
+        return Ok(Err(diagnostics));
+    }
     let translation_unit = tu.get_entity();
     assert!(translation_unit.get_kind() == EntityKind::TranslationUnit);
-    {
-        let diagnostics = tu.get_diagnostics();
-        if !diagnostics.is_empty() {
-            trace!("[DebugParser] Diagnostics:");
-            for diag in &diagnostics {
-                trace!("[DebugParser] {}", diag);
-            }
-        }
-        let should_print_error = |d: &Diagnostic<'_>| {
-            d.get_severity() == Severity::Error || d.get_severity() == Severity::Fatal
-        };
-        if diagnostics.iter().any(should_print_error) {
-            bail!(
-                "fatal clang error:\n{}",
-                diagnostics.iter().map(ToString::to_string).join("\n")
-            );
-        }
-    }
     let result = f(translation_unit);
     remove_file(&tmp_cpp_path)?;
-    result
+    result.map(Ok)
 }
 
 /// Runs the parser on specified data.
 pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
     debug!("clang version: {}", get_version());
+    let settings_hash = parser_settings_hash(data.config)?;
+    if let Some(fingerprint) = data.db.cpp_parser_fingerprint() {
+        if fingerprint.settings_hash == settings_hash && fingerprint.files_unchanged()? {
+            info!("Headers and parser settings are unchanged, skipping cpp_parser");
+            let output = fingerprint.output.clone();
+            return run_after_cpp_parser_hooks(data, &output);
+        }
+    }
     debug!("Initializing clang");
     let mut parser = CppParser {
         current_target_paths: data.config.target_include_paths().to_vec(),
         source_id: None,
+        template_instantiations: data.config.template_instantiations().to_vec(),
         data,
         output: Default::default(),
+        report: Default::default(),
     };
-    run_clang(
-        &parser.data.config,
-        &parser.data.workspace.tmp_path(),
-        None,
-        |translation_unit| parser.parse(translation_unit),
-    )?;
+    let files = loop {
+        let result = try_run_clang(
+            &parser.data.config,
+            &parser.data.workspace.tmp_path(),
+            template_instantiations_code(&parser.template_instantiations),
+            |translation_unit| {
+                parser.parse(translation_unit)?;
+                Ok(included_files(translation_unit))
+            },
+        )?;
+        match result {
+            Ok(files) => break files,
+            Err(diagnostics) => {
+                if !parser.remove_invalid_template_instantiations()? {
+                    bail!("fatal clang error:\n{}", diagnostics);
+                }
+            }
+        }
+    };
+    let report = parser.report;
+    let output = parser.output;
+    save_report(data, "cpp_parser", &report)?;
 
+    let files = files
+        .into_iter()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map_if_ok(|path| -> Result<_> {
+            let hash = file_content_hash(&path)?;
+            Ok((path, hash))
+        })?;
+    data.db
+        .set_cpp_parser_fingerprint(Some(CppParserFingerprint {
+            settings_hash,
+            files,
+            output,
+        }));
     Ok(())
 }
 
+/// Runs hooks added with `Config::add_after_cpp_parser_hook`.
+fn run_after_cpp_parser_hooks(
+    data: &mut ProcessorData<'_>,
+    output: &CppParserOutput,
+) -> Result<()> {
+    for hook in data.config.after_cpp_parser_hooks() {
+        hook(data, output)?;
+    }
+    Ok(())
+}
+
+/// Writes the list of skipped declarations to the workspace's log directory.
+fn save_report(data: &ProcessorData<'_>, step_name: &str, report: &CppParserReport) -> Result<()> {
+    let path = data.workspace.log_path().join(format!(
+        "{}_{}_skipped.txt",
+        data.db.crate_name(),
+        step_name
+    ));
+    if !report.is_empty() {
+        info!(
+            "{}: skipped {} declarations (see {})",
+            step_name,
+            report.skipped().len(),
+            path.display()
+        );
+    }
+    report.save(&path)
+}
+
 pub fn parse_generated_items(data: &mut ProcessorData<'_>) -> Result<()> {
     let current_target = LibraryTarget {
         cpp_library_version: data.config.cpp_lib_version().map(ToString::to_string),
         target: current_target(),
     };
+    let mut report = CppParserReport::default();
     for ffi_item_id in data.db.ffi_item_ids().collect_vec() {
         let ffi_item = data.db.ffi_item(&ffi_item_id)?;
         if !ffi_item.item.is_source_item() {
@@ -349,37 +884,80 @@ pub fn parse_generated_items(data: &mut ProcessorData<'_>) -> Result<()> {
         let mut parser = CppParser {
             current_target_paths: vec![data.workspace.tmp_path()],
             source_id: Some(ffi_item_id),
+            template_instantiations: Vec::new(),
             data,
             output: Default::default(),
+            report: Default::default(),
         };
         run_clang(
             &parser.data.config,
             &parser.data.workspace.tmp_path(),
             Some(code),
-            |translation_unit| {
-                parser.parse(translation_unit)?;
-                Ok(())
-            },
+            |translation_unit| parser.parse(translation_unit),
         )?;
+        report.append(parser.report);
     }
-    Ok(())
+    save_report(data, "cpp_parser_stage2", &report)
 }
 
 impl CppParser<'_, '_> {
+    /// Records an `entity` that couldn't be parsed because of `error`.
+    fn report_skipped(&mut self, entity: Entity<'_>, kind: CppParserEntityKind, error: &Error) {
+        self.report.add_error(
+            get_full_name_display(entity),
+            get_origin_location(entity).ok(),
+            kind,
+            error,
+        );
+    }
+
     fn add_output(
         &mut self,
         include_file: String,
         origin_location: CppOriginLocation,
         item: CppItem,
-    ) -> Result<()> {
-        if let Some(id) = self.data.db.add_cpp_item(self.source_id.clone(), item)? {
+    ) -> Result<Option<ItemId>> {
+        let id = self.data.db.add_cpp_item(self.source_id.clone(), item)?;
+        if let Some(id) = &id {
             self.output.0.push(CppParserOutputItem {
                 include_file,
                 origin_location,
-                id,
+                id: id.clone(),
             });
         }
-        Ok(())
+        Ok(id)
+    }
+
+    /// Saves `ritual:` annotations of `entity` (and its arguments, if it's a function)
+    /// for the newly added item `id`.
+    fn add_annotations(&mut self, id: Option<ItemId>, entity: Entity<'_>) {
+        let id = if let Some(id) = id {
+            id
+        } else {
+            return;
+        };
+        let mut annotations = CppAnnotations::default();
+        for annotation in get_annotations(entity) {
+            annotations.add(annotation);
+        }
+        if let Some(arguments) = entity.get_arguments() {
+            for (index, argument) in arguments.into_iter().enumerate() {
+                for annotation in get_annotations(argument) {
+                    if annotation == CppAnnotation::TransferOwnership {
+                        annotations.ownership_transferring_arguments.push(index);
+                    } else {
+                        warn!(
+                            "unsupported annotation of an argument of {}: {:?}",
+                            get_full_name_display(entity),
+                            annotation
+                        );
+                    }
+                }
+            }
+        }
+        if !annotations.is_empty() {
+            self.data.db.add_annotations_item(id, annotations);
+        }
     }
 
     /// Search for a C++ type information in the types found by the parser
@@ -395,6 +973,49 @@ impl CppParser<'_, '_> {
             .find(|i| f(i))
     }
 
+    /// Returns fully qualified name of `entity`. Unlike `get_path`, this function
+    /// supports class template partial specializations and their members.
+    fn get_full_path(&self, entity: Entity<'_>) -> Result<CppPath> {
+        if entity.get_kind() == EntityKind::ClassTemplatePartialSpecialization {
+            return self.get_partial_specialization_path(entity);
+        }
+        if let Some(parent) = entity.get_semantic_parent() {
+            if parent.get_kind() == EntityKind::ClassTemplatePartialSpecialization {
+                let parent_path = self.get_partial_specialization_path(parent)?;
+                return Ok(parent_path.join(get_path_item(entity)?));
+            }
+        }
+        get_path(entity)
+    }
+
+    /// Returns fully qualified name of a class template partial specialization `entity`.
+    /// Template arguments of the last item contain the specialization's pattern
+    /// (e.g. `QList<T*>`) instead of its template parameters.
+    fn get_partial_specialization_path(&self, entity: Entity<'_>) -> Result<CppPath> {
+        let display_name = entity
+            .get_display_name()
+            .ok_or_else(|| err_msg("failed to get partial specialization name"))?;
+        let (_, args) = parse_template_args(&display_name).ok_or_else(|| {
+            format_err!(
+                "failed to parse partial specialization pattern: {}",
+                display_name
+            )
+        })?;
+        let context_template_args = get_context_template_args(entity);
+        let mut arg_types = Vec::new();
+        for arg in args {
+            let arg_type = self
+                .parse_unexposed_type(None, Some(arg.trim().to_string()), &context_template_args)
+                .with_context(|_| {
+                    format!("failed to parse partial specialization argument: {}", arg)
+                })?;
+            arg_types.push(arg_type);
+        }
+        let mut path = get_path(entity)?;
+        path.last_mut().template_arguments = Some(arg_types);
+        Ok(path)
+    }
+
     /// Attempts to parse an unexposed type, i.e. a type the used `clang` API
     /// is not able to describe. Either `type1` or `string` must be specified,
     /// and both may be specified at the same time.
@@ -425,6 +1046,7 @@ impl CppParser<'_, '_> {
                 if declaration.get_kind() == EntityKind::ClassDecl
                     || declaration.get_kind() == EntityKind::ClassTemplate
                     || declaration.get_kind() == EntityKind::StructDecl
+                    || declaration.get_kind() == EntityKind::UnionDecl
                 {
                     if declaration
                         .get_accessibility()
@@ -552,7 +1174,7 @@ impl CppParser<'_, '_> {
                 CppTypeDeclarationKind::Enum { .. } => {
                     return Ok(CppType::Enum { path });
                 }
-                CppTypeDeclarationKind::Class { .. } => {
+                CppTypeDeclarationKind::Class { .. } | CppTypeDeclarationKind::Union => {
                     return Ok(CppType::Class(path));
                 }
             }
@@ -659,6 +1281,7 @@ impl CppParser<'_, '_> {
             | TypeKind::ULongLong
             | TypeKind::Int128
             | TypeKind::UInt128
+            | TypeKind::Float16
             | TypeKind::Float
             | TypeKind::Double
             | TypeKind::LongDouble => {
@@ -796,7 +1419,8 @@ impl CppParser<'_, '_> {
                             } else {
                                 Ok(CppType::PointerLike {
                                     kind: original_type_indirection,
-                                    is_const: pointee.is_const_qualified(),
+                                    is_const: pointee.is_const_qualified()
+                                        || is_const_array(pointee),
                                     target: Box::new(subtype),
                                 })
                             }
@@ -806,6 +1430,22 @@ impl CppParser<'_, '_> {
                     None => bail!("can't get pointee type"),
                 }
             }
+            TypeKind::ConstantArray => {
+                let element_type = type1
+                    .get_element_type()
+                    .ok_or_else(|| err_msg("can't get array element type"))?;
+                let size = type1
+                    .get_size()
+                    .ok_or_else(|| err_msg("can't get array size"))?;
+                let element_type = self.parse_type(element_type, context_template_args)?;
+                if element_type.is_void() {
+                    bail!("array of void");
+                }
+                Ok(CppType::Array {
+                    element_type: Box::new(element_type),
+                    size,
+                })
+            }
             TypeKind::Elaborated => {
                 self.parse_type(type1.get_canonical_type(), context_template_args)
             }
@@ -845,87 +1485,46 @@ impl CppParser<'_, '_> {
                     parsed_canonical
                 }
             }
+            TypeKind::MemberPointer => {
+                let class_type = type1
+                    .get_class_type()
+                    .ok_or_else(|| err_msg("can't get class type of member pointer"))?;
+                let class_path = match self.parse_type(class_type, context_template_args)? {
+                    CppType::Class(path) => path,
+                    other => bail!("unexpected class type of member pointer: {:?}", other),
+                };
+                let pointee = type1
+                    .get_pointee_type()
+                    .ok_or_else(|| err_msg("can't get pointee type"))?;
+                let target = if pointee.get_kind() == TypeKind::FunctionPrototype {
+                    if pointee.get_ref_qualifier().is_some() {
+                        bail!("ref-qualified member function pointers are not supported");
+                    }
+                    let function_type = match self.parse_type(pointee, context_template_args)? {
+                        CppType::FunctionPointer(function_type) => function_type,
+                        other => bail!("unexpected member function type: {:?}", other),
+                    };
+                    CppMemberPointerTarget::Method {
+                        function_type,
+                        is_const: is_const_function_type(&pointee.get_display_name()),
+                    }
+                } else {
+                    CppMemberPointerTarget::Field(Box::new(
+                        self.parse_type(pointee, context_template_args)?,
+                    ))
+                };
+                Ok(CppType::MemberPointer(CppMemberPointerType {
+                    class_path,
+                    target,
+                }))
+            }
             _ => bail!("Unsupported kind of type: {:?}", type1.get_kind()),
         }
     }
 
     /// Checks if the typedef `name` has a special meaning for the parser.
     fn parse_special_typedef(&self, name: &str) -> Option<CppType> {
-        match name {
-            "qint8" | "int8_t" | "GLbyte" => {
-                Some(CppType::SpecificNumeric(CppSpecificNumericType {
-                    path: CppPath::from_good_str(name),
-                    bits: 8,
-                    kind: CppSpecificNumericTypeKind::Integer { is_signed: true },
-                }))
-            }
-            "quint8" | "uint8_t" | "GLubyte" => {
-                Some(CppType::SpecificNumeric(CppSpecificNumericType {
-                    path: CppPath::from_good_str(name),
-                    bits: 8,
-                    kind: CppSpecificNumericTypeKind::Integer { is_signed: false },
-                }))
-            }
-            "qint16" | "int16_t" | "GLshort" => {
-                Some(CppType::SpecificNumeric(CppSpecificNumericType {
-                    path: CppPath::from_good_str(name),
-                    bits: 16,
-                    kind: CppSpecificNumericTypeKind::Integer { is_signed: true },
-                }))
-            }
-            "quint16" | "uint16_t" | "GLushort" => {
-                Some(CppType::SpecificNumeric(CppSpecificNumericType {
-                    path: CppPath::from_good_str(name),
-                    bits: 16,
-                    kind: CppSpecificNumericTypeKind::Integer { is_signed: false },
-                }))
-            }
-            "qint32" | "int32_t" | "GLint" => {
-                Some(CppType::SpecificNumeric(CppSpecificNumericType {
-                    path: CppPath::from_good_str(name),
-                    bits: 32,
-                    kind: CppSpecificNumericTypeKind::Integer { is_signed: true },
-                }))
-            }
-            "quint32" | "uint32_t" | "GLuint" => {
-                Some(CppType::SpecificNumeric(CppSpecificNumericType {
-                    path: CppPath::from_good_str(name),
-                    bits: 32,
-                    kind: CppSpecificNumericTypeKind::Integer { is_signed: false },
-                }))
-            }
-            "qint64" | "int64_t" | "qlonglong" | "GLint64" => {
-                Some(CppType::SpecificNumeric(CppSpecificNumericType {
-                    path: CppPath::from_good_str(name),
-                    bits: 64,
-                    kind: CppSpecificNumericTypeKind::Integer { is_signed: true },
-                }))
-            }
-            "quint64" | "uint64_t" | "qulonglong" | "GLuint64" => {
-                Some(CppType::SpecificNumeric(CppSpecificNumericType {
-                    path: CppPath::from_good_str(name),
-                    bits: 64,
-                    kind: CppSpecificNumericTypeKind::Integer { is_signed: false },
-                }))
-            }
-            "qintptr" | "qptrdiff" | "QList::difference_type" => {
-                Some(CppType::PointerSizedInteger {
-                    path: CppPath::from_good_str(name),
-                    is_signed: true,
-                })
-            }
-            "quintptr" | "size_t" | "std::size_t" => Some(CppType::PointerSizedInteger {
-                path: CppPath::from_good_str(name),
-                is_signed: false,
-            }),
-            "std::initializer_list::size_type"
-            | "std::__cxx11::basic_string::size_type"
-            | "std::vector::size_type" => Some(CppType::PointerSizedInteger {
-                path: CppPath::from_good_str("size_t"),
-                is_signed: false,
-            }),
-            _ => None,
-        }
+        self.data.config.special_typedefs().get(name).cloned()
     }
 
     /// Parses a function `entity`.
@@ -933,20 +1532,19 @@ impl CppParser<'_, '_> {
     fn parse_function(&mut self, entity: Entity<'_>) -> Result<()> {
         let class_name = match entity.get_semantic_parent() {
             Some(p) => match p.get_kind() {
-                EntityKind::ClassDecl | EntityKind::ClassTemplate | EntityKind::StructDecl => {
-                    match get_path(p) {
-                        Ok(class_name) => Some(class_name),
-                        Err(msg) => {
-                            bail!(
-                                "function parent is a class but it doesn't have a name: {}",
-                                msg
-                            );
-                        }
+                EntityKind::ClassDecl
+                | EntityKind::ClassTemplate
+                | EntityKind::StructDecl
+                | EntityKind::UnionDecl
+                | EntityKind::ClassTemplatePartialSpecialization => match self.get_full_path(p) {
+                    Ok(class_name) => Some(class_name),
+                    Err(msg) => {
+                        bail!(
+                            "function parent is a class but it doesn't have a name: {}",
+                            msg
+                        );
                     }
-                }
-                EntityKind::ClassTemplatePartialSpecialization => {
-                    bail!("this function is part of a template partial specialization");
-                }
+                },
                 _ => None,
             },
             None => None,
@@ -1010,35 +1608,14 @@ impl CppParser<'_, '_> {
                         clang_type.get_display_name()
                     )
                 })?;
-            let mut has_default_value = false;
-            for token in argument_entity
-                .get_range()
-                .ok_or_else(|| {
-                    format_err!(
-                        "failed to get range from argument entity: {:?}",
-                        argument_entity
-                    )
-                })?
-                .tokenize()
-            {
-                let spelling = token.get_spelling();
-                if spelling == "=" {
-                    has_default_value = true;
-                    break;
-                }
-                if spelling == "{" {
-                    // clang sometimes reports incorrect range for arguments
-                    break;
-                }
-            }
             arguments.push(CppFunctionArgument {
                 name,
                 argument_type,
-                has_default_value,
+                has_default_value: has_default_value(argument_entity)?,
             });
         }
 
-        let mut name_with_namespace = get_path(entity)?;
+        let mut name_with_namespace = self.get_full_path(entity)?;
 
         let mut name = entity
             .get_name()
@@ -1185,6 +1762,22 @@ impl CppParser<'_, '_> {
             Some(token_strings.join(" "))
         };
 
+        let function_type = entity
+            .get_type()
+            .ok_or_else(|| err_msg("failed to get function type"))?;
+        let is_deleted = declaration_code
+            .as_ref()
+            .map_or(false, |code| is_deleted_declaration(code));
+
+        // friend functions declared in a class body belong to the enclosing namespace
+        let is_friend = class_name.is_none()
+            && entity
+                .get_lexical_parent()
+                .map_or(false, |p| match p.get_kind() {
+                    EntityKind::ClassDecl | EntityKind::StructDecl | EntityKind::UnionDecl => true,
+                    _ => false,
+                });
+
         let function = CppFunction {
             path: name_with_namespace,
             operator: method_operator,
@@ -1207,6 +1800,17 @@ impl CppParser<'_, '_> {
                     // not all signals are detected here! see CppData::detect_signals_and_slots
                     is_signal,
                     is_slot: false,
+                    ref_qualifier: match function_type.get_ref_qualifier() {
+                        Some(RefQualifier::LValue) => CppRefQualifier::LValue,
+                        Some(RefQualifier::RValue) => CppRefQualifier::RValue,
+                        None => CppRefQualifier::None,
+                    },
+                    is_defaulted: entity.is_defaulted(),
+                    is_explicit: is_explicit_function(
+                        entity,
+                        declaration_code.as_ref().map(String::as_str),
+                    ),
+                    inherited_from: None,
                 })
             } else {
                 None
@@ -1215,14 +1819,18 @@ impl CppParser<'_, '_> {
             allows_variadic_arguments,
             return_type: return_type_parsed,
             cast: None,
+            is_noexcept: is_noexcept_function(entity),
+            is_deleted,
+            is_friend,
             declaration_code,
         };
 
-        self.add_output(
+        let id = self.add_output(
             self.entity_include_file(entity)?,
             get_origin_location(entity)?,
             CppItem::Function(function),
         )?;
+        self.add_annotations(id, entity);
 
         Ok(())
     }
@@ -1237,16 +1845,21 @@ impl CppParser<'_, '_> {
             )
         })?;
         let enum_name = get_path(entity)?;
-        self.add_output(
+        let id = self.add_output(
             include_file.clone(),
             get_origin_location(entity)?,
             CppItem::Type(CppTypeDeclaration {
                 kind: CppTypeDeclarationKind::Enum,
                 path: enum_name.clone(),
+                special_members: None,
             }),
         )?;
+        self.add_annotations(id, entity);
         for child in entity.get_children() {
             if child.get_kind() == EntityKind::EnumConstantDecl {
+                if has_skip_annotation(child) {
+                    continue;
+                }
                 let val = child
                     .get_enum_constant_value()
                     .ok_or_else(|| err_msg("failed to get value of enum variant"))?;
@@ -1254,7 +1867,7 @@ impl CppParser<'_, '_> {
                 let value_name = child
                     .get_name()
                     .ok_or_else(|| err_msg("failed to get name of enum variant"))?;
-                self.add_output(
+                let id = self.add_output(
                     include_file.clone(),
                     get_origin_location(child)?,
                     CppItem::EnumValue(CppEnumValue {
@@ -1262,6 +1875,7 @@ impl CppParser<'_, '_> {
                         value: val.0,
                     }),
                 )?;
+                self.add_annotations(id, child);
             }
         }
         Ok(())
@@ -1269,6 +1883,9 @@ impl CppParser<'_, '_> {
 
     /// Parses a class field `entity`.
     fn parse_class_field(&mut self, entity: Entity<'_>, class_type: &CppPath) -> Result<()> {
+        if has_skip_annotation(entity) {
+            return Ok(());
+        }
         let include_file = self
             .entity_include_file(entity)
             .with_context(|_| err_msg("Origin of class field is unknown"))?;
@@ -1281,7 +1898,7 @@ impl CppParser<'_, '_> {
         let field_type = self
             .parse_type(field_clang_type, &get_context_template_args(entity))
             .with_context(|_| err_msg("failed to parse field type"))?;
-        self.add_output(
+        let id = self.add_output(
             include_file,
             get_origin_location(entity)?,
             CppItem::ClassField(CppClassField {
@@ -1293,9 +1910,50 @@ impl CppParser<'_, '_> {
                     Accessibility::Private => CppVisibility::Private,
                 },
                 is_static: entity.get_kind() == EntityKind::VarDecl,
+                bit_field_width: if entity.is_bit_field() {
+                    entity.get_bit_field_width()
+                } else {
+                    None
+                },
             }),
         )?;
+        self.add_annotations(id, entity);
+        Ok(())
+    }
 
+    /// Parses a typedef or a type alias `entity`.
+    fn parse_type_alias(&mut self, entity: Entity<'_>) -> Result<()> {
+        let include_file = self
+            .entity_include_file(entity)
+            .with_context(|_| err_msg("Origin of type alias is unknown"))?;
+        let path = get_path(entity)?;
+        if self.parse_special_typedef(&path.to_cpp_code()?).is_some() {
+            // the typedef is already represented by a special type
+            return Ok(());
+        }
+        let underlying_type = entity
+            .get_typedef_underlying_type()
+            .ok_or_else(|| err_msg("failed to get underlying type"))?;
+        let target_type = self
+            .parse_type(underlying_type, &get_context_template_args(entity))
+            .with_context(|_| err_msg("failed to parse underlying type"))?;
+        if target_type.is_or_contains_template_parameter() {
+            bail!("type aliases depending on template parameters are not supported");
+        }
+        match &target_type {
+            CppType::Class(target_path) | CppType::Enum { path: target_path }
+                if target_path == &path =>
+            {
+                // `typedef struct A A;`
+                return Ok(());
+            }
+            _ => {}
+        }
+        self.add_output(
+            include_file,
+            get_origin_location(entity)?,
+            CppItem::TypeAlias(CppTypeAlias { path, target_type }),
+        )?;
         Ok(())
     }
 
@@ -1326,7 +1984,7 @@ impl CppParser<'_, '_> {
                         Accessibility::Private => CppVisibility::Private,
                     },
                     base_index,
-                    derived_class_type: get_path(parent)?,
+                    derived_class_type: self.get_full_path(parent)?,
                 }),
             )?;
         } else {
@@ -1344,9 +2002,11 @@ impl CppParser<'_, '_> {
                 entity
             )
         })?;
-        let full_name = get_path(entity)?;
+        let full_name = self.get_full_path(entity)?;
         let template_arguments = get_template_arguments(entity);
-        if entity.get_kind() == EntityKind::ClassTemplate {
+        if entity.get_kind() == EntityKind::ClassTemplate
+            || entity.get_kind() == EntityKind::ClassTemplatePartialSpecialization
+        {
             if entity
                 .get_children()
                 .into_iter()
@@ -1362,6 +2022,11 @@ impl CppParser<'_, '_> {
         } else if template_arguments.is_some() {
             bail!("unexpected template arguments");
         }
+        let mut special_members = if template_arguments.is_none() {
+            Some(parse_special_members(entity))
+        } else {
+            None
+        };
         let mut current_base_index = 0;
         for child in entity.get_children() {
             if child.get_kind() == EntityKind::FieldDecl || child.get_kind() == EntityKind::VarDecl
@@ -1373,6 +2038,20 @@ impl CppParser<'_, '_> {
                         err
                     );
                     trace!("entity: {:?}", entity);
+                    self.report_skipped(child, CppParserEntityKind::ClassField, &err);
+                    if let Some(special_members) = &mut special_members {
+                        special_members.has_unknown_members = true;
+                    }
+                } else if has_skip_annotation(child) {
+                    if let Some(special_members) = &mut special_members {
+                        special_members.has_unknown_members = true;
+                    }
+                }
+            }
+            if is_anonymous_member(child, entity) {
+                self.parse_anonymous_member(child, &full_name);
+                if let Some(special_members) = &mut special_members {
+                    special_members.has_unknown_members = true;
                 }
             }
             if child.get_kind() == EntityKind::BaseSpecifier {
@@ -1382,6 +2061,21 @@ impl CppParser<'_, '_> {
                         get_full_name_display(entity),
                         err
                     );
+                    // base specifiers don't have names, so the base type
+                    // is reported along with the derived class
+                    self.report.add_error(
+                        format!(
+                            "{} : {}",
+                            get_full_name_display(entity),
+                            child.get_display_name().unwrap_or_default()
+                        ),
+                        get_origin_location(child).ok(),
+                        CppParserEntityKind::ClassBase,
+                        &err,
+                    );
+                    if let Some(special_members) = &mut special_members {
+                        special_members.has_unknown_members = true;
+                    }
                 }
                 current_base_index += 1;
             }
@@ -1389,17 +2083,48 @@ impl CppParser<'_, '_> {
                 bail!("Non-type template parameter is not supported");
             }
         }
-        self.add_output(
+        let kind = if entity.get_kind() == EntityKind::UnionDecl {
+            CppTypeDeclarationKind::Union
+        } else {
+            CppTypeDeclarationKind::Class
+        };
+        let id = self.add_output(
             include_file,
             get_origin_location(entity).unwrap(),
             CppItem::Type(CppTypeDeclaration {
-                kind: CppTypeDeclarationKind::Class,
+                kind,
                 path: full_name,
+                special_members,
             }),
         )?;
+        self.add_annotations(id, entity);
         Ok(())
     }
 
+    /// Parses fields of an anonymous struct or union `entity`
+    /// as fields of the enclosing class `class_type`.
+    fn parse_anonymous_member(&mut self, entity: Entity<'_>, class_type: &CppPath) {
+        if entity.get_accessibility().unwrap_or(Accessibility::Public) != Accessibility::Public {
+            return; // skipping private stuff
+        }
+        for child in entity.get_children() {
+            if child.get_kind() == EntityKind::FieldDecl {
+                if let Err(err) = self.parse_class_field(child, class_type) {
+                    debug!(
+                        "failed to parse anonymous member field: {}: {}",
+                        get_full_name_display(child),
+                        err
+                    );
+                    trace!("entity: {:?}", entity);
+                    self.report_skipped(child, CppParserEntityKind::ClassField, &err);
+                }
+            }
+            if is_anonymous_member(child, entity) {
+                self.parse_anonymous_member(child, class_type);
+            }
+        }
+    }
+
     /// Determines file path of the include file this `entity` is located in.
     fn entity_include_path(&self, entity: Entity<'_>) -> Result<PathBuf> {
         if let Some(location) = entity.get_location() {
@@ -1428,6 +2153,12 @@ impl CppParser<'_, '_> {
         if entity.get_kind() == EntityKind::TranslationUnit {
             return Ok(true);
         }
+        if entity.get_kind() == EntityKind::Namespace
+            && entity.get_name().as_ref().map(String::as_str)
+                == Some(TEMPLATE_INSTANTIATIONS_NAMESPACE)
+        {
+            return Ok(false);
+        }
         if let Ok(file_path) = self.entity_include_path(entity) {
             let file_path = Path::new(&file_path);
             if !self.current_target_paths.is_empty()
@@ -1441,25 +2172,132 @@ impl CppParser<'_, '_> {
         } else {
             return Ok(false);
         }
-        if let Ok(full_name) = get_path(entity) {
-            if let Some(hook) = self.data.config.cpp_parser_path_hook() {
-                if !hook(&full_name)? {
-                    return Ok(false);
-                }
-            }
-        } else {
-            return Ok(false);
+        let full_name = match get_path(entity) {
+            Ok(full_name) => full_name,
+            Err(_) => return Ok(false),
+        };
+        if let Some(hook) = self.data.config.cpp_parser_path_hook() {
+            return Ok(hook(&full_name)? && !has_skip_annotation(entity));
         }
-        Ok(true)
+        Ok(!has_skip_annotation(entity))
     }
 
-    fn parse(&mut self, entity: Entity<'_>) -> Result<()> {
+    fn parse(&mut self, translation_unit: Entity<'_>) -> Result<()> {
         debug!("Parsing types");
-        self.parse_types(entity)?;
+        self.parse_types(translation_unit)?;
         debug!("Parsing functions");
-        self.parse_functions(entity)?;
-        for hook in self.data.config.after_cpp_parser_hooks() {
-            hook(self.data, &self.output)?;
+        self.parse_functions(translation_unit)?;
+        self.parse_template_instantiations(translation_unit)?;
+        run_after_cpp_parser_hooks(self.data, &self.output)
+    }
+
+    /// Checks each of `self.template_instantiations` separately and removes
+    /// the ones that fail to compile. Returns false if all of them are valid.
+    fn remove_invalid_template_instantiations(&mut self) -> Result<bool> {
+        if self.template_instantiations.is_empty() {
+            return Ok(false);
+        }
+        info!("Checking requested template instantiations");
+        let mut is_changed = false;
+        for instantiation in mem::replace(&mut self.template_instantiations, Vec::new()) {
+            let result = try_run_clang(
+                &self.data.config,
+                &self.data.workspace.tmp_path(),
+                template_instantiations_code(&[instantiation.clone()]),
+                |_| Ok(()),
+            )?;
+            if let Err(diagnostics) = result {
+                let error = format_err!("template instantiation failed:\n{}", diagnostics);
+                self.report_skipped_instantiation(&instantiation, &error);
+                is_changed = true;
+            } else {
+                self.template_instantiations.push(instantiation);
+            }
+        }
+        Ok(is_changed)
+    }
+
+    /// Records a template instantiation requested in the config
+    /// that couldn't be added because of `error`.
+    fn report_skipped_instantiation(
+        &mut self,
+        instantiation: &TemplateInstantiation,
+        error: &Error,
+    ) {
+        warn!(
+            "skipping requested template instantiation: {}: {}",
+            instantiation.to_cpp_code(),
+            error
+        );
+        self.report.add_error(
+            instantiation.to_cpp_code(),
+            None,
+            CppParserEntityKind::TemplateInstantiation,
+            error,
+        );
+    }
+
+    /// Adds class template instantiations requested in the config
+    /// (see `template_instantiations_code`).
+    fn parse_template_instantiations(&mut self, translation_unit: Entity<'_>) -> Result<()> {
+        let namespace = translation_unit.get_children().into_iter().find(|child| {
+            child.get_kind() == EntityKind::Namespace
+                && child.get_name().as_ref().map(String::as_str)
+                    == Some(TEMPLATE_INSTANTIATIONS_NAMESPACE)
+        });
+        let namespace = if let Some(namespace) = namespace {
+            namespace
+        } else {
+            return Ok(());
+        };
+        let typedefs = namespace
+            .get_children()
+            .into_iter()
+            .filter(|child| child.get_kind() == EntityKind::TypedefDecl);
+        let instantiations = self.template_instantiations.clone();
+        let mut types = Vec::new();
+        for (entity, instantiation) in typedefs.zip(&instantiations) {
+            let underlying_type = entity
+                .get_typedef_underlying_type()
+                .ok_or_else(|| err_msg("failed to get underlying type"))?;
+            let type1 = match self.parse_type(underlying_type, &[]) {
+                Ok(CppType::Class(path)) if path.last().template_arguments.is_some() => {
+                    CppType::Class(path)
+                }
+                Ok(_) => {
+                    let error = err_msg("not a class template instantiation");
+                    self.report_skipped_instantiation(instantiation, &error);
+                    continue;
+                }
+                Err(error) => {
+                    self.report_skipped_instantiation(instantiation, &error);
+                    continue;
+                }
+            };
+            types.push(type1);
+        }
+        add_template_instantiations(self.data, &types)?;
+
+        for type1 in &types {
+            if let CppType::Class(path) = type1 {
+                let is_available = self
+                    .data
+                    .db
+                    .all_cpp_items()
+                    .filter_map(|item| item.item.as_type_ref())
+                    .any(|item| &item.path == path);
+                if is_available {
+                    debug!(
+                        "added requested template instantiation: {}",
+                        path.to_cpp_pseudo_code()
+                    );
+                } else {
+                    warn!(
+                        "class template not found for requested instantiation: {}",
+                        path.to_cpp_pseudo_code()
+                    );
+                }
+            }
         }
         Ok(())
     }
@@ -1483,16 +2321,24 @@ impl CppParser<'_, '_> {
                             error
                         );
                         trace!("entity: {:?}", entity);
+                        self.report_skipped(entity, CppParserEntityKind::Enum, &error);
                     }
                 }
             }
-            EntityKind::ClassDecl | EntityKind::ClassTemplate | EntityKind::StructDecl => {
+            EntityKind::ClassDecl
+            | EntityKind::ClassTemplate
+            | EntityKind::ClassTemplatePartialSpecialization
+            | EntityKind::StructDecl
+            | EntityKind::UnionDecl => {
                 if entity.get_accessibility() == Some(Accessibility::Private) {
                     return Ok(()); // skipping private stuff
                 }
+                let is_partial_specialization =
+                    entity.get_kind() == EntityKind::ClassTemplatePartialSpecialization;
                 let ok = entity.get_name().is_some() && // not an anonymous struct
                     entity.is_definition() && // not a forward declaration
-                    entity.get_template().is_none(); // not a template specialization
+                    // not a full template specialization
+                    (entity.get_template().is_none() || is_partial_specialization);
                 if ok {
                     if let Err(error) = self.parse_class(entity) {
                         debug!(
@@ -1501,9 +2347,24 @@ impl CppParser<'_, '_> {
                             error
                         );
                         trace!("entity: {:?}", entity);
+                        self.report_skipped(entity, CppParserEntityKind::Class, &error);
                     }
                 }
             }
+            EntityKind::TypedefDecl | EntityKind::TypeAliasDecl => {
+                if entity.get_accessibility() == Some(Accessibility::Private) {
+                    return Ok(()); // skipping private stuff
+                }
+                if let Err(error) = self.parse_type_alias(entity) {
+                    debug!(
+                        "failed to parse type alias: {}: {}",
+                        get_full_name_display(entity),
+                        error
+                    );
+                    trace!("entity: {:?}", entity);
+                    self.report_skipped(entity, CppParserEntityKind::TypeAlias, &error);
+                }
+            }
             EntityKind::Namespace => match get_path(entity) {
                 Ok(path) => {
                     self.add_output(
@@ -1520,9 +2381,11 @@ impl CppParser<'_, '_> {
             EntityKind::TranslationUnit
             | EntityKind::Namespace
             | EntityKind::StructDecl
+            | EntityKind::UnionDecl
             | EntityKind::ClassDecl
             | EntityKind::UnexposedDecl
-            | EntityKind::ClassTemplate => {
+            | EntityKind::ClassTemplate
+            | EntityKind::ClassTemplatePartialSpecialization => {
                 for c in entity.get_children() {
                     self.parse_types(c)?;
                 }
@@ -1532,8 +2395,38 @@ impl CppParser<'_, '_> {
         Ok(())
     }
 
+    /// Parses functions declared in a `friend` declaration `entity` inside a class body.
+    /// These functions (e.g. hidden friend operators) are added as free functions
+    /// of the enclosing namespace.
+    fn parse_friend_functions(&mut self, entity: Entity<'_>) -> Result<()> {
+        for child in entity.get_children() {
+            match child.get_kind() {
+                EntityKind::FunctionDecl | EntityKind::FunctionTemplate => {}
+                _ => continue,
+            }
+            if !get_context_template_args(entity).is_empty() {
+                if self.should_process_entity(child)? {
+                    trace!(
+                        "skipping friend function of a class template: {}",
+                        get_full_name_display(child)
+                    );
+                    let error = err_msg(
+                        "friend functions of class templates depend on template parameters",
+                    );
+                    self.report_skipped(child, CppParserEntityKind::Function, &error);
+                }
+                continue;
+            }
+            self.parse_functions(child)?;
+        }
+        Ok(())
+    }
+
     /// Parses methods in translation unit `entity`.
     fn parse_functions(&mut self, entity: Entity<'_>) -> Result<()> {
+        if entity.get_kind() == EntityKind::FriendDecl {
+            return self.parse_friend_functions(entity);
+        }
         if !self.should_process_entity(entity)? {
             return Ok(());
         }
@@ -1552,12 +2445,10 @@ impl CppParser<'_, '_> {
                     );
                     print_trace(&error, Some(log::Level::Trace));
                     trace!("entity: {:?}", entity);
+                    self.report_skipped(entity, CppParserEntityKind::Function, &error);
                 }
             }
-            EntityKind::StructDecl
-            | EntityKind::ClassDecl
-            | EntityKind::ClassTemplate
-            | EntityKind::ClassTemplatePartialSpecialization => {
+            EntityKind::StructDecl | EntityKind::ClassDecl | EntityKind::ClassTemplate => {
                 if let Some(name) = entity.get_display_name() {
                     if let Ok(parent_type) = self.parse_unexposed_type(
                         None,
@@ -1571,10 +2462,19 @@ impl CppParser<'_, '_> {
                                     .any(|x| !x.is_template_parameter())
                                 {
                                     trace!(
-                                        "skipping template partial specialization: {}",
+                                        "skipping template specialization: {}",
                                         get_full_name_display(entity),
                                     );
                                     trace!("entity: {:?}", entity);
+                                    self.report.add(CppParserSkippedEntity {
+                                        display_name: get_full_name_display(entity),
+                                        location: get_origin_location(entity).ok(),
+                                        kind: CppParserEntityKind::Class,
+                                        reason: CppParserSkipReason::TemplateSpecialization,
+                                        message: "members of template specializations \
+                                                  are not supported"
+                                            .into(),
+                                    });
                                     return Ok(());
                                 }
                             }
@@ -1588,9 +2488,11 @@ impl CppParser<'_, '_> {
             EntityKind::TranslationUnit
             | EntityKind::Namespace
             | EntityKind::StructDecl
+            | EntityKind::UnionDecl
             | EntityKind::ClassDecl
             | EntityKind::UnexposedDecl
-            | EntityKind::ClassTemplate => {
+            | EntityKind::ClassTemplate
+            | EntityKind::ClassTemplatePartialSpecialization => {
                 for c in entity.get_children() {
                     self.parse_functions(c)?;
                 }
@@ -0,0 +1,224 @@
+//! Summary of C++ declarations skipped by the parser.
+
+use crate::cpp_data::CppOriginLocation;
+use itertools::Itertools;
+use ritual_common::errors::{Error, Result};
+use ritual_common::file_utils::create_file;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::Write;
+use std::path::Path;
+
+/// Kind of a C++ declaration skipped by the parser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CppParserEntityKind {
+    Enum,
+    /// Class, struct or union
+    Class,
+    ClassField,
+    /// Base specifier of a class
+    ClassBase,
+    /// Typedef or type alias
+    TypeAlias,
+    /// Function or method
+    Function,
+    /// Class template instantiation requested in the config
+    TemplateInstantiation,
+}
+
+impl fmt::Display for CppParserEntityKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            CppParserEntityKind::Enum => "enum",
+            CppParserEntityKind::Class => "class",
+            CppParserEntityKind::ClassField => "field",
+            CppParserEntityKind::ClassBase => "base",
+            CppParserEntityKind::TypeAlias => "type alias",
+            CppParserEntityKind::Function => "function",
+            CppParserEntityKind::TemplateInstantiation => "template instantiation",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// Cause of a parser failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CppParserSkipReason {
+    /// A type of unsupported kind is used (e.g. a volatile or an unexposed type)
+    UnsupportedType,
+    /// The declaration depends on template parameters in an unsupported way
+    TemplateParameter,
+    /// The declaration or a type used by it is anonymous
+    AnonymousEntity,
+    /// Location of the declaration is unknown
+    MissingLocation,
+    /// A type used by the declaration is private or blacklisted
+    InaccessibleType,
+    /// Members of full template specializations are not supported
+    TemplateSpecialization,
+    /// Any other failure
+    Other,
+}
+
+impl CppParserSkipReason {
+    /// Determines the cause of a parser failure by examining messages of `error`,
+    /// starting from the root cause.
+    pub fn from_error(error: &Error) -> Self {
+        let messages = error
+            .iter_chain()
+            .map(|cause| cause.to_string().to_lowercase())
+            .collect_vec();
+        for message in messages.iter().rev() {
+            let contains_any = |patterns: &[&str]| patterns.iter().any(|p| message.contains(p));
+            if contains_any(&["location", "origin of", "file path", "no file"]) {
+                return CppParserSkipReason::MissingLocation;
+            }
+            if contains_any(&["anonymous", "doesn't have a name"]) {
+                return CppParserSkipReason::AnonymousEntity;
+            }
+            if contains_any(&["template parameter", "template argument"]) {
+                return CppParserSkipReason::TemplateParameter;
+            }
+            if contains_any(&["private class", "blacklisted"]) {
+                return CppParserSkipReason::InaccessibleType;
+            }
+            if contains_any(&[
+                "unsupported kind of type",
+                "unexposed type",
+                "volatile",
+                "array of void",
+                "member function pointer",
+                "member function type",
+                "member pointer",
+            ]) {
+                return CppParserSkipReason::UnsupportedType;
+            }
+        }
+        CppParserSkipReason::Other
+    }
+}
+
+impl fmt::Display for CppParserSkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            CppParserSkipReason::UnsupportedType => "unsupported type",
+            CppParserSkipReason::TemplateParameter => "unsupported use of template parameters",
+            CppParserSkipReason::AnonymousEntity => "anonymous entity",
+            CppParserSkipReason::MissingLocation => "missing location",
+            CppParserSkipReason::InaccessibleType => "private or blacklisted type",
+            CppParserSkipReason::TemplateSpecialization => "template specialization",
+            CppParserSkipReason::Other => "other",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// Information about a C++ declaration the parser didn't add to the database.
+#[derive(Debug, Clone)]
+pub struct CppParserSkippedEntity {
+    /// C++ name of the entity (pseudo code)
+    pub display_name: String,
+    /// Location of the declaration, if available
+    pub location: Option<CppOriginLocation>,
+    pub kind: CppParserEntityKind,
+    pub reason: CppParserSkipReason,
+    /// Error message, including all causes
+    pub message: String,
+}
+
+/// Collection of declarations skipped during a parser run.
+#[derive(Debug, Default)]
+pub struct CppParserReport {
+    skipped: Vec<CppParserSkippedEntity>,
+}
+
+impl CppParserReport {
+    pub fn add(&mut self, entity: CppParserSkippedEntity) {
+        self.skipped.push(entity);
+    }
+
+    /// Records a declaration skipped because of `error`.
+    /// The reason is determined by `CppParserSkipReason::from_error`.
+    pub fn add_error(
+        &mut self,
+        display_name: String,
+        location: Option<CppOriginLocation>,
+        kind: CppParserEntityKind,
+        error: &Error,
+    ) {
+        self.add(CppParserSkippedEntity {
+            display_name,
+            location,
+            kind,
+            reason: CppParserSkipReason::from_error(error),
+            message: error.iter_chain().join(": "),
+        });
+    }
+
+    /// Moves all entries of `other` to `self`.
+    pub fn append(&mut self, other: CppParserReport) {
+        self.skipped.extend(other.skipped);
+    }
+
+    pub fn skipped(&self) -> &[CppParserSkippedEntity] {
+        &self.skipped
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.skipped.is_empty()
+    }
+
+    /// Generates the text of the report. Skipped declarations are
+    /// grouped by reason and then by header file. Reasons that caused
+    /// the most skipped declarations come first.
+    pub fn to_text(&self) -> String {
+        let mut groups = BTreeMap::<_, BTreeMap<_, Vec<_>>>::new();
+        for item in &self.skipped {
+            let file = item
+                .location
+                .as_ref()
+                .map_or("[unknown location]", |l| l.include_file_path.as_str());
+            groups
+                .entry(item.reason)
+                .or_default()
+                .entry(file)
+                .or_default()
+                .push(item);
+        }
+        let mut groups = groups
+            .into_iter()
+            .map(|(reason, files)| {
+                let count: usize = files.values().map(Vec::len).sum();
+                (reason, count, files)
+            })
+            .collect_vec();
+        groups.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        let mut text = format!("Total skipped: {}\n", self.skipped.len());
+        for (reason, count, files) in groups {
+            text += &format!("\n{} ({})\n", reason, count);
+            for (file, items) in files {
+                text += &format!("  {} ({})\n", file, items.len());
+                for item in items {
+                    let line = item
+                        .location
+                        .as_ref()
+                        .map(|l| format!("{}:{}: ", l.line, l.column))
+                        .unwrap_or_default();
+                    text += &format!(
+                        "    {}{} {}: {}\n",
+                        line, item.kind, item.display_name, item.message
+                    );
+                }
+            }
+        }
+        text
+    }
+
+    /// Writes the report to a text file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut file = create_file(path)?;
+        write!(file, "{}", self.to_text())?;
+        Ok(())
+    }
+}
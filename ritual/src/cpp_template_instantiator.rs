@@ -1,32 +1,29 @@
 use crate::cpp_data::{CppItem, CppPath, CppPathItem, CppTypeDeclaration};
-use crate::cpp_function::{CppFunction, CppFunctionArgument, CppOperator};
-use crate::cpp_type::CppType;
-use crate::database::{DatabaseClient, ItemWithSource};
+use crate::cpp_function::{CppFunction, CppOperator};
+use crate::cpp_type::{CppTemplateParameter, CppType};
+use crate::database::{DatabaseClient, ItemId, ItemWithSource};
 use crate::processor::ProcessorData;
-use log::{debug, trace};
+use itertools::Itertools;
+use log::{debug, trace, warn};
 use ritual_common::errors::{bail, err_msg, Result};
 use ritual_common::utils::MapIfOk;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 
-/// Returns true if `type1` is a known template instantiation.
-fn check_template_type(data: &ProcessorData<'_>, type1: &CppType) -> Result<()> {
+/// Returns `Err` if `type1` contains an unknown template instantiation.
+fn check_template_type(known_types: &HashSet<CppPath>, type1: &CppType) -> Result<()> {
     match &type1 {
         CppType::Class(path) => {
             if let Some(template_arguments) = &path.last().template_arguments {
-                let is_available = data
-                    .db
-                    .all_cpp_items()
-                    .filter_map(|i| i.item.as_type_ref())
-                    .any(|inst| &inst.path == path);
-                if !is_available {
+                if !known_types.contains(path) {
                     bail!("type is not available: {:?}", type1);
                 }
                 for arg in template_arguments {
-                    check_template_type(data, arg)?;
+                    check_template_type(known_types, arg)?;
                 }
             }
         }
         CppType::PointerLike { ref target, .. } => {
-            check_template_type(data, target)?;
+            check_template_type(known_types, target)?;
         }
         _ => {}
     }
@@ -41,19 +38,36 @@ pub fn instantiate_function(
     function: &CppFunction,
     nested_level: usize,
     arguments: &[CppType],
+) -> Result<CppFunction> {
+    instantiate_function_levels(function, &[(nested_level, arguments.to_vec())])
+}
+
+/// Same as `instantiate_function`, but replaces template parameters
+/// of multiple nested levels (e.g. for methods of nested class templates).
+fn instantiate_function_levels(
+    function: &CppFunction,
+    levels: &[(usize, Vec<CppType>)],
 ) -> Result<CppFunction> {
     let mut new_method = function.clone();
-    new_method.arguments.clear();
-    for arg in &function.arguments {
-        new_method.arguments.push(CppFunctionArgument {
-            name: arg.name.clone(),
-            has_default_value: arg.has_default_value,
-            argument_type: arg.argument_type.instantiate(nested_level, arguments)?,
-        });
+    let mut conversion_type = None;
+    for (nested_level, arguments) in levels {
+        for arg in &mut new_method.arguments {
+            arg.argument_type = arg.argument_type.instantiate(*nested_level, arguments)?;
+        }
+        new_method.return_type = new_method
+            .return_type
+            .instantiate(*nested_level, arguments)?;
+        new_method.path = new_method.path.instantiate(*nested_level, arguments)?;
+
+        if let Some(operator) = &mut new_method.operator {
+            if let CppOperator::Conversion(cpp_type) = operator {
+                let r = cpp_type.instantiate(*nested_level, arguments)?;
+                *cpp_type = r.clone();
+                conversion_type = Some(r);
+            }
+        }
     }
-    new_method.return_type = function.return_type.instantiate(nested_level, arguments)?;
 
-    new_method.path = new_method.path.instantiate(nested_level, arguments)?;
     if let Some(args) = &new_method.path.last().template_arguments {
         if args
             .iter()
@@ -71,14 +85,6 @@ pub fn instantiate_function(
         }
     }
 
-    let mut conversion_type = None;
-    if let Some(operator) = &mut new_method.operator {
-        if let CppOperator::Conversion(cpp_type) = operator {
-            let r = cpp_type.instantiate(nested_level, arguments)?;
-            *cpp_type = r.clone();
-            conversion_type = Some(r);
-        }
-    }
     if new_method
         .all_involved_types()
         .iter()
@@ -102,114 +108,461 @@ pub fn instantiate_function(
 
 // TODO: instantiations of QObject::findChild and QObject::findChildren should be available
 
-#[derive(Debug)]
-struct Substitution<'a> {
-    nested_level: usize,
-    arguments: &'a [CppType],
+/// Template arguments deduced by matching a template pattern
+/// (e.g. `QList<T*>`) against a concrete type (e.g. `QList<QObject*>`).
+#[derive(Debug, Default, Clone)]
+pub struct Substitution {
+    /// Deduced arguments, grouped by nested level of template parameters
+    arguments: BTreeMap<usize, Vec<Option<CppType>>>,
 }
 
-fn find_suitable_template_arguments<'a>(
-    path: &CppPath,
-    db: &'a DatabaseClient,
-) -> Result<Vec<Substitution<'a>>> {
-    let mut current_path = path.clone();
-    let mut result = Vec::new();
-    loop {
-        if let Some(template_arguments) = &current_path.last().template_arguments {
-            assert!(!template_arguments.is_empty());
-            if template_arguments.iter().all(|t| t.is_template_parameter()) {
-                let items =
-                    db.cpp_items()
-                        .filter_map(|item| item.item.as_type_ref())
-                        .filter(|type1| {
-                            type1.path.parent_parts().ok() == current_path.parent_parts().ok()
-                                && type1.path.last().name == current_path.last().name
-                                && type1.path.last().template_arguments.as_ref().map_or(
-                                    false,
-                                    |args| {
-                                        !args.iter().all(CppType::is_or_contains_template_parameter)
-                                    },
-                                )
-                        })
-                        .map_if_ok(|type1| {
-                            let nested_level =
-                                if let CppType::TemplateParameter(param) = &template_arguments[0] {
-                                    param.nested_level
-                                } else {
-                                    bail!("only template parameters can be here");
-                                };
-
-                            let arguments = type1
-                                .path
-                                .last()
-                                .template_arguments
-                                .as_ref()
-                                .ok_or_else(|| {
-                                    err_msg("template instantiation must have template arguments")
-                                })?;
-
-                            Ok(Substitution {
-                                nested_level,
-                                arguments,
-                            })
-                        })?;
-                result.extend(items);
+impl Substitution {
+    fn bind(&mut self, param: &CppTemplateParameter, value: &CppType) -> bool {
+        let arguments = self.arguments.entry(param.nested_level).or_default();
+        if arguments.len() <= param.index {
+            arguments.resize(param.index + 1, None);
+        }
+        match &arguments[param.index] {
+            Some(old_value) => old_value == value,
+            None => {
+                arguments[param.index] = Some(value.clone());
+                true
             }
         }
-        if let Ok(p) = current_path.parent() {
-            current_path = p;
-        } else {
-            return Ok(result);
+    }
+
+    /// Returns deduced arguments for each nested level.
+    pub fn levels(&self) -> Result<Vec<(usize, Vec<CppType>)>> {
+        self.arguments
+            .iter()
+            .map_if_ok(|(nested_level, arguments)| -> Result<_> {
+                let arguments = arguments.iter().map_if_ok(|arg| {
+                    arg.clone()
+                        .ok_or_else(|| err_msg("template argument is not deduced"))
+                })?;
+                Ok((*nested_level, arguments))
+            })
+    }
+}
+
+/// Attempts to match `pattern` against `concrete` type,
+/// adding deduced template arguments to `substitution`.
+fn match_type(pattern: &CppType, concrete: &CppType, substitution: &mut Substitution) -> bool {
+    match (pattern, concrete) {
+        (CppType::TemplateParameter(param), _) => substitution.bind(param, concrete),
+        (
+            CppType::PointerLike {
+                kind,
+                is_const,
+                target,
+            },
+            CppType::PointerLike {
+                kind: concrete_kind,
+                is_const: concrete_is_const,
+                target: concrete_target,
+            },
+        ) => {
+            kind == concrete_kind
+                && is_const == concrete_is_const
+                && match_type(target, concrete_target, substitution)
+        }
+        (CppType::Class(path), CppType::Class(concrete_path)) => {
+            match_path(path, concrete_path, substitution)
         }
+        (
+            CppType::Array { element_type, size },
+            CppType::Array {
+                element_type: concrete_element_type,
+                size: concrete_size,
+            },
+        ) => size == concrete_size && match_type(element_type, concrete_element_type, substitution),
+        (CppType::FunctionPointer(function), CppType::FunctionPointer(concrete_function)) => {
+            function.allows_variadic_arguments == concrete_function.allows_variadic_arguments
+                && function.arguments.len() == concrete_function.arguments.len()
+                && match_type(
+                    &function.return_type,
+                    &concrete_function.return_type,
+                    substitution,
+                )
+                && function
+                    .arguments
+                    .iter()
+                    .zip(&concrete_function.arguments)
+                    .all(|(a, b)| match_type(a, b, substitution))
+        }
+        _ => pattern == concrete,
     }
 }
 
-fn instantiate_types(data: &mut ProcessorData<'_>) -> Result<()> {
-    loop {
-        let mut new_types = Vec::<ItemWithSource<_>>::new();
-        for type1 in data
-            .db
+/// Attempts to match `pattern` against `concrete` path,
+/// adding deduced template arguments to `substitution`.
+pub fn match_path(pattern: &CppPath, concrete: &CppPath, substitution: &mut Substitution) -> bool {
+    pattern.items().len() == concrete.items().len()
+        && pattern
+            .items()
+            .iter()
+            .zip(concrete.items())
+            .all(|(item, concrete_item)| {
+                if item.name != concrete_item.name {
+                    return false;
+                }
+                match (&item.template_arguments, &concrete_item.template_arguments) {
+                    (None, None) => true,
+                    (Some(args), Some(concrete_args)) => {
+                        args.len() == concrete_args.len()
+                            && args
+                                .iter()
+                                .zip(concrete_args)
+                                .all(|(a, b)| match_type(a, b, substitution))
+                    }
+                    _ => false,
+                }
+            })
+}
+
+/// Returns true if `path` is a template pattern (e.g. `QList<T>` or `QList<T*>`),
+/// i.e. its last item has template arguments containing template parameters.
+fn is_template_pattern(path: &CppPath) -> bool {
+    path.last()
+        .template_arguments
+        .as_ref()
+        .map_or(false, |args| {
+            args.iter().any(CppType::is_or_contains_template_parameter)
+        })
+}
+
+/// Returns true if template pattern `pattern` is more specialized than `other`,
+/// i.e. every type matched by `pattern` is also matched by `other`, but not vice versa.
+pub fn is_more_specialized(pattern: &CppPath, other: &CppPath) -> bool {
+    match_path(other, pattern, &mut Substitution::default())
+        && !match_path(pattern, other, &mut Substitution::default())
+}
+
+/// Maximum nesting depth of template arguments in instantiated types.
+/// Deeper instantiations are usually produced by recursive templates.
+const MAX_NESTING_DEPTH: usize = 8;
+
+/// Returns nesting depth of template arguments in `type1`
+/// (e.g. 0 for `int`, 1 for `QList<int>`, 2 for `QList<QList<int>>`).
+pub fn type_nesting_depth(type1: &CppType) -> usize {
+    match type1 {
+        CppType::Class(path) => path_nesting_depth(path),
+        CppType::PointerLike { target, .. } => type_nesting_depth(target),
+        CppType::Array { element_type, .. } => type_nesting_depth(element_type),
+        _ => 0,
+    }
+}
+
+/// Returns nesting depth of template arguments in `path`.
+pub fn path_nesting_depth(path: &CppPath) -> usize {
+    path.items()
+        .iter()
+        .filter_map(|item| item.template_arguments.as_ref())
+        .map(|args| 1 + args.iter().map(type_nesting_depth).max().unwrap_or(0))
+        .max()
+        .unwrap_or(0)
+}
+
+/// Returns true if `path` doesn't contain template parameters in any of its items.
+fn is_concrete(path: &CppPath) -> bool {
+    !path
+        .items()
+        .iter()
+        .filter_map(|item| item.template_arguments.as_ref())
+        .flatten()
+        .any(CppType::is_or_contains_template_parameter)
+}
+
+/// Returns true if any of the items of `path` has template arguments.
+fn has_template_arguments(path: &CppPath) -> bool {
+    path.items()
+        .iter()
+        .any(|item| item.template_arguments.is_some())
+}
+
+/// Returns names of items of `path` without template arguments.
+/// A pattern can only match paths with the same key.
+fn pattern_key(path: &CppPath) -> Vec<String> {
+    path.items().iter().map(|item| item.name.clone()).collect()
+}
+
+/// A class template (or its partial specialization, or a nested class of a class template)
+/// that can be instantiated.
+struct GenericType {
+    id: ItemId,
+    declaration: CppTypeDeclaration,
+}
+
+/// Types and template instantiations indexed for matching template patterns.
+struct TemplateIndex {
+    /// Paths of all known types, including dependencies.
+    known_types: HashSet<CppPath>,
+    /// Types with template parameters, including dependencies.
+    generic_types: Vec<GenericType>,
+    /// Indexes in `generic_types` by keys of their prefixes that are template patterns.
+    generic_types_by_prefix: HashMap<Vec<String>, Vec<usize>>,
+    /// Paths of concrete template instantiations of the current crate by their keys.
+    concrete_types: HashMap<Vec<String>, Vec<CppPath>>,
+}
+
+impl TemplateIndex {
+    fn new(db: &DatabaseClient) -> Self {
+        let mut index = TemplateIndex {
+            known_types: HashSet::new(),
+            generic_types: Vec::new(),
+            generic_types_by_prefix: HashMap::new(),
+            concrete_types: HashMap::new(),
+        };
+        for item in db
             .all_cpp_items()
             .filter_map(|item| item.filter_map(|item| item.as_type_ref()))
         {
-            trace!("class: {}", type1.item.path.to_cpp_pseudo_code());
-            for substitution in find_suitable_template_arguments(&type1.item.path, data.db)? {
-                trace!("found template instantiation: {:?}", substitution);
+            index.known_types.insert(item.item.path.clone());
+            if !is_concrete(&item.item.path) {
+                index.add_generic_type(item.id, item.item.clone());
+            }
+        }
+        for item in db.cpp_items().filter_map(|item| item.item.as_type_ref()) {
+            if is_concrete(&item.path) {
+                index.add_concrete_type(item.path.clone());
+            }
+        }
+        index
+    }
 
-                let new_type = CppTypeDeclaration {
-                    kind: type1.item.kind.clone(),
-                    path: type1
-                        .item
-                        .path
-                        .instantiate(substitution.nested_level, substitution.arguments)?,
-                };
+    /// Adds a type with template parameters and returns its index.
+    fn add_generic_type(&mut self, id: ItemId, declaration: CppTypeDeclaration) -> usize {
+        let index = self.generic_types.len();
+        for len in 1..=declaration.path.items().len() {
+            let prefix = CppPath::from_items(declaration.path.items()[..len].to_vec());
+            if is_template_pattern(&prefix) {
+                self.generic_types_by_prefix
+                    .entry(pattern_key(&prefix))
+                    .or_default()
+                    .push(index);
+            }
+        }
+        self.generic_types.push(GenericType { id, declaration });
+        index
+    }
 
-                if data
-                    .db
-                    .all_cpp_items()
-                    .filter_map(|item| item.item.as_type_ref())
-                    .any(|item| item.is_same(&new_type))
-                    || new_types.iter().any(|item| item.item == new_type)
-                {
-                    trace!("type already exists");
-                    continue;
+    fn add_concrete_type(&mut self, path: CppPath) {
+        if has_template_arguments(&path) {
+            self.concrete_types
+                .entry(pattern_key(&path))
+                .or_default()
+                .push(path);
+        }
+    }
+
+    /// Returns true if a declared class template partial specialization
+    /// is more suitable for `concrete` type than `pattern`.
+    fn has_more_specialized_match(&self, pattern: &CppPath, concrete: &CppPath) -> bool {
+        let candidates = match self.generic_types_by_prefix.get(&pattern_key(pattern)) {
+            Some(candidates) => candidates,
+            None => return false,
+        };
+        candidates
+            .iter()
+            .map(|&index| &self.generic_types[index].declaration.path)
+            .filter(|path| {
+                *path != pattern
+                    && path.items().len() == pattern.items().len()
+                    && path.parent_parts().ok() == pattern.parent_parts().ok()
+                    && is_template_pattern(path)
+            })
+            .any(|path| {
+                match_path(path, concrete, &mut Substitution::default())
+                    && is_more_specialized(path, pattern)
+            })
+    }
+
+    /// Attempts to match prefix of `path` of the same length as `concrete`
+    /// against `concrete`. Returns the deduced template arguments if `concrete`
+    /// should be instantiated from this prefix.
+    fn match_prefix(&self, path: &CppPath, concrete: &CppPath) -> Option<Substitution> {
+        let len = concrete.items().len();
+        if path.items().len() < len {
+            return None;
+        }
+        let pattern = CppPath::from_items(path.items()[..len].to_vec());
+        if !is_template_pattern(&pattern) {
+            return None;
+        }
+        let mut substitution = Substitution::default();
+        if !match_path(&pattern, concrete, &mut substitution) {
+            return None;
+        }
+        if self.has_more_specialized_match(&pattern, concrete) {
+            trace!(
+                "{} is not used for {} because a more specialized template exists",
+                pattern.to_cpp_pseudo_code(),
+                concrete.to_cpp_pseudo_code()
+            );
+            return None;
+        }
+        Some(substitution)
+    }
+
+    /// Finds all known instantiations of class templates used in `path`.
+    /// A partial specialization of a class template is used instead of
+    /// the template itself if it's more specialized for the instantiation.
+    fn find_suitable_template_arguments(&self, path: &CppPath) -> Vec<Substitution> {
+        let mut result = Vec::new();
+        for len in 1..=path.items().len() {
+            let prefix = CppPath::from_items(path.items()[..len].to_vec());
+            if !is_template_pattern(&prefix) {
+                continue;
+            }
+            if let Some(concrete_types) = self.concrete_types.get(&pattern_key(&prefix)) {
+                for concrete in concrete_types {
+                    if let Some(substitution) = self.match_prefix(path, concrete) {
+                        result.push(substitution);
+                    }
                 }
-                new_types.push(ItemWithSource::new(&type1.id, new_type));
             }
         }
-        let any_found = !new_types.is_empty();
-        for new_type in new_types {
-            data.db
-                .add_cpp_item(Some(new_type.source_id), CppItem::Type(new_type.item))?;
-        }
-        if !any_found {
-            break;
+        result
+    }
+}
+
+/// A type that is added to the queue of `instantiate_types`.
+struct QueuedType {
+    path: CppPath,
+    /// Index in `TemplateIndex::generic_types` if the type is not concrete.
+    generic_index: Option<usize>,
+    /// Number of instantiations that led to this type.
+    depth: usize,
+}
+
+/// Instantiates class templates (and nested classes of class templates)
+/// for all known concrete template instantiations.
+///
+/// Newly created types are added to the work queue because they may enable
+/// more instantiations (e.g. nested classes of instantiated class templates).
+fn instantiate_types(data: &mut ProcessorData<'_>) -> Result<()> {
+    let mut index = TemplateIndex::new(data.db);
+    let mut queue = data
+        .db
+        .cpp_items()
+        .filter_map(|item| item.item.as_type_ref())
+        .filter(|type1| has_template_arguments(&type1.path) && is_concrete(&type1.path))
+        .map(|type1| QueuedType {
+            path: type1.path.clone(),
+            generic_index: None,
+            depth: 0,
+        })
+        .collect::<VecDeque<_>>();
+    let mut new_types = Vec::<ItemWithSource<CppTypeDeclaration>>::new();
+    let mut skipped_count = 0;
+
+    while let Some(queued) = queue.pop_front() {
+        // pairs of (generic type index, concrete type path)
+        let candidates = if let Some(generic_index) = queued.generic_index {
+            let mut result = Vec::new();
+            for len in 1..=queued.path.items().len() {
+                let prefix = CppPath::from_items(queued.path.items()[..len].to_vec());
+                if !is_template_pattern(&prefix) {
+                    continue;
+                }
+                if let Some(concrete_types) = index.concrete_types.get(&pattern_key(&prefix)) {
+                    result.extend(concrete_types.iter().map(|c| (generic_index, c.clone())));
+                }
+            }
+            result
+        } else {
+            index
+                .generic_types_by_prefix
+                .get(&pattern_key(&queued.path))
+                .map_or_else(Vec::new, |generic_types| {
+                    generic_types
+                        .iter()
+                        .map(|&i| (i, queued.path.clone()))
+                        .collect()
+                })
+        };
+
+        for (generic_index, concrete) in candidates {
+            let generic_type = &index.generic_types[generic_index];
+            let substitution = match index.match_prefix(&generic_type.declaration.path, &concrete) {
+                Some(substitution) => substitution,
+                None => continue,
+            };
+            let mut path = generic_type.declaration.path.clone();
+            for (nested_level, arguments) in substitution.levels()? {
+                path = path.instantiate(nested_level, &arguments)?;
+            }
+            if index.known_types.contains(&path) {
+                continue;
+            }
+            if path_nesting_depth(&path) > MAX_NESTING_DEPTH {
+                if skipped_count == 0 {
+                    warn!(
+                        "template nesting depth limit ({}) exceeded: {}",
+                        MAX_NESTING_DEPTH,
+                        path.to_cpp_pseudo_code()
+                    );
+                }
+                skipped_count += 1;
+                continue;
+            }
+            debug!(
+                "instantiated {} from {} for {} (depth {})",
+                path.to_cpp_pseudo_code(),
+                generic_type.declaration.path.to_cpp_pseudo_code(),
+                concrete.to_cpp_pseudo_code(),
+                queued.depth + 1
+            );
+            let new_type = CppTypeDeclaration {
+                kind: generic_type.declaration.kind.clone(),
+                path: path.clone(),
+                special_members: None,
+            };
+            let source_id = generic_type.id.clone();
+            new_types.push(ItemWithSource::new(&source_id, new_type.clone()));
+
+            index.known_types.insert(path.clone());
+            let generic_index = if is_concrete(&path) {
+                index.add_concrete_type(path.clone());
+                None
+            } else {
+                Some(index.add_generic_type(source_id, new_type))
+            };
+            queue.push_back(QueuedType {
+                path,
+                generic_index,
+                depth: queued.depth + 1,
+            });
         }
     }
+    if skipped_count > 0 {
+        warn!(
+            "skipped {} template instantiations exceeding the nesting depth limit",
+            skipped_count
+        );
+    }
+
+    for new_type in new_types {
+        data.db
+            .add_cpp_item(Some(new_type.source_id), CppItem::Type(new_type.item))?;
+    }
     Ok(())
 }
 
 fn instantiate_functions(data: &mut ProcessorData<'_>) -> Result<()> {
+    let index = TemplateIndex::new(data.db);
+    let mut functions_by_path = HashMap::<CppPath, Vec<CppFunction>>::new();
+    for function in data
+        .db
+        .all_cpp_items()
+        .filter_map(|item| item.item.as_function_ref())
+    {
+        functions_by_path
+            .entry(function.path.clone())
+            .or_default()
+            .push(function.clone());
+    }
+
     let mut new_methods = Vec::new();
     for item in data.db.all_cpp_items() {
         let function = if let Some(f) = item.item.as_function_ref() {
@@ -228,19 +581,22 @@ fn instantiate_functions(data: &mut ProcessorData<'_>) -> Result<()> {
                 _ => continue,
             };
 
-            for substitution in find_suitable_template_arguments(path, data.db)? {
+            for substitution in index.find_suitable_template_arguments(path) {
                 trace!("method: {}", function.short_text());
                 trace!("found template instantiation: {:?}", substitution);
 
-                match instantiate_function(
-                    function,
-                    substitution.nested_level,
-                    substitution.arguments,
-                ) {
+                let levels = match substitution.levels() {
+                    Ok(levels) => levels,
+                    Err(msg) => {
+                        trace!("failed: {}", msg);
+                        continue;
+                    }
+                };
+                match instantiate_function_levels(function, &levels) {
                     Ok(method) => {
                         let mut ok = true;
                         for type1 in method.all_involved_types() {
-                            match check_template_type(&data, &type1) {
+                            match check_template_type(&index.known_types, &type1) {
                                 Ok(_) => {}
                                 Err(msg) => {
                                     ok = false;
@@ -250,14 +606,12 @@ fn instantiate_functions(data: &mut ProcessorData<'_>) -> Result<()> {
                             }
                         }
                         if ok {
-                            if data
-                                .db
-                                .all_cpp_items()
-                                .filter_map(|item| item.item.as_function_ref())
-                                .any(|item| item.is_same(&method))
-                            {
+                            let same_path =
+                                functions_by_path.entry(method.path.clone()).or_default();
+                            if same_path.iter().any(|item| item.is_same(&method)) {
                                 trace!("this method already exists");
                             } else {
+                                same_path.push(method.clone());
                                 new_methods.push(ItemWithSource::new(&item.id, method));
                             }
                         }
@@ -287,6 +641,17 @@ pub fn instantiate_templates(data: &mut ProcessorData<'_>) -> Result<()> {
 /// Searches for template instantiations in this library's API,
 /// excluding results that were already processed in dependencies.
 pub fn find_template_instantiations(data: &mut ProcessorData<'_>) -> Result<()> {
+    let types = data
+        .db
+        .cpp_items()
+        .flat_map(|item| item.item.all_involved_types())
+        .collect_vec();
+    add_template_instantiations(data, &types)
+}
+
+/// Adds instantiations of class templates used in `types`
+/// that are not present in the database yet.
+pub fn add_template_instantiations(data: &mut ProcessorData<'_>, types: &[CppType]) -> Result<()> {
     fn check_type(type1: &CppType, data: &ProcessorData<'_>, result: &mut Vec<CppPath>) {
         match &type1 {
             CppType::Class(path) => {
@@ -317,10 +682,8 @@ pub fn find_template_instantiations(data: &mut ProcessorData<'_>) -> Result<()>
         }
     }
     let mut result = Vec::new();
-    for item in data.db.cpp_items() {
-        for type1 in item.item.all_involved_types() {
-            check_type(&type1, &data, &mut result);
-        }
+    for type1 in types {
+        check_type(type1, &data, &mut result);
     }
     for item in result {
         let original_type = data
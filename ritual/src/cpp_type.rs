@@ -1,7 +1,9 @@
 //! Types for handling information about C++ types.
 
 use crate::cpp_data::CppPath;
+use itertools::Itertools;
 use ritual_common::errors::{bail, Result};
+use ritual_common::utils::MapIfOk;
 use serde_derive::{Deserialize, Serialize};
 use std::hash::{Hash, Hasher};
 
@@ -15,7 +17,8 @@ pub enum CppPointerLikeTypeKind {
 /// Available built-in C++ numeric types.
 /// All these types have corresponding
 /// `clang::TypeKind` values (except for `CharS` and `CharU`
-/// which map to `CppBuiltInNumericType::Char`)
+/// which map to `CppBuiltInNumericType::Char`, and `Char8`
+/// which is reported by clang as an unexposed type)
 #[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
 pub enum CppBuiltInNumericType {
     Bool,
@@ -23,6 +26,7 @@ pub enum CppBuiltInNumericType {
     SChar,
     UChar,
     WChar,
+    Char8,
     Char16,
     Char32,
     Short,
@@ -35,6 +39,7 @@ pub enum CppBuiltInNumericType {
     ULongLong,
     Int128,
     UInt128,
+    Float16,
     Float,
     Double,
     LongDouble,
@@ -58,6 +63,29 @@ pub struct CppFunctionPointerType {
     pub allows_variadic_arguments: bool,
 }
 
+/// Information about a C++ pointer to member type
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+pub struct CppMemberPointerType {
+    /// Class containing the member
+    pub class_path: CppPath,
+    /// Kind and type of the member
+    pub target: CppMemberPointerTarget,
+}
+
+/// Member referenced by a C++ pointer to member type
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+pub enum CppMemberPointerTarget {
+    /// Pointer to data member, like `int Foo::*`
+    Field(Box<CppType>),
+    /// Pointer to member function, like `int (Foo::*)(bool) const`
+    Method {
+        /// Signature of the method
+        function_type: CppFunctionPointerType,
+        /// Whether the method is const
+        is_const: bool,
+    },
+}
+
 /// Information about a numeric C++ type that is
 /// guaranteed to be the same on all platforms,
 /// e.g. `uint32_t`.
@@ -115,6 +143,13 @@ pub enum CppType {
     TemplateParameter(CppTemplateParameter),
     /// Function pointer type
     FunctionPointer(CppFunctionPointerType),
+    /// Pointer to member, like `int Foo::*`
+    MemberPointer(CppMemberPointerType),
+    /// Fixed-size array, like `int[16]`
+    Array {
+        element_type: Box<CppType>,
+        size: usize,
+    },
     PointerLike {
         kind: CppPointerLikeTypeKind,
         is_const: bool,
@@ -132,6 +167,7 @@ impl CppBuiltInNumericType {
             SChar => "signed char",
             UChar => "unsigned char",
             WChar => "wchar_t",
+            Char8 => "char8_t",
             Char16 => "char16_t",
             Char32 => "char32_t",
             Short => "short",
@@ -144,6 +180,7 @@ impl CppBuiltInNumericType {
             ULongLong => "unsigned long long",
             Int128 => "__int128_t",
             UInt128 => "__uint128_t",
+            Float16 => "_Float16",
             Float => "float",
             Double => "double",
             LongDouble => "long double",
@@ -154,7 +191,7 @@ impl CppBuiltInNumericType {
     pub fn is_float(&self) -> bool {
         use self::CppBuiltInNumericType::*;
         match *self {
-            Float | Double | LongDouble => true,
+            Float16 | Float | Double | LongDouble => true,
             _ => false,
         }
     }
@@ -172,7 +209,7 @@ impl CppBuiltInNumericType {
     pub fn is_unsigned_integer(&self) -> bool {
         use self::CppBuiltInNumericType::*;
         match *self {
-            UChar | Char16 | Char32 | UShort | UInt | ULong | ULongLong | UInt128 => true,
+            UChar | Char8 | Char16 | Char32 | UShort | UInt | ULong | ULongLong | UInt128 => true,
             _ => false,
         }
     }
@@ -187,12 +224,23 @@ impl CppBuiltInNumericType {
         }
     }
 
+    /// Returns true if Rust has no equivalent of this type that could be
+    /// passed by value through FFI. Such values are passed by pointer
+    /// and represented by opaque types from `cpp_core` on the Rust side.
+    pub fn is_opaque_in_rust(&self) -> bool {
+        use self::CppBuiltInNumericType::*;
+        match *self {
+            Float16 | LongDouble | Int128 | UInt128 => true,
+            _ => false,
+        }
+    }
+
     /// Returns all supported types.
     pub fn all() -> &'static [CppBuiltInNumericType] {
         use self::CppBuiltInNumericType::*;
         &[
-            Bool, Char, SChar, UChar, WChar, Char16, Char32, Short, UShort, Int, UInt, Long, ULong,
-            LongLong, ULongLong, Int128, UInt128, Float, Double, LongDouble,
+            Bool, Char, SChar, UChar, WChar, Char8, Char16, Char32, Short, UShort, Int, UInt, Long,
+            ULong, LongLong, ULongLong, Int128, UInt128, Float16, Float, Double, LongDouble,
         ]
     }
 }
@@ -243,6 +291,34 @@ impl CppType {
         }
     }
 
+    /// Returns true if this is a pointer to member.
+    pub fn is_member_pointer(&self) -> bool {
+        match *self {
+            CppType::MemberPointer(..) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns true if this is a fixed-size array.
+    pub fn is_array(&self) -> bool {
+        match *self {
+            CppType::Array { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Returns the innermost element type of a (possibly multidimensional) array
+    /// and its dimensions, from outer to inner. Non-array types have no dimensions.
+    pub fn array_dimensions(&self) -> (&CppType, Vec<usize>) {
+        let mut dimensions = Vec::new();
+        let mut current = self;
+        while let CppType::Array { element_type, size } = current {
+            dimensions.push(*size);
+            current = element_type;
+        }
+        (current, dimensions)
+    }
+
     pub fn is_pointer(&self) -> bool {
         match self {
             CppType::PointerLike { kind, .. } => *kind == CppPointerLikeTypeKind::Pointer,
@@ -250,12 +326,21 @@ impl CppType {
         }
     }
 
+    /// Returns true if this is an rvalue reference (like `QPoint&&`).
+    pub fn is_rvalue_reference(&self) -> bool {
+        match self {
+            CppType::PointerLike { kind, .. } => *kind == CppPointerLikeTypeKind::RValueReference,
+            _ => false,
+        }
+    }
+
     /// Returns true if this is a template parameter or a type that
     /// contains any template parameters.
     pub fn is_or_contains_template_parameter(&self) -> bool {
         match self {
             CppType::TemplateParameter { .. } => true,
             CppType::PointerLike { target, .. } => target.is_or_contains_template_parameter(),
+            CppType::Array { element_type, .. } => element_type.is_or_contains_template_parameter(),
             CppType::FunctionPointer(type1) => {
                 type1.return_type.is_or_contains_template_parameter()
                     || type1
@@ -263,6 +348,13 @@ impl CppType {
                         .iter()
                         .any(CppType::is_or_contains_template_parameter)
             }
+            CppType::MemberPointer(type1) => {
+                CppType::Class(type1.class_path.clone()).is_or_contains_template_parameter()
+                    || type1
+                        .target
+                        .member_type()
+                        .is_or_contains_template_parameter()
+            }
             CppType::Class(path) => path.items().iter().any(|item| {
                 if let Some(template_arguments) = &item.template_arguments {
                     template_arguments
@@ -282,6 +374,7 @@ impl CppType {
                 self_params.nested_level == param.nested_level && self_params.index == param.index
             }
             CppType::PointerLike { target, .. } => target.contains_template_parameter(param),
+            CppType::Array { element_type, .. } => element_type.contains_template_parameter(param),
             CppType::FunctionPointer(type1) => {
                 type1.return_type.contains_template_parameter(param)
                     || type1
@@ -289,6 +382,13 @@ impl CppType {
                         .iter()
                         .any(|t| t.contains_template_parameter(param))
             }
+            CppType::MemberPointer(type1) => {
+                CppType::Class(type1.class_path.clone()).contains_template_parameter(param)
+                    || type1
+                        .target
+                        .member_type()
+                        .contains_template_parameter(param)
+            }
             CppType::Class(path) => path.items().iter().any(|item| {
                 if let Some(template_arguments) = &item.template_arguments {
                     template_arguments
@@ -303,8 +403,15 @@ impl CppType {
     }
 
     /// Returns C++ code representing this type.
+    ///
+    /// `function_pointer_inner_text` is the declarator name placed inside
+    /// a function pointer, member pointer or array type (e.g. `int name[16]`).
     pub fn to_cpp_code(&self, function_pointer_inner_text: Option<&str>) -> Result<String> {
-        if !self.is_function_pointer() && function_pointer_inner_text.is_some() {
+        if !self.is_function_pointer()
+            && !self.is_member_pointer()
+            && !self.is_array()
+            && function_pointer_inner_text.is_some()
+        {
             bail!("unexpected function_pointer_inner_text");
         }
         match self {
@@ -340,6 +447,61 @@ impl CppType {
                     bail!("function_pointer_inner_text argument is missing");
                 }
             }
+            CppType::MemberPointer(CppMemberPointerType { class_path, target }) => {
+                let inner_text = function_pointer_inner_text.unwrap_or("");
+                let class_text = class_path.to_cpp_code()?;
+                match target {
+                    CppMemberPointerTarget::Field(field_type) => {
+                        if field_type.is_function_pointer()
+                            || field_type.is_member_pointer()
+                            || field_type.is_array()
+                        {
+                            bail!(
+                                "unsupported type of member pointer target: {:?}",
+                                field_type
+                            );
+                        }
+                        Ok(format!(
+                            "{} {}::*{}",
+                            field_type.to_cpp_code(None)?,
+                            class_text,
+                            inner_text
+                        ))
+                    }
+                    CppMemberPointerTarget::Method {
+                        function_type,
+                        is_const,
+                    } => {
+                        if function_type.allows_variadic_arguments {
+                            bail!("function pointers with variadic arguments are not supported");
+                        }
+                        let arg_texts = function_type
+                            .arguments
+                            .iter()
+                            .map_if_ok(|arg| arg.to_cpp_code(None))?;
+                        Ok(format!(
+                            "{} ({}::*{})({}){}",
+                            function_type.return_type.to_cpp_code(None)?,
+                            class_text,
+                            inner_text,
+                            arg_texts.join(", "),
+                            if *is_const { " const" } else { "" }
+                        ))
+                    }
+                }
+            }
+            CppType::Array { .. } => {
+                let (base, dimensions) = self.array_dimensions();
+                if base.is_function_pointer() {
+                    bail!("arrays of function pointers are not supported");
+                }
+                let dimensions_text = dimensions.iter().map(|size| format!("[{}]", size)).join("");
+                let base_text = base.to_cpp_code(None)?;
+                Ok(match function_pointer_inner_text {
+                    Some(inner_text) => format!("{} {}{}", base_text, inner_text, dimensions_text),
+                    None => format!("{}{}", base_text, dimensions_text),
+                })
+            }
             CppType::PointerLike {
                 kind,
                 is_const,
@@ -370,6 +532,36 @@ impl CppType {
                     .to_cpp_code(Some(&"FN_PTR".to_string()))
                     .unwrap_or_else(|_| "[?]".to_string());
             }
+            CppType::MemberPointer(type1) => {
+                let class_text = type1.class_path.to_cpp_pseudo_code();
+                return match &type1.target {
+                    CppMemberPointerTarget::Field(field_type) => {
+                        format!("{} {}::*", field_type.to_cpp_pseudo_code(), class_text)
+                    }
+                    CppMemberPointerTarget::Method {
+                        function_type,
+                        is_const,
+                    } => format!(
+                        "{} ({}::*)({}){}",
+                        function_type.return_type.to_cpp_pseudo_code(),
+                        class_text,
+                        function_type
+                            .arguments
+                            .iter()
+                            .map(CppType::to_cpp_pseudo_code)
+                            .join(", "),
+                        if *is_const { " const" } else { "" }
+                    ),
+                };
+            }
+            CppType::Array { .. } => {
+                let (base, dimensions) = self.array_dimensions();
+                return format!(
+                    "{}{}",
+                    base.to_cpp_pseudo_code(),
+                    dimensions.iter().map(|size| format!("[{}]", size)).join("")
+                );
+            }
             CppType::PointerLike {
                 kind,
                 is_const,
@@ -402,6 +594,12 @@ impl CppType {
             | CppType::Class(path) => path.ascii_caption(),
             CppType::TemplateParameter(param) => param.name.to_string(),
             CppType::FunctionPointer(_) => "fn".into(),
+            CppType::MemberPointer(type1) => {
+                format!("{}_member_ptr", type1.class_path.ascii_caption())
+            }
+            CppType::Array { element_type, size } => {
+                format!("{}_array_{}", element_type.ascii_caption(), size)
+            }
             CppType::PointerLike {
                 kind,
                 is_const,
@@ -498,11 +696,74 @@ impl CppType {
                 is_const: *is_const,
                 target: Box::new(target.instantiate(nested_level, template_arguments1)?),
             }),
+            CppType::Array { element_type, size } => Ok(CppType::Array {
+                element_type: Box::new(
+                    element_type.instantiate(nested_level, template_arguments1)?,
+                ),
+                size: *size,
+            }),
+            CppType::MemberPointer(CppMemberPointerType { class_path, target }) => {
+                let target = match target {
+                    CppMemberPointerTarget::Field(field_type) => CppMemberPointerTarget::Field(
+                        Box::new(field_type.instantiate(nested_level, template_arguments1)?),
+                    ),
+                    CppMemberPointerTarget::Method {
+                        function_type,
+                        is_const,
+                    } => CppMemberPointerTarget::Method {
+                        function_type: function_type
+                            .instantiate(nested_level, template_arguments1)?,
+                        is_const: *is_const,
+                    },
+                };
+                Ok(CppType::MemberPointer(CppMemberPointerType {
+                    class_path: class_path.instantiate(nested_level, template_arguments1)?,
+                    target,
+                }))
+            }
+            CppType::FunctionPointer(function_type) => Ok(CppType::FunctionPointer(
+                function_type.instantiate(nested_level, template_arguments1)?,
+            )),
             _ => Ok(self.clone()),
         }
     }
 }
 
+impl CppFunctionPointerType {
+    /// Attempts to replace template types at `nested_level`
+    /// within the return type and the argument types with `template_arguments1`.
+    pub fn instantiate(
+        &self,
+        nested_level: usize,
+        template_arguments1: &[CppType],
+    ) -> Result<CppFunctionPointerType> {
+        Ok(CppFunctionPointerType {
+            return_type: Box::new(
+                self.return_type
+                    .instantiate(nested_level, template_arguments1)?,
+            ),
+            arguments: self
+                .arguments
+                .iter()
+                .map_if_ok(|arg| arg.instantiate(nested_level, template_arguments1))?,
+            allows_variadic_arguments: self.allows_variadic_arguments,
+        })
+    }
+}
+
+impl CppMemberPointerTarget {
+    /// Returns type of the data member or a function pointer type
+    /// with the signature of the member function.
+    pub fn member_type(&self) -> CppType {
+        match self {
+            CppMemberPointerTarget::Field(field_type) => (**field_type).clone(),
+            CppMemberPointerTarget::Method { function_type, .. } => {
+                CppType::FunctionPointer(function_type.clone())
+            }
+        }
+    }
+}
+
 impl PartialEq for CppSpecificNumericType {
     fn eq(&self, other: &CppSpecificNumericType) -> bool {
         // name field is ignored
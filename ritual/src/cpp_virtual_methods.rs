@@ -0,0 +1,281 @@
+//! Generates subclasses that allow to override virtual methods from Rust.
+
+use crate::cpp_data::{CppPath, CppVisibility};
+use crate::cpp_ffi_data::{
+    CppFfiItem, CppFfiType, CppOverriddenMethod, CppOverrider, CppToFfiTypeConversion,
+};
+use crate::cpp_ffi_generator::{ffi_type, FfiNameProvider};
+use crate::cpp_function::CppFunction;
+use crate::cpp_type::{CppFunctionPointerType, CppPointerLikeTypeKind, CppType, CppTypeRole};
+use crate::processor::ProcessorData;
+use log::{trace, warn};
+use ritual_common::errors::{bail, format_err, Result};
+use ritual_common::utils::MapIfOk;
+use std::collections::{HashMap, HashSet};
+use std::iter::once;
+
+/// Returns true if `function` is a virtual method that can be overridden
+/// by the generated subclass.
+fn is_overridable(function: &CppFunction) -> bool {
+    let member = match &function.member {
+        Some(member) => member,
+        None => return false,
+    };
+    member.is_virtual
+        && member.visibility != CppVisibility::Private
+        && !function.is_destructor()
+        && !member.ref_qualifier.is_rvalue()
+        && member.inherited_from.is_none()
+        && !function.allows_variadic_arguments
+        && !function
+            .all_involved_types()
+            .iter()
+            .any(CppType::is_or_contains_template_parameter)
+}
+
+/// Checks that the value can be passed between C++ and Rust
+/// without allocating a new object.
+fn check_conversion(ffi_type: &CppFfiType) -> Result<()> {
+    match ffi_type.conversion() {
+        CppToFfiTypeConversion::NoChange | CppToFfiTypeConversion::ReferenceToPointer => Ok(()),
+        conversion => bail!("unsupported type conversion: {:?}", conversion),
+    }
+}
+
+fn overridden_method(function: &CppFunction, class_path: &CppPath) -> Result<CppOverriddenMethod> {
+    let is_const = function.member.as_ref().map_or(false, |m| m.is_const);
+    let arguments = function
+        .arguments
+        .iter()
+        .map_if_ok(|arg| ffi_type(&arg.argument_type, CppTypeRole::NotReturnType))?;
+    for arg in &arguments {
+        check_conversion(arg)?;
+    }
+    let return_type = ffi_type(&function.return_type, CppTypeRole::ReturnType)?;
+    check_conversion(&return_type)?;
+
+    let void_ptr = CppType::PointerLike {
+        is_const: false,
+        kind: CppPointerLikeTypeKind::Pointer,
+        target: Box::new(CppType::Void),
+    };
+    let this_ptr = CppType::PointerLike {
+        is_const,
+        kind: CppPointerLikeTypeKind::Pointer,
+        target: Box::new(CppType::Class(class_path.clone())),
+    };
+    let function_type = CppFunctionPointerType {
+        return_type: Box::new(return_type.ffi_type().clone()),
+        arguments: once(void_ptr)
+            .chain(once(this_ptr))
+            .chain(arguments.iter().map(|t| t.ffi_type().clone()))
+            .collect(),
+        allows_variadic_arguments: false,
+    };
+
+    Ok(CppOverriddenMethod {
+        name: function.path.last().name.clone(),
+        unique_name: function.path.last().name.clone(),
+        is_const,
+        is_pure_virtual: function
+            .member
+            .as_ref()
+            .map_or(false, |m| m.is_pure_virtual),
+        arguments,
+        return_type,
+        function_type,
+    })
+}
+
+/// Collects virtual methods of `base_class_path` and its bases and
+/// creates a subclass that overrides all of them.
+fn generate_overrider(
+    data: &ProcessorData<'_>,
+    base_class_path: &CppPath,
+    name_provider: &mut FfiNameProvider,
+) -> Result<CppOverrider> {
+    let mut bases = HashMap::<&CppPath, Vec<&CppPath>>::new();
+    let mut functions = HashMap::<CppPath, Vec<&CppFunction>>::new();
+    for item in data.db.all_cpp_items() {
+        if let Some(base) = item.item.as_base_ref() {
+            bases
+                .entry(&base.derived_class_type)
+                .or_default()
+                .push(&base.base_class_type);
+        } else if let Some(function) = item.item.as_function_ref() {
+            if function.member.is_some() {
+                functions
+                    .entry(function.class_path()?)
+                    .or_default()
+                    .push(function);
+            }
+        }
+    }
+
+    // derived classes come before their bases, so that overriding
+    // declarations are encountered first
+    let mut classes = vec![base_class_path];
+    let mut index = 0;
+    while index < classes.len() {
+        if let Some(class_bases) = bases.get(classes[index]) {
+            for base in class_bases {
+                if !classes.contains(base) {
+                    classes.push(base);
+                }
+            }
+        }
+        index += 1;
+    }
+
+    let class_path =
+        name_provider.create_path(&format!("overrider_{}", base_class_path.last().name));
+
+    let mut methods = Vec::<CppOverriddenMethod>::new();
+    let mut visited_names = HashSet::new();
+    for class in classes {
+        let class_functions = match functions.get(class) {
+            Some(f) => f,
+            None => continue,
+        };
+        for function in class_functions {
+            if !function.is_virtual() {
+                continue;
+            }
+            let name = &function.path.last().name;
+            let is_pure_virtual = function
+                .member
+                .as_ref()
+                .map_or(false, |m| m.is_pure_virtual);
+            let is_overridden = methods.iter().any(|method| {
+                &method.name == name
+                    && method.is_const == function.member.as_ref().map_or(false, |m| m.is_const)
+                    && method.arguments.len() == function.arguments.len()
+                    && method
+                        .arguments
+                        .iter()
+                        .zip(&function.arguments)
+                        .all(|(a, b)| a.original_type() == &b.argument_type)
+            });
+            if is_overridden {
+                continue;
+            }
+            // a method hidden by a declaration in a derived class
+            // can't be called as `Base::method`
+            let is_hidden = visited_names.contains(name);
+
+            let method = if is_overridable(function) && !is_hidden {
+                overridden_method(function, &class_path)
+            } else {
+                Err(format_err!("unsupported method"))
+            };
+            match method {
+                Ok(method) => methods.push(method),
+                Err(err) => {
+                    if is_pure_virtual {
+                        bail!(
+                            "pure virtual method {} can't be overridden: {}",
+                            function.short_text(),
+                            err
+                        );
+                    }
+                    trace!("skipping virtual method {}: {}", function.short_text(), err);
+                }
+            }
+        }
+        visited_names.extend(class_functions.iter().map(|f| f.path.last().name.clone()));
+    }
+
+    // overloaded methods need distinct names for generated setters
+    let mut name_counts = HashMap::<String, usize>::new();
+    for method in &methods {
+        *name_counts.entry(method.name.clone()).or_default() += 1;
+    }
+    for (name, _) in name_counts.into_iter().filter(|(_, count)| *count > 1) {
+        for (num, method) in methods
+            .iter_mut()
+            .filter(|method| method.name == name)
+            .enumerate()
+        {
+            method.unique_name = format!("{}_{}", name, num + 1);
+        }
+    }
+
+    Ok(CppOverrider {
+        base_class_path: base_class_path.clone(),
+        class_path,
+        methods,
+    })
+}
+
+/// Returns true if the class has a public or protected constructor
+/// that can be called without arguments.
+fn has_default_constructor(data: &ProcessorData<'_>, class_path: &CppPath) -> bool {
+    data.db
+        .all_cpp_items()
+        .filter_map(|item| item.item.as_function_ref())
+        .any(|function| {
+            function.is_constructor()
+                && !function.is_deleted
+                && function.arguments.iter().all(|arg| arg.has_default_value)
+                && function
+                    .member
+                    .as_ref()
+                    .map_or(false, |m| m.visibility != CppVisibility::Private)
+                && function.class_path().ok().as_ref() == Some(class_path)
+        })
+}
+
+/// Adds overriders for classes requested with `Config::add_overridable_class`.
+///
+/// The generated subclass only has a default constructor, so this step fails
+/// if an overridable class doesn't have a public or protected default constructor.
+pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
+    let mut name_provider = FfiNameProvider::new(data);
+    let mut overriders = Vec::new();
+    for base_class_path in data.config.overridable_classes() {
+        let class_item = data.db.cpp_items().find(|item| {
+            item.item
+                .as_type_ref()
+                .map_or(false, |t| t.kind.is_class() && &t.path == base_class_path)
+        });
+        let class_item = match class_item {
+            Some(item) => item,
+            None => {
+                warn!(
+                    "overridable class not found: {}",
+                    base_class_path.to_cpp_pseudo_code()
+                );
+                continue;
+            }
+        };
+        let exists = data
+            .db
+            .ffi_items()
+            .filter_map(|item| item.item.as_overrider_ref())
+            .any(|item| &item.base_class_path == base_class_path);
+        if exists {
+            continue;
+        }
+        if !has_default_constructor(data, base_class_path) {
+            bail!(
+                "overridable class {} doesn't have a public or protected default constructor",
+                base_class_path.to_cpp_pseudo_code()
+            );
+        }
+        match generate_overrider(data, base_class_path, &mut name_provider) {
+            Ok(overrider) => overriders.push((class_item.id.clone(), overrider)),
+            Err(err) => {
+                warn!(
+                    "failed to add overrider for {}: {}",
+                    base_class_path.to_cpp_pseudo_code(),
+                    err
+                );
+            }
+        }
+    }
+    for (id, overrider) in overriders {
+        data.db
+            .add_ffi_item(Some(id), CppFfiItem::Overrider(overrider))?;
+    }
+    Ok(())
+}
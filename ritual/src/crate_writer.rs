@@ -241,27 +241,30 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
     generate_crate_template(data, &output_path)?;
     data.workspace.update_cargo_toml()?;
 
-    let c_lib_path = output_path.join("c_lib");
-    if !c_lib_path.exists() {
-        create_dir(&c_lib_path)?;
-    }
     let c_lib_name = format!("{}_c", data.config.crate_properties().name());
-    let global_header_name = format!("{}_global.h", c_lib_name);
-    generate_c_lib_template(
-        &c_lib_name,
-        &c_lib_path,
-        &global_header_name,
-        data.config.include_directives(),
-    )?;
+    // C libraries are called directly, so no wrapper library is needed
+    if !data.config.is_c_library() {
+        let c_lib_path = output_path.join("c_lib");
+        if !c_lib_path.exists() {
+            create_dir(&c_lib_path)?;
+        }
+        let global_header_name = format!("{}_global.h", c_lib_name);
+        generate_c_lib_template(
+            &c_lib_name,
+            &c_lib_path,
+            &global_header_name,
+            data.config.include_directives(),
+        )?;
 
-    cpp_code_generator::generate_cpp_file(
-        &data.db,
-        &c_lib_path.join("file1.cpp"),
-        &global_header_name,
-    )?;
+        cpp_code_generator::generate_cpp_file(
+            &data.db,
+            &c_lib_path.join("file1.cpp"),
+            &global_header_name,
+        )?;
 
-    let file = create_file(c_lib_path.join("sized_types.cxx"))?;
-    generate_cpp_type_size_requester(data.db, data.config.include_directives(), file)?;
+        let file = create_file(c_lib_path.join("sized_types.cxx"))?;
+        generate_cpp_type_size_requester(data.db, data.config.include_directives(), file)?;
+    }
 
     rust_code_generator::generate(
         &data.db,
@@ -289,6 +292,7 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
             cpp_build_config: data.config.cpp_build_config().clone(),
             cpp_wrapper_lib_name: c_lib_name,
             known_targets: data.db.environments().to_vec(),
+            is_c_library: data.config.is_c_library(),
         },
         None,
     )?;
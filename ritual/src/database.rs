@@ -1,6 +1,8 @@
+use crate::cpp_annotations::CppAnnotations;
 use crate::cpp_checks::{CppChecks, CppChecksItem};
 use crate::cpp_data::{CppItem, CppPath};
 use crate::cpp_ffi_data::CppFfiItem;
+use crate::cpp_parser::CppParserFingerprint;
 use crate::rust_info::RustItem;
 use crate::rust_type::RustPath;
 use log::{debug, error, info, trace, warn};
@@ -176,8 +178,11 @@ pub enum DatabaseItemData {
     CppItem(CppItem),
     FfiItem(CppFfiItem),
     CppChecksItem(CppChecksItem),
+    /// Result of checking whether a class is movable in an environment.
+    MovableTypeChecksItem(CppChecksItem),
     RustItem(RustItem),
     DocItem(DocItem),
+    AnnotationsItem(CppAnnotations),
 }
 
 impl DatabaseItemData {
@@ -237,6 +242,13 @@ impl DatabaseItemData {
             None
         }
     }
+    pub fn as_rust_item_mut(&mut self) -> Option<&mut RustItem> {
+        if let DatabaseItemData::RustItem(data) = self {
+            Some(data)
+        } else {
+            None
+        }
+    }
     pub fn is_cpp_checks_item(&self) -> bool {
         if let DatabaseItemData::CppChecksItem(_) = self {
             true
@@ -258,6 +270,20 @@ impl DatabaseItemData {
             None
         }
     }
+    pub fn is_movable_type_checks_item(&self) -> bool {
+        if let DatabaseItemData::MovableTypeChecksItem(_) = self {
+            true
+        } else {
+            false
+        }
+    }
+    pub fn as_movable_type_checks_item(&self) -> Option<&CppChecksItem> {
+        if let DatabaseItemData::MovableTypeChecksItem(data) = self {
+            Some(data)
+        } else {
+            None
+        }
+    }
     pub fn is_doc_item(&self) -> bool {
         if let DatabaseItemData::DocItem(_) = self {
             true
@@ -279,6 +305,20 @@ impl DatabaseItemData {
             None
         }
     }
+    pub fn is_annotations_item(&self) -> bool {
+        if let DatabaseItemData::AnnotationsItem(_) = self {
+            true
+        } else {
+            false
+        }
+    }
+    pub fn as_annotations_item(&self) -> Option<&CppAnnotations> {
+        if let DatabaseItemData::AnnotationsItem(data) = self {
+            Some(data)
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -288,6 +328,8 @@ pub struct Database {
     items: Vec<DbItem<DatabaseItemData>>,
     targets: Vec<LibraryTarget>,
     next_id: u32,
+    #[serde(default)]
+    cpp_parser_fingerprint: Option<CppParserFingerprint>,
 }
 
 impl Database {
@@ -298,6 +340,7 @@ impl Database {
             items: Vec::new(),
             targets: Vec::new(),
             next_id: 1,
+            cpp_parser_fingerprint: None,
         }
     }
 
@@ -574,6 +617,12 @@ impl DatabaseClient {
             .ok_or_else(|| err_msg("not a rust item"))
     }
 
+    pub fn rust_item_mut(&mut self, id: &ItemId) -> Result<DbItem<&mut RustItem>> {
+        self.item_mut(id)?
+            .filter_map(|v| v.as_rust_item_mut())
+            .ok_or_else(|| err_msg("not a rust item"))
+    }
+
     fn new_id(&mut self) -> ItemId {
         let id = self.current_database.db.next_id;
         self.current_database.db.next_id += 1;
@@ -627,6 +676,18 @@ impl DatabaseClient {
         }
     }
 
+    /// Returns information about inputs of the last `cpp_parser` run.
+    pub fn cpp_parser_fingerprint(&self) -> Option<&CppParserFingerprint> {
+        self.current_database.db.cpp_parser_fingerprint.as_ref()
+    }
+
+    pub fn set_cpp_parser_fingerprint(&mut self, value: Option<CppParserFingerprint>) {
+        if self.current_database.db.cpp_parser_fingerprint != value {
+            self.is_modified = true;
+            self.current_database.db.cpp_parser_fingerprint = value;
+        }
+    }
+
     pub fn add_cpp_item(
         &mut self,
         source_id: Option<ItemId>,
@@ -796,6 +857,33 @@ impl DatabaseClient {
         Some(id)
     }
 
+    pub fn add_movable_type_checks_item(
+        &mut self,
+        source_id: ItemId,
+        item: CppChecksItem,
+    ) -> Option<ItemId> {
+        if self
+            .current_database
+            .filter_by_source(&Some(source_id.clone()))
+            .filter_map(|other| other.filter_map(|other| other.as_movable_type_checks_item()))
+            .any(|other| other.item.env == item.env)
+        {
+            self.counters.items_ignored += 1;
+            return None;
+        }
+
+        self.is_modified = true;
+        let id = self.new_id();
+
+        self.current_database.push(DbItem {
+            id: id.clone(),
+            source_id: Some(source_id),
+            item: DatabaseItemData::MovableTypeChecksItem(item),
+        });
+        self.counters.items_added += 1;
+        Some(id)
+    }
+
     pub fn add_doc_item(&mut self, source_id: ItemId, item: DocItem) -> Option<ItemId> {
         if self
             .current_database
@@ -817,6 +905,32 @@ impl DatabaseClient {
         Some(id)
     }
 
+    pub fn add_annotations_item(
+        &mut self,
+        source_id: ItemId,
+        item: CppAnnotations,
+    ) -> Option<ItemId> {
+        if self
+            .current_database
+            .filter_by_source(&Some(source_id.clone()))
+            .any(|other| other.item.is_annotations_item())
+        {
+            self.counters.items_ignored += 1;
+            return None;
+        }
+
+        self.is_modified = true;
+        let id = self.new_id();
+
+        self.current_database.push(DbItem {
+            id: id.clone(),
+            source_id: Some(source_id),
+            item: DatabaseItemData::AnnotationsItem(item),
+        });
+        self.counters.items_added += 1;
+        Some(id)
+    }
+
     pub fn cpp_checks(&self, source_id: &ItemId) -> Result<CppChecks> {
         let items = self
             .database(&source_id.crate_name)?
@@ -825,6 +939,15 @@ impl DatabaseClient {
         Ok(CppChecks::new(items))
     }
 
+    /// Returns results of movability checks of the class declared by `source_id`.
+    pub fn movable_type_checks(&self, source_id: &ItemId) -> Result<CppChecks> {
+        let items = self
+            .database(&source_id.crate_name)?
+            .filter_by_source(&Some(source_id.clone()))
+            .filter_map(|item| item.item.as_movable_type_checks_item().cloned());
+        Ok(CppChecks::new(items))
+    }
+
     pub fn delete_items(&mut self, mut function: impl FnMut(DbItem<&DatabaseItemData>) -> bool) {
         let mut ids = HashSet::new();
         let mut items_deleted = 0;
@@ -931,6 +1054,25 @@ impl DatabaseClient {
         }
     }
 
+    /// Returns annotations of the C++ item `id` was generated from.
+    /// Only the closest C++ item is checked, so C++ items derived from
+    /// an annotated item (e.g. implicit methods of a class) don't inherit its annotations.
+    pub fn find_annotations_for(&self, id: &ItemId) -> Result<Option<&CppAnnotations>> {
+        let item = self.item(id)?;
+        let cpp_item_id = if item.item.is_cpp_item() {
+            item.id
+        } else if let Some(cpp_item) = self.source_cpp_item(id)? {
+            cpp_item.id
+        } else {
+            return Ok(None);
+        };
+        Ok(self
+            .database(&cpp_item_id.crate_name)?
+            .filter_by_source(&Some(cpp_item_id.clone()))
+            .filter_map(|i| i.item.as_annotations_item())
+            .next())
+    }
+
     fn all_databases(&self) -> impl Iterator<Item = &IndexedDatabase> {
         once(&self.current_database as &_).chain(self.dependencies.iter())
     }
@@ -962,6 +1104,21 @@ impl DatabaseClient {
         bail!("unknown cpp path: {}", cpp_path.to_cpp_pseudo_code())
     }
 
+    /// Returns annotations of the C++ type or enum value declared at `cpp_path`.
+    pub fn find_annotations_for_cpp_path(&self, cpp_path: &CppPath) -> Option<&CppAnnotations> {
+        for db in self.all_databases() {
+            if let Some(cpp_item) = db.filter_by_cpp_path(cpp_path).find(|item| {
+                item.item.as_type_ref().is_some() || item.item.as_enum_value_ref().is_some()
+            }) {
+                return db
+                    .filter_by_source(&Some(cpp_item.id.clone()))
+                    .filter_map(|i| i.item.as_annotations_item())
+                    .next();
+            }
+        }
+        None
+    }
+
     fn database(&self, crate_name: &str) -> Result<&IndexedDatabase> {
         self.all_databases()
             .find(|db| *db.db.crate_name == crate_name)
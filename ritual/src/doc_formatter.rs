@@ -9,7 +9,7 @@ use crate::database::{DatabaseClient, DbItem, DocItem};
 use crate::rust_code_generator::rust_type_to_code;
 use crate::rust_info::{
     RustEnumValue, RustFunction, RustFunctionKind, RustModule, RustModuleKind, RustQtReceiverType,
-    RustSpecialModuleKind, RustStruct, RustStructKind, RustWrapperTypeKind,
+    RustSpecialModuleKind, RustStruct, RustStructKind, RustTypeAlias, RustWrapperTypeKind,
 };
 use itertools::Itertools;
 use ritual_common::errors::{bail, err_msg, Result};
@@ -123,9 +123,21 @@ pub fn struct_doc(type1: DbItem<&RustStruct>, database: &DatabaseClient) -> Resu
                     )?;
                 }
                 RustWrapperTypeKind::ImmovableClassWrapper => {
+                    let is_union = cpp_item
+                        .item
+                        .as_type_ref()
+                        .map_or(false, |t| t.kind.is_union());
+                    writeln!(
+                        output,
+                        "C++ {}: {}.\n",
+                        if is_union { "union" } else { "class" },
+                        wrap_inline_cpp_code(&cpp_type_code)
+                    )?;
+                }
+                RustWrapperTypeKind::ReprCStruct { .. } => {
                     writeln!(
                         output,
-                        "C++ class: {}.\n",
+                        "C struct: {}.\n",
                         wrap_inline_cpp_code(&cpp_type_code)
                     )?;
                 }
@@ -194,6 +206,30 @@ pub fn struct_doc(type1: DbItem<&RustStruct>, database: &DatabaseClient) -> Resu
                      If `set()` was not called, slot invocation has no effect.\n"
                 )?;
             }
+
+            let overrider = database
+                .source_ffi_item(&cpp_item.id)?
+                .and_then(|item| item.item.as_overrider_ref());
+            if let Some(overrider) = overrider {
+                output.clear(); // remove irrelevant C++ type name
+                writeln!(
+                    output,
+                    "Subclass of C++ class {} that forwards calls of virtual methods to Rust.\n",
+                    wrap_inline_cpp_code(&overrider.base_class_path.to_cpp_pseudo_code())
+                )?;
+                let virtual_methods_trait = database
+                    .rust_items()
+                    .filter_map(|item| item.item.as_virtual_methods_trait_ref())
+                    .find(|item| item.overrider_path == type1.item.path);
+                if let Some(virtual_methods_trait) = virtual_methods_trait {
+                    writeln!(
+                        output,
+                        "Create an object using `new_with_methods()` and pass \
+                         an implementation of `{}` trait to it.\n",
+                        virtual_methods_trait.path.last()
+                    )?;
+                }
+            }
         }
         RustStructKind::QtSlotWrapper(wrapper) => {
             let cpp_item = database
@@ -258,6 +294,19 @@ pub fn struct_doc(type1: DbItem<&RustStruct>, database: &DatabaseClient) -> Resu
                  If `set()` was not called, slot invocation has no effect.\n"
             )?;
         }
+        RustStructKind::OptionalArguments(data) => {
+            writeln!(
+                output,
+                "Arguments with default values of `{}`.\n",
+                data.function_path.last()
+            )?;
+            writeln!(
+                output,
+                "Default values are used for unset fields. \
+                 Arguments must be set in order: if a field is set, \
+                 all previous fields must also be set, otherwise the function panics.\n"
+            )?;
+        }
         // private struct, no doc needed
         RustStructKind::SizedType(_) => {}
     };
@@ -290,6 +339,28 @@ pub fn enum_value_doc(value: DbItem<&RustEnumValue>, database: &DatabaseClient)
     Ok(doc)
 }
 
+pub fn type_alias_doc(alias: DbItem<&RustTypeAlias>, database: &DatabaseClient) -> Result<String> {
+    let cpp_item = database
+        .source_cpp_item(&alias.id)?
+        .ok_or_else(|| err_msg("source cpp item not found"))?
+        .item
+        .as_type_alias_ref()
+        .ok_or_else(|| err_msg("invalid source cpp item type"))?;
+
+    let mut doc = format!(
+        "C++ type alias: {}",
+        wrap_inline_cpp_code(&format!(
+            "{} = {}",
+            cpp_item.path.to_cpp_pseudo_code(),
+            cpp_item.target_type.to_cpp_pseudo_code()
+        ))
+    );
+    if let Some(doc_item) = database.find_doc_for(&alias.id)? {
+        doc = format!("{} ({})", doc_item.item.html, doc);
+    }
+    Ok(doc)
+}
+
 fn format_maybe_link(url: &Option<String>, text: &str) -> String {
     if let Some(url) = url {
         format!("<a href=\"{}\">{}</a>", url, text)
@@ -428,6 +499,50 @@ pub fn function_doc(function: DbItem<&RustFunction>, database: &DatabaseClient)
                         }
                     };
                 }
+                CppFfiFunctionKind::MemberPointerGetter => {
+                    let cpp_item = cpp_item
+                        .item
+                        .as_function_ref()
+                        .ok_or_else(|| err_msg("invalid source cpp item type"))?;
+                    write!(
+                        output,
+                        "Returns an opaque pointer to a value of type {} pointing to \
+                         C++ function: {}. The value can be passed to functions that accept \
+                         this member pointer type.",
+                        wrap_inline_cpp_code(
+                            &cpp_ffi_function
+                                .return_type
+                                .original_type()
+                                .to_cpp_pseudo_code()
+                        ),
+                        wrap_inline_cpp_code(&cpp_item.short_text())
+                    )?;
+                }
+            }
+        }
+        RustFunctionKind::OptionalArgumentsWrapper(data) => {
+            let cpp_item = cpp_item
+                .item
+                .as_function_ref()
+                .ok_or_else(|| err_msg("invalid source cpp item type"))?;
+            write!(
+                output,
+                "Calls C++ function: {}.\n\n",
+                wrap_inline_cpp_code(&cpp_item.short_text())
+            )?;
+            write!(
+                output,
+                "Arguments with default values are passed in `{}`. \
+                 Default values are used for unset arguments.\n\n",
+                data.arguments_struct_path.last()
+            )?;
+            // the variants include the one without optional arguments
+            if data.variants.len() > 2 {
+                write!(
+                    output,
+                    "Panics if a field of `{}` is set while any of the previous fields is unset.\n\n",
+                    data.arguments_struct_path.last()
+                )?;
             }
         }
         RustFunctionKind::SignalOrSlotGetter(getter) => {
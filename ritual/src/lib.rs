@@ -9,6 +9,7 @@ pub use ritual_common as common;
 pub mod cli;
 pub mod cluster_api;
 pub mod config;
+pub mod cpp_annotations;
 mod cpp_casts;
 pub mod cpp_checker;
 mod cpp_checks;
@@ -22,8 +23,10 @@ mod cpp_inheritance; // TODO: deal with inheritance for subclassing support
 mod cpp_omitting_arguments;
 mod cpp_operator;
 pub mod cpp_parser;
+pub mod cpp_parser_report;
 pub mod cpp_template_instantiator;
 pub mod cpp_type;
+mod cpp_virtual_methods;
 mod crate_writer;
 pub mod database;
 mod doc_formatter;
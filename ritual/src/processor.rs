@@ -2,8 +2,9 @@ use crate::config::Config;
 use crate::database::{DatabaseClient, ItemId};
 use crate::workspace::Workspace;
 use crate::{
-    cpp_casts, cpp_checker, cpp_ffi_generator, cpp_implicit_methods, cpp_omitting_arguments,
-    cpp_parser, cpp_template_instantiator, crate_writer, rust_generator,
+    cpp_casts, cpp_checker, cpp_ffi_generator, cpp_implicit_methods, cpp_inheritance,
+    cpp_omitting_arguments, cpp_parser, cpp_template_instantiator, cpp_virtual_methods,
+    crate_writer, rust_generator, type_allocation_places,
 };
 use itertools::Itertools;
 use log::{error, info, trace};
@@ -93,10 +94,6 @@ impl Default for ProcessingSteps {
                 &format!("add_implicit_methods{}", suffix),
                 cpp_implicit_methods::run,
             );
-            //            s.push(
-            //                &format!("set_allocation_places{}", suffix),
-            //                type_allocation_places::set_allocation_places,
-            //            );
             s.push(
                 &format!("find_template_instantiations{}", suffix),
                 cpp_template_instantiator::find_template_instantiations,
@@ -105,15 +102,27 @@ impl Default for ProcessingSteps {
                 &format!("instantiate_templates{}", suffix),
                 cpp_template_instantiator::instantiate_templates,
             );
+            s.push(
+                &format!("inherited_methods{}", suffix),
+                cpp_inheritance::run,
+            );
             s.push(
                 &format!("omitting_arguments{}", suffix),
                 cpp_omitting_arguments::run,
             );
             s.push(&format!("cpp_casts{}", suffix), cpp_casts::run);
+            s.push(
+                &format!("set_allocation_places{}", suffix),
+                type_allocation_places::set_allocation_places,
+            );
             s.push(
                 &format!("cpp_ffi_generator{}", suffix),
                 cpp_ffi_generator::run,
             );
+            s.push(
+                &format!("virtual_methods{}", suffix),
+                cpp_virtual_methods::run,
+            );
             s.push(&format!("cpp_checker{}", suffix), cpp_checker::run);
         };
 
@@ -133,16 +142,21 @@ impl Default for ProcessingSteps {
             data.db.delete_items(|i| i.item.is_cpp_checks_item());
             Ok(())
         });
+        s.add_custom("clear_movable_type_checks", |data| {
+            data.db
+                .delete_items(|i| i.item.is_movable_type_checks_item());
+            Ok(())
+        });
         s.add_custom("clear_rust_info", |data| {
             data.db.delete_items(|i| i.item.is_rust_item());
             Ok(())
         });
         s.add_custom("show_non_portable", show_non_portable);
 
-        //        s.add_custom(
-        //            "suggest_allocation_places",
-        //            type_allocation_places::suggest_allocation_places,
-        //        );
+        s.add_custom(
+            "suggest_allocation_places",
+            type_allocation_places::suggest_allocation_places,
+        );
         s
     }
 }
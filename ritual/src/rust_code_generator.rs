@@ -1,14 +1,15 @@
 //! Types and functions used for Rust code generation.
 
 use crate::cpp_checks::Condition;
-use crate::cpp_ffi_data::CppFfiArgumentMeaning;
-use crate::database::{DatabaseClient, DbItem, ItemId};
+use crate::cpp_ffi_data::{CppFfiArgumentMeaning, CppFfiFunction};
+use crate::database::{DatabaseClient, DbItem};
 use crate::doc_formatter;
 use crate::rust_generator::qt_core_path;
 use crate::rust_info::{
-    RustEnumValue, RustExtraImpl, RustExtraImplKind, RustFfiWrapperData, RustFunction,
-    RustFunctionArgument, RustFunctionKind, RustItem, RustModule, RustModuleKind,
-    RustSpecialModuleKind, RustStruct, RustStructKind, RustTraitImpl, RustWrapperTypeKind,
+    RustEnumValue, RustExtraImpl, RustExtraImplKind, RustFunction, RustFunctionArgument,
+    RustFunctionKind, RustItem, RustModule, RustModuleKind, RustOptionalArgumentsWrapperData,
+    RustSpecialModuleKind, RustStruct, RustStructKind, RustTraitImpl, RustTypeAlias,
+    RustVirtualMethodsTrait, RustWrapperTypeKind,
 };
 use crate::rust_type::{
     RustCommonType, RustFinalType, RustPath, RustPointerLikeTypeKind, RustToFfiTypeConversion,
@@ -107,14 +108,19 @@ pub fn rust_type_to_code(rust_type: &RustType, current_crate: Option<&str>) -> S
             "impl {}",
             rust_common_type_to_code(trait_type, current_crate)
         ),
+        RustType::Array { element_type, size } => format!(
+            "[{}; {}]",
+            rust_type_to_code(element_type, current_crate),
+            size
+        ),
     }
 }
 
-struct Generator<'a> {
-    output_src_path: PathBuf,
-    crate_template_src_path: Option<PathBuf>,
-    destination: Vec<File<BufWriter<fs::File>>>,
-    current_database: &'a DatabaseClient,
+pub struct Generator<'a> {
+    pub output_src_path: PathBuf,
+    pub crate_template_src_path: Option<PathBuf>,
+    pub destination: Vec<File<BufWriter<fs::File>>>,
+    pub current_database: &'a DatabaseClient,
 }
 
 impl Write for Generator<'_> {
@@ -142,9 +148,9 @@ fn format_doc(doc: &str) -> String {
 }
 
 #[derive(Debug, Default)]
-struct ConditionTexts {
-    attribute: String,
-    doc_text: String,
+pub struct ConditionTexts {
+    pub attribute: String,
+    pub doc_text: String,
 }
 
 /// Generates documentation comments containing
@@ -331,6 +337,13 @@ impl Generator<'_> {
                 )?;
                 Ok(())
             }
+            RustItem::TypeAlias(_) => {
+                self.generate_type_alias(item.map(|i| i.as_type_alias_ref().unwrap()))
+            }
+            RustItem::VirtualMethodsTrait(_) => self.generate_virtual_methods_trait(
+                item.map(|i| i.as_virtual_methods_trait_ref().unwrap()),
+                &condition_texts,
+            ),
         }
     }
 
@@ -475,6 +488,25 @@ impl Generator<'_> {
                         rust_struct.item.path.last()
                     )?;
                 }
+                RustWrapperTypeKind::ReprCStruct { fields } => {
+                    writeln!(self, "#[repr(C)]")?;
+                    writeln!(self, "#[derive(Clone, Copy)]")?;
+                    writeln!(
+                        self,
+                        "{}struct {} {{",
+                        visibility,
+                        rust_struct.item.path.last()
+                    )?;
+                    for field in fields {
+                        writeln!(
+                            self,
+                            "pub {}: {},",
+                            field.name,
+                            self.rust_type_to_code(&field.field_type)
+                        )?;
+                    }
+                    writeln!(self, "}}")?;
+                }
                 RustWrapperTypeKind::MovableClassWrapper { sized_type_path } => {
                     writeln!(self, "#[repr(transparent)]")?;
                     writeln!(
@@ -524,6 +556,24 @@ impl Generator<'_> {
             RustStructKind::SizedType(_) => {
                 bail!("sized struct can't be generated with rust code generator")
             }
+            RustStructKind::OptionalArguments(data) => {
+                writeln!(self, "#[derive(Default)]")?;
+                writeln!(
+                    self,
+                    "{}struct {} {{",
+                    visibility,
+                    rust_struct.item.path.last()
+                )?;
+                for field in &data.fields {
+                    writeln!(
+                        self,
+                        "pub {}: Option<{}>,",
+                        field.name,
+                        self.rust_type_to_code(&field.field_type)
+                    )?;
+                }
+                writeln!(self, "}}")?;
+            }
         }
 
         if self
@@ -573,6 +623,178 @@ impl Generator<'_> {
         Ok(())
     }
 
+    fn generate_type_alias(&mut self, alias: DbItem<&RustTypeAlias>) -> Result<()> {
+        write!(
+            self,
+            "{}",
+            format_doc(&doc_formatter::type_alias_doc(
+                alias.clone(),
+                self.current_database
+            )?)
+        )?;
+        writeln!(
+            self,
+            "pub type {} = {};",
+            alias.item.path.last(),
+            self.rust_type_to_code(&alias.item.target)
+        )?;
+        Ok(())
+    }
+
+    pub fn generate_virtual_methods_trait(
+        &mut self,
+        data: DbItem<&RustVirtualMethodsTrait>,
+        condition_texts: &ConditionTexts,
+    ) -> Result<()> {
+        let data = data.item;
+        let overrider = self.rust_path_to_string(&data.overrider_path);
+        let trait_name = data.path.last();
+
+        let mut trait_methods = String::new();
+        let mut callbacks = String::new();
+        let mut set_callbacks = String::new();
+        for (index, method) in data.methods.iter().enumerate() {
+            let (this_type, this_ffi_type) = if method.is_const {
+                ("::cpp_core::Ptr", "*const")
+            } else {
+                ("::cpp_core::MutPtr", "*mut")
+            };
+            let return_type = if method.return_type.api_type().is_unit() {
+                String::new()
+            } else {
+                format!(
+                    " -> {}",
+                    self.rust_type_to_code(method.return_type.api_type())
+                )
+            };
+            let args = method
+                .arguments
+                .iter()
+                .enumerate()
+                .map(|(num, t)| format!(", arg{}: {}", num, self.rust_type_to_code(t.api_type())))
+                .join("");
+            let arg_names = (0..method.arguments.len())
+                .map(|num| format!("arg{}", num))
+                .join(", ");
+
+            let doc = if method.base_function.is_some() {
+                format!(
+                    "Called by C++ `{}` method. The default implementation calls \
+                     the base class implementation.",
+                    method.cpp_name
+                )
+            } else {
+                format!("Called by C++ `{}` method.", method.cpp_name)
+            };
+            write!(trait_methods, "{}", format_doc(&doc))?;
+            let signature = format!(
+                "unsafe fn {name}(&self, this: {this_type}<{overrider}>{args}){return_type}",
+                name = method.name,
+                this_type = this_type,
+                overrider = overrider,
+                args = args,
+                return_type = return_type,
+            );
+            if let Some(base_function) = &method.base_function {
+                let signature = if method.is_const {
+                    signature
+                } else {
+                    // `DerefMut` is needed to call the base implementation
+                    signature.replacen("this:", "mut this:", 1)
+                };
+                writeln!(
+                    trait_methods,
+                    "{} {{\nthis.{}({})\n}}",
+                    signature, base_function, arg_names
+                )?;
+            } else {
+                writeln!(trait_methods, "{};", signature)?;
+            }
+
+            let callback_args = method
+                .arguments
+                .iter()
+                .enumerate()
+                .map(|(num, t)| format!(", arg{}: {}", num, self.rust_type_to_code(t.ffi_type())))
+                .join("");
+            let callback_return_type = if method.return_type.ffi_type().is_unit() {
+                String::new()
+            } else {
+                format!(
+                    " -> {}",
+                    self.rust_type_to_code(method.return_type.ffi_type())
+                )
+            };
+            let func_args = method
+                .arguments
+                .iter()
+                .enumerate()
+                .map_if_ok(|(num, t)| {
+                    self.convert_type_from_ffi(t, format!("arg{}", num), true, false)
+                })?
+                .into_iter()
+                .map(|arg| format!(", {}", arg))
+                .join("");
+            writeln!(
+                callbacks,
+                "extern \"C\" fn callback_{index}<T: {trait_name}>(\
+                 data: *mut ::std::ffi::c_void, this: {this_ffi_type} {overrider}{callback_args}\
+                 ){callback_return_type} {{\n\
+                 // unwinding into C++ code is undefined behavior\n\
+                 let result = ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe(|| \
+                 unsafe {{\n\
+                 let methods = &*(data as *const T);\n\
+                 let result = methods.{name}({this_type}::from_raw(this){func_args});\n\
+                 {result}\n\
+                 }}));\n\
+                 match result {{\n\
+                 Ok(result) => result,\n\
+                 Err(_) => ::std::process::abort(),\n\
+                 }}\n\
+                 }}",
+                index = index,
+                trait_name = trait_name,
+                this_ffi_type = this_ffi_type,
+                overrider = overrider,
+                callback_args = callback_args,
+                callback_return_type = callback_return_type,
+                name = method.name,
+                this_type = this_type,
+                func_args = func_args,
+                result = self.convert_type_to_ffi("result", &method.return_type)?,
+            )?;
+            writeln!(
+                set_callbacks,
+                "obj.{}(Some(callback_{}::<T>));",
+                method.set_callback_function, index
+            )?;
+        }
+
+        let doc = format!(
+            "Allows to implement virtual methods of `{}` in Rust.\n\n\
+             Use `{}::new_with_methods` to create a C++ object \
+             that calls methods of this trait.\n\n\
+             The methods take `&self` because C++ may call them re-entrantly \
+             (e.g. if a method calls another virtual method using `this`), \
+             so implementations should use interior mutability \
+             (`Cell`, `RefCell`) to modify their state.",
+            self.rust_path_to_string(&data.base_class_path),
+            overrider,
+        ) + &condition_texts.doc_text;
+        write!(self, "{}", format_doc(&doc))?;
+        writeln!(
+            self,
+            include_str!("../templates/crate/virtual_methods_trait.rs.in"),
+            condition_attribute = condition_texts.attribute,
+            trait_name = trait_name,
+            trait_methods = trait_methods,
+            overrider = overrider,
+            callbacks = callbacks,
+            set_callbacks = set_callbacks,
+        )?;
+        Ok(())
+    }
+
     // TODO: generate relative paths for better readability
     fn rust_path_to_string(&self, path: &RustPath) -> String {
         path.full_name(Some(&self.current_database.crate_name()))
@@ -693,6 +915,23 @@ impl Generator<'_> {
                 )?;
                 format!("&{}", expr)
             }
+            RustToFfiTypeConversion::ArrayRefToPtr { .. } => {
+                bail!("ArrayRefToPtr is not convertable from FFI type");
+            }
+            RustToFfiTypeConversion::UtilsArrayRefToPtr { array_type } => {
+                let api_type_path = &type1.api_type().as_common()?.path;
+                let mut array_ptr_type = type1.ffi_type().clone();
+                if let RustType::PointerLike { target, .. } = &mut array_ptr_type {
+                    *target = Box::new(array_type.clone());
+                }
+                let code = format!(
+                    "{}::from_raw({} as {}).expect(\"attempted to construct a null Ref\")",
+                    self.rust_path_to_string(api_type_path),
+                    source_expr,
+                    self.rust_type_to_code(&array_ptr_type)
+                );
+                wrap_unsafe(in_unsafe_context, &code)
+            }
             RustToFfiTypeConversion::ImplCastInto(_) => {
                 bail!("ImplCastInto is not convertable from FFI type");
             }
@@ -757,6 +996,21 @@ impl Generator<'_> {
             RustToFfiTypeConversion::AsCast { .. } => {
                 format!("{} as {}", expr, self.rust_type_to_code(type1.ffi_type()))
             }
+            RustToFfiTypeConversion::ArrayRefToPtr { array_type } => {
+                let mut array_ptr_type = type1.ffi_type().clone();
+                if let RustType::PointerLike { target, .. } = &mut array_ptr_type {
+                    *target = Box::new(array_type.clone());
+                }
+                format!(
+                    "{} as {} as {}",
+                    expr,
+                    self.rust_type_to_code(&array_ptr_type),
+                    self.rust_type_to_code(type1.ffi_type())
+                )
+            }
+            RustToFfiTypeConversion::UtilsArrayRefToPtr { .. } => {
+                bail!("UtilsArrayRefToPtr is not supported in argument position");
+            }
             RustToFfiTypeConversion::RefTo(conversion) => {
                 let intermediate =
                     RustFinalType::new(type1.ffi_type().clone(), (**conversion).clone())?;
@@ -787,10 +1041,10 @@ impl Generator<'_> {
     /// an `unsafe` block.
     fn generate_ffi_call(
         &self,
-        id: &ItemId,
+        ffi_item: &CppFfiFunction,
         arguments: &[RustFunctionArgument],
         return_type: &RustFinalType,
-        wrapper_data: &RustFfiWrapperData,
+        ffi_function_path: &RustPath,
         in_unsafe_context: bool,
     ) -> Result<String> {
         let mut final_args = Vec::new();
@@ -803,14 +1057,6 @@ impl Generator<'_> {
         let mut result = Vec::new();
         let mut maybe_result_var_name = None;
 
-        let ffi_item = self
-            .current_database
-            .source_ffi_item(id)?
-            .ok_or_else(|| err_msg("source ffi item not found"))?
-            .item
-            .as_function_ref()
-            .ok_or_else(|| err_msg("invalid source ffi item type"))?;
-
         let return_type_ffi_index = ffi_item
             .arguments
             .iter()
@@ -863,7 +1109,7 @@ impl Generator<'_> {
             in_unsafe_context,
             &format!(
                 "{}({}){maybe_semicolon}",
-                self.rust_path_to_string(&wrapper_data.ffi_function_path),
+                self.rust_path_to_string(ffi_function_path),
                 final_args.join(", "),
                 maybe_semicolon = if maybe_result_var_name.is_some() {
                     ";"
@@ -883,6 +1129,69 @@ impl Generator<'_> {
         }
     }
 
+    /// Generates Rust code for calling an FFI function corresponding
+    /// to the set optional arguments.
+    fn generate_optional_arguments_call(
+        &self,
+        function: &RustFunction,
+        data: &RustOptionalArgumentsWrapperData,
+        optional_arguments_name: &str,
+    ) -> Result<String> {
+        let full_variant = data
+            .variants
+            .get(0)
+            .ok_or_else(|| err_msg("no variants of function with optional arguments"))?;
+        let names = full_variant.arguments[function.arguments.len()..]
+            .iter()
+            .map(|arg| arg.name.as_str())
+            .collect_vec();
+
+        let mut code = format!(
+            "let {} {{ {} }} = {};\nmatch ({},) {{\n",
+            self.rust_path_to_string(&data.arguments_struct_path),
+            names.join(", "),
+            optional_arguments_name,
+            names.join(", ")
+        );
+        for variant in &data.variants {
+            let set_count = variant.arguments.len() - function.arguments.len();
+            let patterns = names
+                .iter()
+                .enumerate()
+                .map(|(index, name)| {
+                    if index < set_count {
+                        format!("Some({})", name)
+                    } else {
+                        "None".to_string()
+                    }
+                })
+                .join(", ");
+            let ffi_item = self
+                .current_database
+                .ffi_item(&variant.ffi_item_id)?
+                .item
+                .as_function_ref()
+                .ok_or_else(|| err_msg("invalid ffi item type"))?;
+            let call = self.generate_ffi_call(
+                ffi_item,
+                &variant.arguments,
+                &function.return_type,
+                &variant.ffi_function_path,
+                function.is_unsafe,
+            )?;
+            writeln!(code, "({},) => {{\n{}\n}}", patterns, call)?;
+        }
+        if names.len() > 1 {
+            writeln!(
+                code,
+                "_ => panic!(\"optional arguments must be set in order: \
+                 an argument can't be set if any of the previous arguments is unset\"),"
+            )?;
+        }
+        code.push('}');
+        Ok(code)
+    }
+
     /// Generates Rust code for declaring a function's arguments.
     fn arg_texts(
         &self,
@@ -916,20 +1225,9 @@ impl Generator<'_> {
                     }
                 }
             }
-            let mut maybe_mut_declaration = "";
-            if let RustType::Common { .. } = arg.argument_type.api_type() {
-                if arg.argument_type.conversion() == &RustToFfiTypeConversion::ValueToPtr {
-                    if let RustType::PointerLike { is_const, .. } = &arg.argument_type.ffi_type() {
-                        if !*is_const {
-                            maybe_mut_declaration = "mut ";
-                        }
-                    }
-                }
-            }
-
             Ok(format!(
                 "{}{}: {}",
-                maybe_mut_declaration,
+                maybe_mut_declaration(arg),
                 arg.name,
                 match lifetime {
                     Some(lifetime) => self.rust_type_to_code(
@@ -941,6 +1239,37 @@ impl Generator<'_> {
         })
     }
 
+    /// Generates Rust code for declaring arguments of a function
+    /// that receives all arguments except `self` as a single tuple.
+    /// Returns the declarations and the statement that unpacks the tuple.
+    fn tuple_arg_texts(
+        &self,
+        args: &[RustFunctionArgument],
+        self_type: Option<&RustType>,
+    ) -> Result<(Vec<String>, String)> {
+        let (self_args, other_args): (Vec<_>, Vec<_>) =
+            args.iter().cloned().partition(|arg| arg.name == "self");
+        let mut texts = self.arg_texts(&self_args, None, self_type)?;
+        let tuple_type = RustType::Tuple(
+            other_args
+                .iter()
+                .map(|arg| arg.argument_type.api_type().clone())
+                .collect(),
+        );
+        let tuple_name = optional_arguments_name(args);
+        texts.push(format!(
+            "{}: {}",
+            tuple_name,
+            self.rust_type_to_code(&tuple_type)
+        ));
+        let patterns = other_args
+            .iter()
+            .map(|arg| format!("{}{},", maybe_mut_declaration(arg), arg.name))
+            .join(" ");
+        let unpack = format!("let ({}) = {};\n", patterns, tuple_name);
+        Ok((texts, unpack))
+    }
+
     /// Generates complete code of a Rust wrapper function.
     fn generate_function(
         &mut self,
@@ -956,14 +1285,33 @@ impl Generator<'_> {
         };
         let maybe_unsafe = if func.item.is_unsafe { "unsafe " } else { "" };
 
+        let optional_arguments_name = optional_arguments_name(&func.item.arguments);
         let body = match &func.item.kind {
-            RustFunctionKind::FfiWrapper(data) => Some(self.generate_ffi_call(
-                &func.id,
-                &func.item.arguments,
-                &func.item.return_type,
-                data,
-                func.item.is_unsafe,
-            )?),
+            RustFunctionKind::FfiWrapper(data) => {
+                let ffi_item = self
+                    .current_database
+                    .source_ffi_item(&func.id)?
+                    .ok_or_else(|| err_msg("source ffi item not found"))?
+                    .item
+                    .as_function_ref()
+                    .ok_or_else(|| err_msg("invalid source ffi item type"))?;
+                let call = self.generate_ffi_call(
+                    ffi_item,
+                    &func.item.arguments,
+                    &func.item.return_type,
+                    &data.ffi_function_path,
+                    func.item.is_unsafe,
+                )?;
+                if data.has_tuple_arguments {
+                    let (_, unpack) = self.tuple_arg_texts(&func.item.arguments, self_type)?;
+                    Some(unpack + &call)
+                } else {
+                    Some(call)
+                }
+            }
+            RustFunctionKind::OptionalArgumentsWrapper(data) => Some(
+                self.generate_optional_arguments_call(func.item, data, &optional_arguments_name)?,
+            ),
             RustFunctionKind::SignalOrSlotGetter(getter) => {
                 let path = &func.item.return_type.api_type().as_common()?.path;
                 let call = format!(
@@ -1005,6 +1353,20 @@ impl Generator<'_> {
             )
         };
 
+        let mut arg_texts = match &func.item.kind {
+            RustFunctionKind::FfiWrapper(data) if data.has_tuple_arguments => {
+                self.tuple_arg_texts(&func.item.arguments, self_type)?.0
+            }
+            _ => self.arg_texts(&func.item.arguments, None, self_type)?,
+        };
+        if let RustFunctionKind::OptionalArgumentsWrapper(data) = &func.item.kind {
+            arg_texts.push(format!(
+                "{}: {}",
+                optional_arguments_name,
+                self.rust_path_to_string(&data.arguments_struct_path)
+            ));
+        }
+
         // TODO: move condition texts to doc parser
         let doc = doc_formatter::function_doc(func.clone(), self.current_database)?
             + &condition_texts.doc_text;
@@ -1019,9 +1381,7 @@ impl Generator<'_> {
             maybe_unsafe = maybe_unsafe,
             lifetimes_text = lifetimes_text,
             name = func.item.path.last(),
-            args = self
-                .arg_texts(&func.item.arguments, None, self_type)?
-                .join(", "),
+            args = arg_texts.join(", "),
             return_type = return_type_for_signature,
             maybe_body = maybe_body
         )?;
@@ -1104,6 +1464,30 @@ impl Generator<'_> {
     }
 }
 
+/// Returns `"mut "` if `arg` should be declared as mutable.
+fn maybe_mut_declaration(arg: &RustFunctionArgument) -> &'static str {
+    if let RustType::Common { .. } = arg.argument_type.api_type() {
+        if arg.argument_type.conversion() == &RustToFfiTypeConversion::ValueToPtr {
+            if let RustType::PointerLike { is_const, .. } = &arg.argument_type.ffi_type() {
+                if !*is_const {
+                    return "mut ";
+                }
+            }
+        }
+    }
+    ""
+}
+
+/// Returns name of the argument containing the struct with optional arguments
+/// (or the tuple of arguments) that doesn't conflict with names of other arguments.
+fn optional_arguments_name(arguments: &[RustFunctionArgument]) -> String {
+    let mut name = "args".to_string();
+    while arguments.iter().any(|arg| arg.name == name) {
+        name.push('_');
+    }
+    name
+}
+
 pub fn generate(
     current_database: &DatabaseClient,
     output_src_path: impl Into<PathBuf>,
@@ -1,6 +1,9 @@
-use crate::config::CrateDependencyKind;
+use crate::config::{CrateDependencyKind, DefaultArgumentsMode};
+use crate::cpp_checker::{self, Snippet, SnippetRunner, SnippetTask, SnippetTaskLocalData};
 use crate::cpp_checks::CppChecks;
-use crate::cpp_data::{CppItem, CppPath, CppPathItem, CppTypeDeclaration, CppTypeDeclarationKind};
+use crate::cpp_data::{
+    CppClassField, CppItem, CppPath, CppPathItem, CppTypeDeclaration, CppTypeDeclarationKind,
+};
 use crate::cpp_ffi_data::{
     CppCast, CppFfiArgumentMeaning, CppFfiFunction, CppFfiFunctionKind, CppFfiItem, CppFfiType,
     CppFieldAccessorType, CppToFfiTypeConversion,
@@ -11,21 +14,24 @@ use crate::cpp_type::{
     is_qflags, CppBuiltInNumericType, CppFunctionPointerType, CppPointerLikeTypeKind,
     CppSpecificNumericType, CppSpecificNumericTypeKind, CppType, CppTypeRole,
 };
-use crate::database::{DbItem, ItemWithSource};
+use crate::database::{DbItem, ItemId, ItemWithSource};
 use crate::processor::ProcessorData;
 use crate::rust_info::{
     NameType, RustEnumValue, RustExtraImpl, RustExtraImplKind, RustFfiWrapperData,
     RustFlagEnumImpl, RustFunction, RustFunctionArgument, RustFunctionCaptionStrategy,
-    RustFunctionKind, RustFunctionSelfArgKind, RustItem, RustModule, RustModuleKind, RustPathScope,
-    RustQtReceiverType, RustQtSlotWrapper, RustRawQtSlotWrapperData, RustRawSlotReceiver,
-    RustReexport, RustReexportSource, RustSignalOrSlotGetter, RustSizedType, RustSpecialModuleKind,
-    RustStruct, RustStructKind, RustTraitAssociatedType, RustTraitImpl, RustTraitImplExtraKind,
-    RustTypeCaptionStrategy, RustWrapperTypeKind, UnnamedRustFunction,
+    RustFunctionKind, RustFunctionSelfArgKind, RustItem, RustModule, RustModuleKind,
+    RustOptionalArgumentsStruct, RustOptionalArgumentsVariant, RustOptionalArgumentsWrapperData,
+    RustPathScope, RustQtReceiverType, RustQtSlotWrapper, RustRawQtSlotWrapperData,
+    RustRawSlotReceiver, RustReexport, RustReexportSource, RustSignalOrSlotGetter, RustSizedType,
+    RustSpecialModuleKind, RustStruct, RustStructField, RustStructKind, RustTraitAssociatedType,
+    RustTraitImpl, RustTraitImplExtraKind, RustTypeAlias, RustTypeCaptionStrategy,
+    RustVirtualMethod, RustVirtualMethodsTrait, RustWrapperTypeKind, UnnamedRustFunction,
 };
 use crate::rust_type::{
     RustCommonType, RustFinalType, RustPath, RustPointerLikeTypeKind, RustToFfiTypeConversion,
     RustType,
 };
+use crate::type_allocation_places;
 use itertools::Itertools;
 use log::{debug, trace};
 use ritual_common::errors::{bail, err_msg, format_err, print_trace, Result};
@@ -58,6 +64,44 @@ fn sanitize_rust_identifier(name: &str, is_module: bool) -> String {
     }
 }
 
+/// Returns code that only compiles if `fields` (in the same order) fully describe
+/// the layout of the C struct `path`. A `#[repr(C)]` Rust struct with the same
+/// fields has the same layout as a C struct declared with these fields.
+pub fn c_struct_layout_snippet(path: &CppPath, fields: &[&CppClassField]) -> Result<Snippet> {
+    let struct_code = path.to_cpp_code()?;
+    let layout_name = format!("ritual_layout_{}", path.ascii_caption());
+    let mut code = "#include <cstddef>\n".to_string();
+    code += &format!("struct {} {{\n", layout_name);
+    for field in fields {
+        let name = &field.path.last().name;
+        let declaration = if field.field_type.is_array() || field.field_type.is_function_pointer() {
+            field.field_type.to_cpp_code(Some(name))?
+        } else {
+            format!("{} {}", field.field_type.to_cpp_code(None)?, name)
+        };
+        code += &format!("    {};\n", declaration);
+    }
+    code += "};\n";
+    for operator in &["sizeof", "alignof"] {
+        code += &format!(
+            "static_assert({op}({}) == {op}({}), \"{op} mismatch\");\n",
+            struct_code,
+            layout_name,
+            op = operator
+        );
+    }
+    for field in fields {
+        let name = &field.path.last().name;
+        code += &format!(
+            "static_assert(offsetof({}, {name}) == offsetof({}, {name}), \"offset of {name} mismatch\");\n",
+            struct_code,
+            layout_name,
+            name = name
+        );
+    }
+    Ok(Snippet::new_global(code, false))
+}
+
 #[test]
 fn sanitize_rust_identifier_test() {
     assert_eq!(&sanitize_rust_identifier("good", false), "good");
@@ -71,9 +115,9 @@ fn sanitize_rust_identifier_test() {
 }
 
 #[derive(Debug)]
-struct FunctionWithDesiredPath {
-    function: UnnamedRustFunction,
-    desired_path: RustPath,
+pub struct FunctionWithDesiredPath {
+    pub function: UnnamedRustFunction,
+    pub desired_path: RustPath,
 }
 
 enum ProcessedFfiItem {
@@ -82,7 +126,7 @@ enum ProcessedFfiItem {
 }
 
 #[derive(Debug, Clone, Copy)]
-enum ReturnTypeConstraint {
+pub enum ReturnTypeConstraint {
     Bool,
     Usize,
     Unit,
@@ -90,17 +134,20 @@ enum ReturnTypeConstraint {
 }
 
 #[derive(Debug, Clone, Copy)]
-struct TraitImplInfo {
-    trait_path: &'static str,
-    function_name: &'static str,
-    is_unsafe: bool,
-    is_inherent: bool,
-    has_output_associated_type: bool,
-    trait_arg_is_second_arg_type: bool,
-    second_arg_is_reference: bool,
-    return_type_constraint: ReturnTypeConstraint,
-    self_arg_kind: RustFunctionSelfArgKind,
-    target_is_reference: bool,
+pub struct TraitImplInfo {
+    pub trait_path: &'static str,
+    pub function_name: &'static str,
+    pub is_unsafe: bool,
+    pub is_inherent: bool,
+    pub has_output_associated_type: bool,
+    pub trait_arg_is_second_arg_type: bool,
+    /// True if all arguments except `self` are passed to the trait function
+    /// as a tuple, and the type of the tuple is the trait argument.
+    pub trait_args_are_tuple: bool,
+    pub second_arg_is_reference: bool,
+    pub return_type_constraint: ReturnTypeConstraint,
+    pub self_arg_kind: RustFunctionSelfArgKind,
+    pub target_is_reference: bool,
 }
 
 impl TraitImplInfo {
@@ -114,6 +161,7 @@ impl TraitImplInfo {
                 self_arg_kind: RustFunctionSelfArgKind::Value,
                 has_output_associated_type: true,
                 trait_arg_is_second_arg_type: true,
+                trait_args_are_tuple: false,
                 second_arg_is_reference: false,
                 return_type_constraint: ReturnTypeConstraint::Any,
                 target_is_reference: true,
@@ -126,6 +174,7 @@ impl TraitImplInfo {
                 self_arg_kind: RustFunctionSelfArgKind::Value,
                 has_output_associated_type: true,
                 trait_arg_is_second_arg_type: true,
+                trait_args_are_tuple: false,
                 second_arg_is_reference: false,
                 return_type_constraint: ReturnTypeConstraint::Any,
                 target_is_reference: true,
@@ -138,6 +187,7 @@ impl TraitImplInfo {
                 self_arg_kind: RustFunctionSelfArgKind::Value,
                 has_output_associated_type: true,
                 trait_arg_is_second_arg_type: true,
+                trait_args_are_tuple: false,
                 second_arg_is_reference: false,
                 return_type_constraint: ReturnTypeConstraint::Any,
                 target_is_reference: true,
@@ -150,6 +200,7 @@ impl TraitImplInfo {
                 self_arg_kind: RustFunctionSelfArgKind::Value,
                 has_output_associated_type: true,
                 trait_arg_is_second_arg_type: true,
+                trait_args_are_tuple: false,
                 second_arg_is_reference: false,
                 return_type_constraint: ReturnTypeConstraint::Any,
                 target_is_reference: true,
@@ -162,6 +213,7 @@ impl TraitImplInfo {
                 self_arg_kind: RustFunctionSelfArgKind::Value,
                 has_output_associated_type: true,
                 trait_arg_is_second_arg_type: true,
+                trait_args_are_tuple: false,
                 second_arg_is_reference: false,
                 return_type_constraint: ReturnTypeConstraint::Any,
                 target_is_reference: true,
@@ -174,6 +226,7 @@ impl TraitImplInfo {
                 self_arg_kind: RustFunctionSelfArgKind::Value,
                 has_output_associated_type: true,
                 trait_arg_is_second_arg_type: true,
+                trait_args_are_tuple: false,
                 second_arg_is_reference: false,
                 return_type_constraint: ReturnTypeConstraint::Any,
                 target_is_reference: true,
@@ -186,6 +239,7 @@ impl TraitImplInfo {
                 self_arg_kind: RustFunctionSelfArgKind::Value,
                 has_output_associated_type: true,
                 trait_arg_is_second_arg_type: true,
+                trait_args_are_tuple: false,
                 second_arg_is_reference: false,
                 return_type_constraint: ReturnTypeConstraint::Any,
                 target_is_reference: true,
@@ -198,6 +252,7 @@ impl TraitImplInfo {
                 self_arg_kind: RustFunctionSelfArgKind::Value,
                 has_output_associated_type: true,
                 trait_arg_is_second_arg_type: true,
+                trait_args_are_tuple: false,
                 second_arg_is_reference: false,
                 return_type_constraint: ReturnTypeConstraint::Any,
                 target_is_reference: true,
@@ -210,6 +265,7 @@ impl TraitImplInfo {
                 self_arg_kind: RustFunctionSelfArgKind::Value,
                 has_output_associated_type: true,
                 trait_arg_is_second_arg_type: true,
+                trait_args_are_tuple: false,
                 second_arg_is_reference: false,
                 return_type_constraint: ReturnTypeConstraint::Any,
                 target_is_reference: true,
@@ -222,6 +278,7 @@ impl TraitImplInfo {
                 self_arg_kind: RustFunctionSelfArgKind::Value,
                 has_output_associated_type: true,
                 trait_arg_is_second_arg_type: true,
+                trait_args_are_tuple: false,
                 second_arg_is_reference: false,
                 return_type_constraint: ReturnTypeConstraint::Any,
                 target_is_reference: true,
@@ -234,6 +291,7 @@ impl TraitImplInfo {
                 self_arg_kind: RustFunctionSelfArgKind::ConstRef,
                 has_output_associated_type: false,
                 trait_arg_is_second_arg_type: true,
+                trait_args_are_tuple: false,
                 second_arg_is_reference: true,
                 return_type_constraint: ReturnTypeConstraint::Bool,
                 target_is_reference: false,
@@ -248,6 +306,7 @@ impl TraitImplInfo {
                 return_type_constraint: ReturnTypeConstraint::Bool,
                 has_output_associated_type: false,
                 trait_arg_is_second_arg_type: true,
+                trait_args_are_tuple: false,
                 target_is_reference: false,
             },
             CppOperator::LessThan => TraitImplInfo {
@@ -260,6 +319,7 @@ impl TraitImplInfo {
                 return_type_constraint: ReturnTypeConstraint::Bool,
                 has_output_associated_type: false,
                 trait_arg_is_second_arg_type: true,
+                trait_args_are_tuple: false,
                 target_is_reference: false,
             },
             CppOperator::GreaterThanOrEqualTo => TraitImplInfo {
@@ -272,6 +332,7 @@ impl TraitImplInfo {
                 return_type_constraint: ReturnTypeConstraint::Bool,
                 has_output_associated_type: false,
                 trait_arg_is_second_arg_type: true,
+                trait_args_are_tuple: false,
                 target_is_reference: false,
             },
             CppOperator::LessThanOrEqualTo => TraitImplInfo {
@@ -284,6 +345,7 @@ impl TraitImplInfo {
                 return_type_constraint: ReturnTypeConstraint::Bool,
                 has_output_associated_type: false,
                 trait_arg_is_second_arg_type: true,
+                trait_args_are_tuple: false,
                 target_is_reference: false,
             },
             CppOperator::LogicalNot => TraitImplInfo {
@@ -296,6 +358,7 @@ impl TraitImplInfo {
                 return_type_constraint: ReturnTypeConstraint::Any,
                 has_output_associated_type: true,
                 trait_arg_is_second_arg_type: false,
+                trait_args_are_tuple: false,
                 target_is_reference: true,
             },
             CppOperator::UnaryMinus => TraitImplInfo {
@@ -306,6 +369,7 @@ impl TraitImplInfo {
                 self_arg_kind: RustFunctionSelfArgKind::Value,
                 has_output_associated_type: true,
                 trait_arg_is_second_arg_type: true,
+                trait_args_are_tuple: false,
                 second_arg_is_reference: false,
                 return_type_constraint: ReturnTypeConstraint::Any,
                 target_is_reference: true,
@@ -318,6 +382,7 @@ impl TraitImplInfo {
                 self_arg_kind: RustFunctionSelfArgKind::MutRef,
                 has_output_associated_type: false,
                 trait_arg_is_second_arg_type: true,
+                trait_args_are_tuple: false,
                 second_arg_is_reference: false,
                 return_type_constraint: ReturnTypeConstraint::Unit,
                 target_is_reference: false,
@@ -330,6 +395,7 @@ impl TraitImplInfo {
                 self_arg_kind: RustFunctionSelfArgKind::MutRef,
                 has_output_associated_type: false,
                 trait_arg_is_second_arg_type: true,
+                trait_args_are_tuple: false,
                 second_arg_is_reference: false,
                 return_type_constraint: ReturnTypeConstraint::Unit,
                 target_is_reference: false,
@@ -342,6 +408,7 @@ impl TraitImplInfo {
                 self_arg_kind: RustFunctionSelfArgKind::MutRef,
                 has_output_associated_type: false,
                 trait_arg_is_second_arg_type: true,
+                trait_args_are_tuple: false,
                 second_arg_is_reference: false,
                 return_type_constraint: ReturnTypeConstraint::Unit,
                 target_is_reference: false,
@@ -354,6 +421,7 @@ impl TraitImplInfo {
                 self_arg_kind: RustFunctionSelfArgKind::MutRef,
                 has_output_associated_type: false,
                 trait_arg_is_second_arg_type: true,
+                trait_args_are_tuple: false,
                 second_arg_is_reference: false,
                 return_type_constraint: ReturnTypeConstraint::Unit,
                 target_is_reference: false,
@@ -366,6 +434,7 @@ impl TraitImplInfo {
                 self_arg_kind: RustFunctionSelfArgKind::MutRef,
                 has_output_associated_type: false,
                 trait_arg_is_second_arg_type: true,
+                trait_args_are_tuple: false,
                 second_arg_is_reference: false,
                 return_type_constraint: ReturnTypeConstraint::Unit,
                 target_is_reference: false,
@@ -378,6 +447,7 @@ impl TraitImplInfo {
                 self_arg_kind: RustFunctionSelfArgKind::MutRef,
                 has_output_associated_type: false,
                 trait_arg_is_second_arg_type: true,
+                trait_args_are_tuple: false,
                 second_arg_is_reference: false,
                 return_type_constraint: ReturnTypeConstraint::Unit,
                 target_is_reference: false,
@@ -390,6 +460,7 @@ impl TraitImplInfo {
                 self_arg_kind: RustFunctionSelfArgKind::MutRef,
                 has_output_associated_type: false,
                 trait_arg_is_second_arg_type: true,
+                trait_args_are_tuple: false,
                 second_arg_is_reference: false,
                 return_type_constraint: ReturnTypeConstraint::Unit,
                 target_is_reference: false,
@@ -402,6 +473,7 @@ impl TraitImplInfo {
                 self_arg_kind: RustFunctionSelfArgKind::MutRef,
                 has_output_associated_type: false,
                 trait_arg_is_second_arg_type: true,
+                trait_args_are_tuple: false,
                 second_arg_is_reference: false,
                 return_type_constraint: ReturnTypeConstraint::Unit,
                 target_is_reference: false,
@@ -414,6 +486,7 @@ impl TraitImplInfo {
                 self_arg_kind: RustFunctionSelfArgKind::MutRef,
                 has_output_associated_type: false,
                 trait_arg_is_second_arg_type: true,
+                trait_args_are_tuple: false,
                 second_arg_is_reference: false,
                 return_type_constraint: ReturnTypeConstraint::Unit,
                 target_is_reference: false,
@@ -426,6 +499,7 @@ impl TraitImplInfo {
                 self_arg_kind: RustFunctionSelfArgKind::MutRef,
                 has_output_associated_type: false,
                 trait_arg_is_second_arg_type: true,
+                trait_args_are_tuple: false,
                 second_arg_is_reference: false,
                 return_type_constraint: ReturnTypeConstraint::Unit,
                 target_is_reference: false,
@@ -438,6 +512,7 @@ impl TraitImplInfo {
                 self_arg_kind: RustFunctionSelfArgKind::MutRef,
                 has_output_associated_type: true,
                 trait_arg_is_second_arg_type: false,
+                trait_args_are_tuple: false,
                 second_arg_is_reference: false,
                 return_type_constraint: ReturnTypeConstraint::Any,
                 target_is_reference: false,
@@ -450,6 +525,7 @@ impl TraitImplInfo {
                 self_arg_kind: RustFunctionSelfArgKind::MutRef,
                 has_output_associated_type: true,
                 trait_arg_is_second_arg_type: false,
+                trait_args_are_tuple: false,
                 second_arg_is_reference: false,
                 return_type_constraint: ReturnTypeConstraint::Any,
                 target_is_reference: false,
@@ -462,6 +538,7 @@ impl TraitImplInfo {
                 self_arg_kind: RustFunctionSelfArgKind::Value,
                 has_output_associated_type: true,
                 trait_arg_is_second_arg_type: false,
+                trait_args_are_tuple: false,
                 second_arg_is_reference: false,
                 return_type_constraint: ReturnTypeConstraint::Any,
                 target_is_reference: false,
@@ -475,22 +552,82 @@ impl TraitImplInfo {
             | CppOperator::LogicalAnd
             | CppOperator::LogicalOr
             | CppOperator::BitwiseNot
-            | CppOperator::Subscript
             | CppOperator::AddressOf
             | CppOperator::StructureDereference
             | CppOperator::PointerToMember
-            | CppOperator::FunctionCall
             | CppOperator::Comma
             | CppOperator::New
             | CppOperator::NewArray
             | CppOperator::Delete
             | CppOperator::DeleteArray => return None,
+            CppOperator::Subscript | CppOperator::FunctionCall => {
+                // depends on constness of the method, see `from_member_operator`
+                return None;
+            }
+        })
+    }
+
+    /// Returns trait info for operators that are mapped to different traits
+    /// depending on constness of the method.
+    ///
+    /// `operator[]` takes exactly one argument that is used as the trait argument.
+    /// `operator()` can take any number of arguments that are passed as a tuple.
+    fn from_member_operator(function: &CppFunction) -> Option<TraitImplInfo> {
+        let member = function.member.as_ref()?;
+        if member.is_static {
+            return None;
+        }
+        let self_arg_kind = if member.is_const {
+            RustFunctionSelfArgKind::ConstRef
+        } else {
+            RustFunctionSelfArgKind::MutRef
+        };
+        let (trait_path, function_name, trait_args_are_tuple) = match function.operator.as_ref()? {
+            CppOperator::Subscript => {
+                if function.arguments.len() != 1 {
+                    return None;
+                }
+                if member.is_const {
+                    ("cpp_core::ops::Index", "index", false)
+                } else {
+                    ("cpp_core::ops::IndexMut", "index_mut", false)
+                }
+            }
+            CppOperator::FunctionCall => {
+                if function.allows_variadic_arguments {
+                    return None;
+                }
+                if member.is_const {
+                    ("cpp_core::ops::Call", "call", true)
+                } else {
+                    ("cpp_core::ops::CallMut", "call_mut", true)
+                }
+            }
+            _ => return None,
+        };
+        Some(TraitImplInfo {
+            trait_path,
+            function_name,
+            is_unsafe: true,
+            is_inherent: true,
+            self_arg_kind,
+            has_output_associated_type: true,
+            trait_arg_is_second_arg_type: !trait_args_are_tuple,
+            trait_args_are_tuple,
+            second_arg_is_reference: false,
+            return_type_constraint: ReturnTypeConstraint::Any,
+            target_is_reference: false,
         })
     }
 
-    fn new(function: &CppFunction) -> Option<TraitImplInfo> {
+    pub fn new(function: &CppFunction) -> Option<TraitImplInfo> {
         if let Some(operator) = &function.operator {
-            return Self::from_operator(operator);
+            return match operator {
+                CppOperator::Subscript | CppOperator::FunctionCall => {
+                    Self::from_member_operator(function)
+                }
+                _ => Self::from_operator(operator),
+            };
         }
         if let Some(member) = &function.member {
             if !member.is_static
@@ -508,6 +645,7 @@ impl TraitImplInfo {
                                 self_arg_kind: RustFunctionSelfArgKind::ConstRef,
                                 has_output_associated_type: true,
                                 trait_arg_is_second_arg_type: false,
+                                trait_args_are_tuple: false,
                                 second_arg_is_reference: false,
                                 return_type_constraint: ReturnTypeConstraint::Any,
                                 target_is_reference: false,
@@ -521,6 +659,7 @@ impl TraitImplInfo {
                                 self_arg_kind: RustFunctionSelfArgKind::MutRef,
                                 has_output_associated_type: true,
                                 trait_arg_is_second_arg_type: false,
+                                trait_args_are_tuple: false,
                                 second_arg_is_reference: false,
                                 return_type_constraint: ReturnTypeConstraint::Any,
                                 target_is_reference: false,
@@ -538,6 +677,7 @@ impl TraitImplInfo {
                                 self_arg_kind: RustFunctionSelfArgKind::ConstRef,
                                 has_output_associated_type: true,
                                 trait_arg_is_second_arg_type: false,
+                                trait_args_are_tuple: false,
                                 second_arg_is_reference: false,
                                 return_type_constraint: ReturnTypeConstraint::Any,
                                 target_is_reference: false,
@@ -551,6 +691,7 @@ impl TraitImplInfo {
                                 self_arg_kind: RustFunctionSelfArgKind::MutRef,
                                 has_output_associated_type: true,
                                 trait_arg_is_second_arg_type: false,
+                                trait_args_are_tuple: false,
                                 second_arg_is_reference: false,
                                 return_type_constraint: ReturnTypeConstraint::Any,
                                 target_is_reference: false,
@@ -569,6 +710,7 @@ impl TraitImplInfo {
                                     self_arg_kind: RustFunctionSelfArgKind::ConstRef,
                                     has_output_associated_type: true,
                                     trait_arg_is_second_arg_type: false,
+                                    trait_args_are_tuple: false,
                                     second_arg_is_reference: false,
                                     return_type_constraint: ReturnTypeConstraint::Any,
                                     target_is_reference: false,
@@ -582,6 +724,7 @@ impl TraitImplInfo {
                                     self_arg_kind: RustFunctionSelfArgKind::MutRef,
                                     has_output_associated_type: true,
                                     trait_arg_is_second_arg_type: false,
+                                    trait_args_are_tuple: false,
                                     second_arg_is_reference: false,
                                     return_type_constraint: ReturnTypeConstraint::Any,
                                     target_is_reference: false,
@@ -599,6 +742,7 @@ impl TraitImplInfo {
                             self_arg_kind: RustFunctionSelfArgKind::ConstRef,
                             has_output_associated_type: false,
                             trait_arg_is_second_arg_type: false,
+                            trait_args_are_tuple: false,
                             second_arg_is_reference: false,
                             return_type_constraint: ReturnTypeConstraint::Usize,
                             target_is_reference: false,
@@ -613,9 +757,9 @@ impl TraitImplInfo {
 }
 
 #[derive(Debug)]
-struct TraitTypes {
-    target_type: RustType,
-    trait_type: RustCommonType,
+pub struct TraitTypes {
+    pub target_type: RustType,
+    pub trait_type: RustCommonType,
 }
 
 impl From<&RustTraitImpl> for TraitTypes {
@@ -651,9 +795,23 @@ fn check_trait_impl_uniqueness(
     Ok(())
 }
 
-struct State<'b, 'a> {
-    data: &'b mut ProcessorData<'a>,
-    special_module_paths: HashMap<RustSpecialModuleKind, RustPath>,
+/// Returns true if implementing a foreign trait for a foreign type with `rust_type`
+/// as a generic argument of the trait is allowed in crate `crate_name`.
+fn is_local_type(rust_type: &RustType, crate_name: &str) -> bool {
+    match rust_type {
+        RustType::Common(RustCommonType { path, .. }) => path.crate_name() == Some(crate_name),
+        RustType::PointerLike {
+            kind: RustPointerLikeTypeKind::Reference { .. },
+            target,
+            ..
+        } => is_local_type(target, crate_name),
+        _ => false,
+    }
+}
+
+pub struct State<'b, 'a> {
+    pub data: &'b mut ProcessorData<'a>,
+    pub special_module_paths: HashMap<RustSpecialModuleKind, RustPath>,
 }
 
 impl State<'_, '_> {
@@ -691,6 +849,12 @@ impl State<'_, '_> {
                     // TODO: bool may not be safe for FFI
                     RustType::bool()
                 } else {
+                    let cpp_core_type = |name: &str| {
+                        RustType::Common(RustCommonType {
+                            path: RustPath::from_good_str("cpp_core").join(name),
+                            generic_arguments: None,
+                        })
+                    };
                     let own_name = match *numeric {
                         CppBuiltInNumericType::Bool => unreachable!(),
                         CppBuiltInNumericType::Char => "c_char",
@@ -706,25 +870,17 @@ impl State<'_, '_> {
                         CppBuiltInNumericType::ULongLong => "c_ulonglong",
                         CppBuiltInNumericType::Float => "c_float",
                         CppBuiltInNumericType::Double => "c_double",
-                        CppBuiltInNumericType::WChar => {
-                            return Ok(RustType::Common(RustCommonType {
-                                path: RustPath::from_good_str("cpp_core::wchar_t"),
-                                generic_arguments: None,
-                            }));
+                        CppBuiltInNumericType::WChar => return Ok(cpp_core_type("wchar_t")),
+                        CppBuiltInNumericType::Char8 => return Ok(cpp_core_type("char8_t")),
+                        CppBuiltInNumericType::Char16 => return Ok(cpp_core_type("char16_t")),
+                        CppBuiltInNumericType::Char32 => return Ok(cpp_core_type("char32_t")),
+                        // opaque types, values are passed by pointer
+                        CppBuiltInNumericType::Float16 => return Ok(cpp_core_type("Float16")),
+                        CppBuiltInNumericType::LongDouble => {
+                            return Ok(cpp_core_type("LongDouble"));
                         }
-                        CppBuiltInNumericType::Char16 => {
-                            return Ok(RustType::Common(RustCommonType {
-                                path: RustPath::from_good_str("cpp_core::char16_t"),
-                                generic_arguments: None,
-                            }));
-                        }
-                        CppBuiltInNumericType::Char32 => {
-                            return Ok(RustType::Common(RustCommonType {
-                                path: RustPath::from_good_str("cpp_core::char32_t"),
-                                generic_arguments: None,
-                            }));
-                        }
-                        _ => bail!("unsupported numeric type: {:?}", numeric),
+                        CppBuiltInNumericType::Int128 => return Ok(cpp_core_type("Int128")),
+                        CppBuiltInNumericType::UInt128 => return Ok(cpp_core_type("UInt128")),
                     };
                     let path = RustPath::from_good_str("std::os::raw").join(own_name);
                     RustType::Common(RustCommonType {
@@ -785,7 +941,12 @@ impl State<'_, '_> {
                     generic_arguments: Some(vec![pointer]),
                 })
             }
+            CppType::Array { element_type, size } => RustType::Array {
+                element_type: Box::new(self.ffi_type_to_rust_ffi_type(element_type)?),
+                size: *size,
+            },
             CppType::TemplateParameter { .. } => bail!("invalid cpp type"),
+            CppType::MemberPointer(..) => bail!("member pointers can't be used in FFI directly"),
         };
 
         Ok(rust_type)
@@ -866,6 +1027,10 @@ impl State<'_, '_> {
         checks: Option<&CppChecks>,
     ) -> Result<RustFinalType> {
         let rust_ffi_type = self.ffi_type_to_rust_ffi_type(cpp_ffi_type.ffi_type())?;
+        if cpp_ffi_type.conversion() == &CppToFfiTypeConversion::NoChange {
+            // C structs can be passed by value only if their layout is known
+            self.check_repr_c_type(cpp_ffi_type.ffi_type())?;
+        }
         let mut api_to_ffi_conversion = RustToFfiTypeConversion::None;
         if let RustType::PointerLike { .. } = &rust_ffi_type {
             if let CppToFfiTypeConversion::ValueToPointer { .. } = cpp_ffi_type.conversion() {
@@ -993,10 +1158,50 @@ impl State<'_, '_> {
                 api_type: self.create_qflags(rust_enum_path),
             };
         };
+        if let CppToFfiTypeConversion::ArrayToPointer { .. } = cpp_ffi_type.conversion() {
+            let cpp_array_type = match cpp_ffi_type.original_type() {
+                CppType::PointerLike { target, .. } => &**target,
+                a => a,
+            };
+            let array_type = self.ffi_type_to_rust_ffi_type(cpp_array_type)?;
+            api_to_ffi_conversion = if argument_meaning == &CppFfiArgumentMeaning::ReturnValue {
+                RustToFfiTypeConversion::UtilsArrayRefToPtr { array_type }
+            } else {
+                RustToFfiTypeConversion::ArrayRefToPtr { array_type }
+            };
+        }
 
         RustFinalType::new(rust_ffi_type, api_to_ffi_conversion)
     }
 
+    /// Generates `CompleteType` for a pointer annotated with `ritual:transfer_ownership`.
+    /// Such pointers are represented by `CppBox` in the Rust API.
+    fn owned_pointer_type(
+        &self,
+        cpp_ffi_type: &CppFfiType,
+        checks: &CppChecks,
+    ) -> Result<RustFinalType> {
+        let original_type = cpp_ffi_type.original_type();
+        if cpp_ffi_type.conversion() != &CppToFfiTypeConversion::NoChange
+            || !original_type.is_pointer()
+            || original_type.pointer_like_is_const()?
+            || !original_type.pointer_like_to_target()?.is_class()
+        {
+            bail!(
+                "ownership can only be transferred for non-const pointers to classes, got {}",
+                original_type.to_cpp_pseudo_code()
+            );
+        }
+        if !self.is_type_deletable(cpp_ffi_type.ffi_type(), checks)? {
+            bail!(
+                "ownership can't be transferred for non-deletable type: {}",
+                original_type.to_cpp_pseudo_code()
+            );
+        }
+        let rust_ffi_type = self.ffi_type_to_rust_ffi_type(cpp_ffi_type.ffi_type())?;
+        RustFinalType::new(rust_ffi_type, RustToFfiTypeConversion::CppBoxToPtr)
+    }
+
     /// Generates exact (FFI-compatible) Rust equivalent of `CppAndFfiMethod` object.
     fn generate_ffi_function(&self, data: &CppFfiFunction) -> Result<RustFunction> {
         let mut args = Vec::new();
@@ -1046,7 +1251,7 @@ impl State<'_, '_> {
         Ok(unnamed_function)
     }
 
-    fn process_operator_as_trait_impl(
+    pub fn process_operator_as_trait_impl(
         unnamed_function: UnnamedRustFunction,
         operator_info: TraitImplInfo,
         crate_name: &str,
@@ -1076,25 +1281,41 @@ impl State<'_, '_> {
             self_value_type.clone()
         };
 
+        // `impl CastInto` can't be used in trait arguments
+        let without_cast_into = |arg: &RustFunctionArgument| -> Result<RustFinalType> {
+            let arg_type = &arg.argument_type;
+            Ok(
+                if let RustToFfiTypeConversion::ImplCastInto(conversion) = arg_type.conversion() {
+                    RustFinalType::new(arg_type.ffi_type().clone(), (**conversion).clone())?
+                } else {
+                    arg_type.clone()
+                },
+            )
+        };
+
         let trait_args;
-        let other_type;
+        let other_types;
 
         if operator_info.trait_arg_is_second_arg_type {
-            let mut other_type1 = unnamed_function
-                .arguments
-                .get(1)
-                .ok_or_else(|| err_msg("not enough arguments"))?
-                .argument_type
-                .clone();
-
-            if let RustToFfiTypeConversion::ImplCastInto(conversion) = other_type1.conversion() {
-                other_type1 =
-                    RustFinalType::new(other_type1.ffi_type().clone(), (**conversion).clone())?;
-            }
-            trait_args = Some(vec![other_type1.api_type().clone()]);
-            other_type = Some(other_type1);
+            let other_type = without_cast_into(
+                unnamed_function
+                    .arguments
+                    .get(1)
+                    .ok_or_else(|| err_msg("not enough arguments"))?,
+            )?;
+            trait_args = Some(vec![other_type.api_type().clone()]);
+            other_types = vec![other_type];
+        } else if operator_info.trait_args_are_tuple {
+            other_types = unnamed_function.arguments[1..]
+                .iter()
+                .map_if_ok(without_cast_into)?;
+            let tuple = other_types
+                .iter()
+                .map(|arg_type| arg_type.api_type().clone())
+                .collect();
+            trait_args = Some(vec![RustType::Tuple(tuple)]);
         } else {
-            other_type = None;
+            other_types = Vec::new();
             trait_args = None;
         }
 
@@ -1137,8 +1358,15 @@ impl State<'_, '_> {
             },
         )?;
         function.arguments[0].name = "self".to_string();
-        if let Some(other_type) = other_type {
-            function.arguments[1].argument_type = other_type;
+        for (arg, other_type) in function.arguments[1..].iter_mut().zip(other_types) {
+            arg.argument_type = other_type;
+        }
+        if operator_info.trait_args_are_tuple {
+            if let RustFunctionKind::FfiWrapper(data) = &mut function.kind {
+                data.has_tuple_arguments = true;
+            } else {
+                bail!("tuple arguments are only supported for FFI wrappers");
+            }
         }
 
         if operator_info.second_arg_is_reference {
@@ -1186,6 +1414,163 @@ impl State<'_, '_> {
         })
     }
 
+    /// Generates `NewFrom` implementation for a non-explicit converting constructor.
+    /// If the argument type is declared in the current crate, `CastFrom` implementation
+    /// for `CppBox` of the class is also generated. Otherwise, `ImplicitNewFrom`
+    /// is implemented instead because the orphan rules don't allow
+    /// to implement `CastFrom` directly.
+    pub fn process_converting_constructor(
+        unnamed_function: UnnamedRustFunction,
+        crate_name: &str,
+        trait_types: &[TraitTypes],
+    ) -> Result<Vec<RustTraitImpl>> {
+        if unnamed_function.arguments.len() != 1 {
+            bail!("converting constructor must have one argument");
+        }
+        if unnamed_function.return_type.conversion() != &RustToFfiTypeConversion::CppBoxToPtr {
+            bail!("constructed object can't be returned in CppBox");
+        }
+        let target_type = unnamed_function
+            .return_type
+            .ffi_type()
+            .pointer_like_to_target()?;
+        let parent_path = if let RustType::Common(RustCommonType { path, .. }) = &target_type {
+            path.parent()?
+        } else {
+            bail!("can't get parent for target type: {:?}", target_type);
+        };
+
+        let mut arg_type = unnamed_function.arguments[0].argument_type.clone();
+        if let RustToFfiTypeConversion::ImplCastInto(conversion) = arg_type.conversion() {
+            arg_type = RustFinalType::new(arg_type.ffi_type().clone(), (**conversion).clone())?;
+        }
+        if arg_type.api_type().lifetime().is_some() {
+            bail!("argument types with lifetimes are not supported");
+        }
+
+        let trait_path = RustPath::from_good_str("cpp_core::NewFrom");
+        let trait_type = RustCommonType {
+            path: trait_path.clone(),
+            generic_arguments: Some(vec![arg_type.api_type().clone()]),
+        };
+        check_trait_impl_uniqueness(trait_types, &target_type, &trait_type)?;
+
+        let mut results = Vec::new();
+        if is_local_type(arg_type.api_type(), crate_name) {
+            let cast_trait_path = RustPath::from_good_str("cpp_core::CastFrom");
+            let cast_trait_type = RustCommonType {
+                path: cast_trait_path.clone(),
+                generic_arguments: Some(vec![arg_type.api_type().clone()]),
+            };
+            let box_type = unnamed_function.return_type.api_type().clone();
+            if check_trait_impl_uniqueness(trait_types, &box_type, &cast_trait_type).is_ok() {
+                let mut function = unnamed_function
+                    .clone()
+                    .with_path(cast_trait_path.join("cast_from"));
+                function.arguments[0].argument_type = arg_type.clone();
+                results.push(RustTraitImpl {
+                    target_type: box_type,
+                    parent_path: parent_path.clone(),
+                    trait_type: cast_trait_type,
+                    associated_types: Vec::new(),
+                    functions: vec![function],
+                    extra_kind: RustTraitImplExtraKind::Normal,
+                });
+            }
+        } else {
+            let marker_trait_type = RustCommonType {
+                path: RustPath::from_good_str("cpp_core::ImplicitNewFrom"),
+                generic_arguments: Some(vec![arg_type.api_type().clone()]),
+            };
+            if check_trait_impl_uniqueness(trait_types, &target_type, &marker_trait_type).is_ok() {
+                results.push(RustTraitImpl {
+                    target_type: target_type.clone(),
+                    parent_path: parent_path.clone(),
+                    trait_type: marker_trait_type,
+                    associated_types: Vec::new(),
+                    functions: Vec::new(),
+                    extra_kind: RustTraitImplExtraKind::Normal,
+                });
+            }
+        }
+
+        let mut function = unnamed_function.with_path(trait_path.join("new_from"));
+        function.arguments[0].argument_type = arg_type;
+
+        results.push(RustTraitImpl {
+            target_type,
+            parent_path,
+            trait_type,
+            associated_types: Vec::new(),
+            functions: vec![function],
+            extra_kind: RustTraitImplExtraKind::Normal,
+        });
+        Ok(results)
+    }
+
+    /// Generates `From` implementation for a conversion operator.
+    pub fn process_conversion_operator(
+        unnamed_function: UnnamedRustFunction,
+        crate_name: &str,
+        trait_types: &[TraitTypes],
+    ) -> Result<RustTraitImpl> {
+        if unnamed_function.arguments.len() != 1 {
+            bail!("conversion operator must have one argument");
+        }
+        let self_type = unnamed_function.arguments[0].argument_type.ffi_type();
+        let is_self_const = self_type.is_const_pointer_like()?;
+        let self_value_type = self_type.pointer_like_to_target()?;
+        let parent_path = if let RustType::Common(RustCommonType { path, .. }) = &self_value_type {
+            let type_crate_name = path
+                .crate_name()
+                .ok_or_else(|| err_msg("common type must have crate name"))?;
+            if type_crate_name != crate_name {
+                bail!("self type is outside current crate");
+            }
+            path.parent()?
+        } else {
+            bail!("self type is not Common");
+        };
+
+        let target_type = unnamed_function.return_type.api_type().clone();
+        if target_type.is_unit() {
+            bail!("conversion to unit type is not supported");
+        }
+        if target_type.lifetime().is_some() {
+            bail!("return types with lifetimes are not supported");
+        }
+
+        let trait_path = RustPath::from_good_str("std::convert::From");
+        let trait_type = RustCommonType {
+            path: trait_path.clone(),
+            generic_arguments: Some(vec![RustType::new_reference(
+                is_self_const,
+                self_value_type,
+            )]),
+        };
+        check_trait_impl_uniqueness(trait_types, &target_type, &trait_type)?;
+
+        let mut function = unnamed_function.with_path(trait_path.join("from"));
+        function.is_unsafe = false;
+        function.arguments[0].name = "value".to_string();
+        function.arguments[0].argument_type = RustFinalType::new(
+            function.arguments[0].argument_type.ffi_type().clone(),
+            RustToFfiTypeConversion::RefToPtr {
+                force_api_is_const: Some(is_self_const),
+                lifetime: None,
+            },
+        )?;
+
+        Ok(RustTraitImpl {
+            target_type,
+            parent_path,
+            trait_type,
+            associated_types: Vec::new(),
+            functions: vec![function],
+            extra_kind: RustTraitImplExtraKind::Normal,
+        })
+    }
+
     fn process_destructor(
         unnamed_function: UnnamedRustFunction,
         allocation_place: ReturnValueAllocationPlace,
@@ -1239,9 +1624,10 @@ impl State<'_, '_> {
         })
     }
 
-    fn process_cast(
+    pub fn process_cast(
         mut unnamed_function: UnnamedRustFunction,
         cast: &CppCast,
+        crate_name: &str,
         trait_types: &[TraitTypes],
     ) -> Result<Vec<RustTraitImpl>> {
         let mut results = Vec::new();
@@ -1278,6 +1664,30 @@ impl State<'_, '_> {
                 cast_function_name = "dynamic_cast";
                 cast_function_name_mut = "dynamic_cast_mut";
             }
+            CppCast::CrossCast => {
+                trait_path = RustPath::from_good_str("cpp_core::CrossCast");
+                // the implementation must be placed in the crate of one of the types
+                let is_from_type_local = if let RustType::Common(RustCommonType { path, .. }) =
+                    &from_type.pointer_like_to_target()?
+                {
+                    path.crate_name() == Some(crate_name)
+                } else {
+                    false
+                };
+                derived_type = if is_from_type_local {
+                    from_type
+                } else {
+                    to_type
+                };
+                cast_function_name = "cross_cast";
+                cast_function_name_mut = "cross_cast_mut";
+            }
+            CppCast::MostDerived => {
+                trait_path = RustPath::from_good_str("cpp_core::MostDerived");
+                derived_type = from_type;
+                cast_function_name = "most_derived";
+                cast_function_name_mut = "most_derived_mut";
+            }
             CppCast::QObject => {
                 trait_path = RustPath::from_good_str("qt_core::QObjectCast");
                 derived_type = to_type;
@@ -1306,12 +1716,18 @@ impl State<'_, '_> {
 
         let target_type = from_type.pointer_like_to_target()?;
         let to_type_value = to_type.pointer_like_to_target()?;
+        // `MostDerived` always returns a `void` pointer
+        let generic_arguments = if let CppCast::MostDerived = cast {
+            None
+        } else {
+            Some(vec![to_type_value.clone()])
+        };
         results.push(RustTraitImpl {
             target_type: target_type.clone(),
             parent_path: parent_path.clone(),
             trait_type: RustCommonType {
                 path: trait_path,
-                generic_arguments: Some(vec![to_type_value.clone()]),
+                generic_arguments,
             },
             associated_types: Vec::new(),
             functions: vec![cast_function, cast_function_mut],
@@ -1397,15 +1813,28 @@ impl State<'_, '_> {
             rust_ffi_function,
         ))];
 
+        let annotations = if function.kind == CppFfiFunctionKind::Function {
+            self.data.db.find_annotations_for(&item.id)?
+        } else {
+            None
+        };
+
         let mut arguments = Vec::new();
         for (arg_index, arg) in function.arguments.iter().enumerate() {
             if arg.meaning != CppFfiArgumentMeaning::ReturnValue {
-                let arg_type = self.rust_final_type(
+                let mut arg_type = self.rust_final_type(
                     &arg.argument_type,
                     &arg.meaning,
                     function.allocation_place,
                     Some(checks),
                 )?;
+                if let CppFfiArgumentMeaning::Argument(index) = &arg.meaning {
+                    if annotations.map_or(false, |annotations| {
+                        annotations.ownership_transferring_arguments.contains(index)
+                    }) {
+                        arg_type = self.owned_pointer_type(&arg.argument_type, checks)?;
+                    }
+                }
                 arguments.push(RustFunctionArgument {
                     ffi_index: arg_index,
                     argument_type: arg_type,
@@ -1435,12 +1864,16 @@ impl State<'_, '_> {
         } else {
             // none of the arguments has return value meaning,
             // so FFI return value must be used
-            self.rust_final_type(
-                &function.return_type,
-                &CppFfiArgumentMeaning::ReturnValue,
-                function.allocation_place,
-                Some(checks),
-            )?
+            if annotations.map_or(false, |annotations| annotations.returns_ownership) {
+                self.owned_pointer_type(&function.return_type, checks)?
+            } else {
+                self.rust_final_type(
+                    &function.return_type,
+                    &CppFfiArgumentMeaning::ReturnValue,
+                    function.allocation_place,
+                    Some(checks),
+                )?
+            }
         };
         if return_type.api_type().is_ref() && return_type.api_type().lifetime().is_none() {
             let mut found = false;
@@ -1481,8 +1914,11 @@ impl State<'_, '_> {
             is_public: true,
             arguments: arguments.clone(),
             return_type,
-            kind: RustFunctionKind::FfiWrapper(RustFfiWrapperData { ffi_function_path }),
-            is_unsafe: true,
+            kind: RustFunctionKind::FfiWrapper(RustFfiWrapperData {
+                ffi_function_path,
+                has_tuple_arguments: false,
+            }),
+            is_unsafe: !annotations.map_or(false, |annotations| annotations.is_safe),
         };
 
         let cpp_item = self
@@ -1503,7 +1939,12 @@ impl State<'_, '_> {
                 return Ok(results);
             }
             if let Some(cast) = &cpp_function.cast {
-                let impls = State::process_cast(unnamed_function, cast, trait_types)?;
+                let impls = State::process_cast(
+                    unnamed_function,
+                    cast,
+                    self.data.db.crate_name(),
+                    trait_types,
+                )?;
                 results.extend(
                     impls
                         .into_iter()
@@ -1511,6 +1952,40 @@ impl State<'_, '_> {
                 );
                 return Ok(results);
             }
+            if cpp_function.is_converting_constructor() {
+                // `new` function is generated as well
+                match State::process_converting_constructor(
+                    unnamed_function.clone(),
+                    self.data.db.crate_name(),
+                    trait_types,
+                ) {
+                    Ok(impls) => {
+                        results.extend(
+                            impls
+                                .into_iter()
+                                .map(|x| ProcessedFfiItem::Item(RustItem::TraitImpl(x))),
+                        );
+                    }
+                    Err(err) => {
+                        debug!("failed to convert constructor to NewFrom: {}", err);
+                    }
+                }
+            }
+            if let Some(CppOperator::Conversion(_)) = &cpp_function.operator {
+                // `to_*` function is generated as well
+                match State::process_conversion_operator(
+                    unnamed_function.clone(),
+                    self.data.db.crate_name(),
+                    trait_types,
+                ) {
+                    Ok(item) => {
+                        results.push(ProcessedFfiItem::Item(RustItem::TraitImpl(item)));
+                    }
+                    Err(err) => {
+                        debug!("failed to convert conversion operator to From: {}", err);
+                    }
+                }
+            }
             if cpp_function.operator.as_ref() == Some(&CppOperator::NotEqualTo) {
                 bail!("NotEqualTo is not needed in public API because PartialEq is used");
             }
@@ -1709,6 +2184,11 @@ impl State<'_, '_> {
             .source_cpp_item(&item.id)?
             .ok_or_else(|| err_msg("source cpp item not found"))?
             .item;
+        let renamed = self
+            .data
+            .db
+            .find_annotations_for(&item.id)?
+            .and_then(|annotations| annotations.rename.as_ref());
 
         let r = match &function.kind {
             CppFfiFunctionKind::Function => {
@@ -1716,7 +2196,9 @@ impl State<'_, '_> {
                     .as_function_ref()
                     .ok_or_else(|| err_msg("invalid source cpp item type"))?;
 
-                if cpp_function.is_constructor() {
+                if let Some(name) = renamed {
+                    Some(name.clone())
+                } else if cpp_function.is_constructor() {
                     if cpp_function.is_copy_constructor() {
                         Some("new_copy".to_string())
                     } else {
@@ -1750,7 +2232,7 @@ impl State<'_, '_> {
                     .as_field_ref()
                     .ok_or_else(|| err_msg("invalid source cpp item type"))?;
 
-                let name = &field.path.last().name;
+                let name = renamed.unwrap_or(&field.path.last().name);
                 let function_name = match accessor_type {
                     CppFieldAccessorType::CopyGetter | CppFieldAccessorType::ConstRefGetter => {
                         name.to_string()
@@ -1760,6 +2242,19 @@ impl State<'_, '_> {
                 };
                 Some(function_name)
             }
+            CppFfiFunctionKind::MemberPointerGetter => {
+                let cpp_function = cpp_item
+                    .as_function_ref()
+                    .ok_or_else(|| err_msg("invalid source cpp item type"))?;
+                let name = if let Some(name) = renamed {
+                    name.clone()
+                } else if let Some(operator) = &cpp_function.operator {
+                    operator_function_name(operator)?.to_string()
+                } else {
+                    cpp_function.path.last().name.clone()
+                };
+                Some(format!("{}_member_ptr", name))
+            }
         };
 
         Ok(r)
@@ -1802,6 +2297,12 @@ impl State<'_, '_> {
                 // crate root
                 self.default_path_scope()
             }
+            NameType::Overrider {
+                base_class_path, ..
+            } => RustPathScope {
+                path: base_class_path.parent()?,
+                prefix: None,
+            },
             NameType::Type { .. }
             | NameType::Module { .. }
             | NameType::EnumValue
@@ -1835,6 +2336,15 @@ impl State<'_, '_> {
             }
         };
 
+        // names requested with `ritual:rename` annotations
+        let renamed = match &name_type {
+            NameType::Type { .. } | NameType::EnumValue | NameType::Module { .. } => self
+                .data
+                .db
+                .find_annotations_for_cpp_path(cpp_path)
+                .and_then(|annotations| annotations.rename.as_ref()),
+            _ => None,
+        };
         let full_last_name = match &name_type {
             NameType::SizedItem => cpp_path
                 .items()
@@ -1861,7 +2371,9 @@ impl State<'_, '_> {
                 }
             }
             NameType::Type { .. } | NameType::EnumValue => {
-                if cpp_path.to_templateless_string() == "std::vector" {
+                if let Some(name) = renamed {
+                    name.to_class_case()
+                } else if cpp_path.to_templateless_string() == "std::vector" {
                     // remove allocator template argument
                     let mut path_item = cpp_path.last().clone();
                     if let Some(args) = &mut path_item.template_arguments {
@@ -1874,9 +2386,14 @@ impl State<'_, '_> {
                         .to_class_case()
                 }
             }
-            NameType::Module { .. } => self
-                .cpp_path_item_to_name(&cpp_path.last(), &scope.path, &name_type)?
-                .to_snake_case(),
+            NameType::Module { .. } => {
+                if let Some(name) = renamed {
+                    name.to_snake_case()
+                } else {
+                    self.cpp_path_item_to_name(&cpp_path.last(), &scope.path, &name_type)?
+                        .to_snake_case()
+                }
+            }
             NameType::FfiFunction => cpp_path.last().name.clone(),
             NameType::QtSlotWrapper {
                 signal_arguments,
@@ -1890,6 +2407,17 @@ impl State<'_, '_> {
                     format!("{}_Of_{}", name, captions).to_class_case()
                 }
             }
+            NameType::Overrider {
+                base_class_path,
+                is_trait,
+            } => {
+                let suffix = if *is_trait {
+                    "VirtualMethods"
+                } else {
+                    "Overrider"
+                };
+                format!("{}{}", base_class_path.last(), suffix)
+            }
         };
 
         if name_type == NameType::FfiFunction {
@@ -1925,6 +2453,9 @@ impl State<'_, '_> {
             CppFfiItem::QtSlotWrapper(_) => {
                 bail!("slot wrappers do not need to be processed here");
             }
+            CppFfiItem::Overrider(_) => {
+                bail!("overriders do not need to be processed here");
+            }
         }
     }
 
@@ -1955,11 +2486,29 @@ impl State<'_, '_> {
         }
 
         let mut qt_slot_wrapper = None;
+        let mut overrider = None;
         if let Some(source_ffi_item) = self.data.db.source_ffi_item(&item.id)? {
-            if let Some(item) = source_ffi_item.filter_map(|i| i.as_slot_wrapper_ref()) {
+            if let Some(item) = source_ffi_item
+                .clone()
+                .filter_map(|i| i.as_slot_wrapper_ref())
+            {
                 qt_slot_wrapper = Some(item);
+            } else if let Some(item) = source_ffi_item.filter_map(|i| i.as_overrider_ref()) {
+                overrider = Some(item);
             }
         }
+        let overrider_base_path = if let Some(overrider) = &overrider {
+            let base_type = self.find_wrapper_type(&overrider.item.base_class_path)?;
+            Some(
+                base_type
+                    .item
+                    .path()
+                    .expect("wrapper type must have path")
+                    .clone(),
+            )
+        } else {
+            None
+        };
 
         let is_from_other_crate = item
             .source_id
@@ -1970,6 +2519,11 @@ impl State<'_, '_> {
                 signal_arguments: &wrapper.item.signal_arguments,
                 is_public: false,
             }
+        } else if let Some(base_class_path) = &overrider_base_path {
+            NameType::Overrider {
+                base_class_path,
+                is_trait: false,
+            }
         } else {
             NameType::Type {
                 is_from_other_crate,
@@ -1980,7 +2534,7 @@ impl State<'_, '_> {
 
         let mut rust_items = Vec::new();
 
-        let is_movable = false;
+        let is_movable = type_allocation_places::is_movable(self.data, &item.id, &data.path)?;
 
         let wrapper_kind;
         if is_movable {
@@ -2088,6 +2642,53 @@ impl State<'_, '_> {
             raw_slot_wrapper_data = None;
         }
 
+        if let (Some(overrider), Some(base_class_path)) = (overrider, overrider_base_path) {
+            let trait_path = self.generate_rust_path(
+                &data.path,
+                NameType::Overrider {
+                    base_class_path: &base_class_path,
+                    is_trait: true,
+                },
+            )?;
+            let methods = overrider
+                .item
+                .methods
+                .iter()
+                .map_if_ok(|method| -> Result<_> {
+                    // values are produced behind FFI in both directions, so arguments
+                    // are handled in the same way as return types
+                    let rust_type = |t: &CppFfiType| {
+                        self.rust_final_type(
+                            t,
+                            &CppFfiArgumentMeaning::ReturnValue,
+                            ReturnValueAllocationPlace::NotApplicable,
+                            None,
+                        )
+                    };
+                    let base_function = if method.is_pure_virtual {
+                        None
+                    } else {
+                        Some(format!("base_{}", method.unique_name).to_snake_case())
+                    };
+                    Ok(RustVirtualMethod {
+                        name: sanitize_rust_identifier(&method.unique_name.to_snake_case(), false),
+                        cpp_name: method.name.clone(),
+                        is_const: method.is_const,
+                        arguments: method.arguments.iter().map_if_ok(rust_type)?,
+                        return_type: rust_type(&method.return_type)?,
+                        set_callback_function: format!("set_{}_callback", method.unique_name)
+                            .to_snake_case(),
+                        base_function,
+                    })
+                })?;
+            rust_items.push(RustItem::VirtualMethodsTrait(RustVirtualMethodsTrait {
+                path: trait_path,
+                base_class_path,
+                overrider_path: public_path.clone(),
+                methods,
+            }));
+        }
+
         let public_rust_item = RustItem::Struct(RustStruct {
             path: public_path,
             kind: RustStructKind::WrapperType(wrapper_kind),
@@ -2121,7 +2722,7 @@ impl State<'_, '_> {
                 Ok(vec![rust_item])
             }
             CppItem::Type(data) => match data.kind {
-                CppTypeDeclarationKind::Class { .. } => {
+                CppTypeDeclarationKind::Class { .. } | CppTypeDeclarationKind::Union => {
                     self.process_cpp_class(cpp_item.map(|v| v.as_type_ref().unwrap()))
                 }
                 CppTypeDeclarationKind::Enum => {
@@ -2245,6 +2846,35 @@ impl State<'_, '_> {
                 };
                 Ok(vec![RustItem::Function(rust_function)])
             }
+            CppItem::TypeAlias(alias) => {
+                let rust_path = self.generate_rust_path(
+                    &alias.path,
+                    NameType::Type {
+                        is_from_other_crate: false,
+                    },
+                )?;
+                let target_ffi_type = ffi_type(&alias.target_type, CppTypeRole::NotReturnType)?;
+                let target = if let CppToFfiTypeConversion::ValueToPointer { .. } =
+                    target_ffi_type.conversion()
+                {
+                    // class values are represented by their wrapper types
+                    self.ffi_type_to_rust_ffi_type(&alias.target_type)?
+                } else {
+                    self.rust_final_type(
+                        &target_ffi_type,
+                        &CppFfiArgumentMeaning::ReturnValue,
+                        ReturnValueAllocationPlace::NotApplicable,
+                        None,
+                    )?
+                    .api_type()
+                    .clone()
+                };
+                let rust_item = RustItem::TypeAlias(RustTypeAlias {
+                    path: rust_path,
+                    target,
+                });
+                Ok(vec![rust_item])
+            }
             CppItem::ClassField(_) | CppItem::ClassBase(_) => {
                 // only need to process FFI items
                 Ok(Vec::new())
@@ -2354,10 +2984,181 @@ impl State<'_, '_> {
         Ok(())
     }
 
+    /// Checks that `cpp_type` can be used by value on the Rust side. If it's a class
+    /// (or an array of classes), it must be a C struct with the same layout in Rust.
+    fn check_repr_c_type(&self, cpp_type: &CppType) -> Result<()> {
+        let (element_type, _) = cpp_type.array_dimensions();
+        match element_type {
+            CppType::Class(path) => {
+                let rust_item = self.find_wrapper_type(path)?;
+                match rust_item.item.as_struct_ref().map(|s| &s.kind) {
+                    Some(RustStructKind::WrapperType(RustWrapperTypeKind::ReprCStruct {
+                        ..
+                    })) => {}
+                    _ => bail!(
+                        "layout of {} is unknown, so it can't be used by value",
+                        path.to_cpp_pseudo_code()
+                    ),
+                }
+            }
+            CppType::BuiltInNumeric(numeric) if numeric.is_opaque_in_rust() => {
+                bail!(
+                    "{} has no Rust equivalent, so it can't be used by value",
+                    numeric.to_cpp_code()
+                );
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Generates fields of a `#[repr(C)]` struct equivalent to the C struct `type1`
+    /// and a snippet that verifies that the fields describe the layout of the struct.
+    fn c_struct_fields(
+        &self,
+        type1: &CppTypeDeclaration,
+    ) -> Result<(Vec<RustStructField>, Snippet)> {
+        let path = &type1.path;
+        match &type1.special_members {
+            Some(special_members) if !special_members.has_unknown_members => {}
+            _ => bail!("struct has anonymous or unparsed members"),
+        }
+        let has_bases = self
+            .data
+            .db
+            .cpp_items()
+            .filter_map(|item| item.item.as_base_ref())
+            .any(|base| &base.derived_class_type == path);
+        if has_bases {
+            bail!("structs with bases are not supported");
+        }
+        let fields = self
+            .data
+            .db
+            .cpp_items()
+            .filter_map(|item| item.item.as_field_ref())
+            .filter(|field| !field.is_static && field.path.parent().ok().as_ref() == Some(path))
+            .collect_vec();
+        if fields.is_empty() {
+            bail!("struct has no fields (it may be an opaque type)");
+        }
+        let mut names = HashSet::new();
+        let rust_fields = fields.iter().map_if_ok(|field| -> Result<_> {
+            if field.bit_field_width.is_some() {
+                bail!("bit-fields are not supported: {}", field.short_text());
+            }
+            self.check_repr_c_type(&field.field_type)?;
+            let name = sanitize_rust_identifier(&field.path.last().name.to_snake_case(), false);
+            if !names.insert(name.clone()) {
+                bail!("duplicate field name: {}", name);
+            }
+            Ok(RustStructField {
+                name,
+                field_type: self.ffi_type_to_rust_ffi_type(&field.field_type)?,
+            })
+        })?;
+        Ok((rust_fields, c_struct_layout_snippet(path, &fields)?))
+    }
+
+    /// Replaces opaque wrappers of C structs with `#[repr(C)]` structs
+    /// if all fields of the struct can be represented in Rust and the layout
+    /// of the Rust struct is confirmed by the C++ compiler in all environments.
+    /// Opaque wrappers are kept for incomplete types and structs with unsupported
+    /// or unknown fields.
+    fn process_c_structs(&mut self) -> Result<()> {
+        let environments = cpp_checker::environments(self.data);
+        let crate_name = self.data.db.crate_name().to_string();
+        let mut runner = SnippetRunner::new(self.data)?;
+        let mut rejected = HashSet::new();
+        loop {
+            let candidates = self
+                .data
+                .db
+                .rust_items()
+                .filter(|item| {
+                    item.item.as_struct_ref().map_or(false, |s| {
+                        s.kind
+                            == RustStructKind::WrapperType(
+                                RustWrapperTypeKind::ImmovableClassWrapper,
+                            )
+                    })
+                })
+                .map(|item| item.id)
+                .filter(|id| !rejected.contains(id))
+                .collect_vec();
+
+            let mut structs = HashMap::new();
+            let mut snippets = Vec::new();
+            for rust_item_id in candidates {
+                let cpp_item = self
+                    .data
+                    .db
+                    .source_cpp_item(&rust_item_id)?
+                    .ok_or_else(|| err_msg("source cpp item not found"))?;
+                let type1 = match cpp_item.item.as_type_ref() {
+                    Some(type1) if !type1.kind.is_union() => type1,
+                    _ => continue,
+                };
+                match self.c_struct_fields(type1) {
+                    Ok((fields, snippet)) => {
+                        for env in &environments {
+                            snippets.push(SnippetTask {
+                                snippet: snippet.clone(),
+                                output: None,
+                                data: SnippetTaskLocalData {
+                                    item_id: cpp_item.id.clone(),
+                                    crate_name: crate_name.clone(),
+                                    library_target: env.clone(),
+                                },
+                            });
+                        }
+                        structs.insert(cpp_item.id.clone(), (rust_item_id, fields));
+                    }
+                    Err(err) => {
+                        trace!(
+                            "keeping opaque type for {}: {}",
+                            type1.path.to_cpp_pseudo_code(),
+                            err
+                        );
+                    }
+                }
+            }
+            if structs.is_empty() {
+                break;
+            }
+
+            runner.check(&mut snippets)?;
+            for snippet in &snippets {
+                let is_success = snippet.output.as_ref().map_or(false, |o| o.is_success());
+                if !is_success {
+                    if let Some((rust_item_id, _)) = structs.remove(&snippet.data.item_id) {
+                        debug!(
+                            "keeping opaque type: layout check failed: {:?}",
+                            snippet.output
+                        );
+                        rejected.insert(rust_item_id);
+                    }
+                }
+            }
+            if structs.is_empty() {
+                break;
+            }
+            for (_, (rust_item_id, fields)) in structs {
+                let rust_item = self.data.db.rust_item_mut(&rust_item_id)?;
+                if let RustItem::Struct(rust_struct) = rust_item.item {
+                    rust_struct.kind =
+                        RustStructKind::WrapperType(RustWrapperTypeKind::ReprCStruct { fields });
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn process_ffi_items(
         &mut self,
     ) -> Result<BTreeMap<RustPath, Vec<ItemWithSource<FunctionWithDesiredPath>>>> {
         let mut grouped_functions = BTreeMap::<_, Vec<_>>::new();
+        let mut optional_arguments_groups = BTreeMap::<_, Vec<_>>::new();
         let mut trait_types = self
             .data
             .db
@@ -2390,6 +3191,15 @@ impl State<'_, '_> {
                                     .add_rust_item(Some(ffi_item_id.clone()), rust_item)?;
                             }
                             ProcessedFfiItem::Function(function) => {
+                                if let Some(group_id) =
+                                    self.optional_arguments_group(&ffi_item_id)?
+                                {
+                                    optional_arguments_groups
+                                        .entry(group_id)
+                                        .or_default()
+                                        .push(ItemWithSource::new(&ffi_item_id, function));
+                                    continue;
+                                }
                                 let entry = grouped_functions
                                     .entry(function.desired_path.clone())
                                     .or_default();
@@ -2408,9 +3218,205 @@ impl State<'_, '_> {
                 }
             }
         }
+
+        for (_, variants) in optional_arguments_groups {
+            let functions = match State::process_optional_arguments(&variants) {
+                Ok(function) => vec![function],
+                Err(err) => {
+                    debug!(
+                        "failed to generate function with optional arguments, \
+                         using overloads instead: {}",
+                        err
+                    );
+                    variants
+                }
+            };
+            for function in functions {
+                grouped_functions
+                    .entry(function.item.desired_path.clone())
+                    .or_default()
+                    .push(function);
+            }
+        }
         Ok(grouped_functions)
     }
 
+    /// Returns ID of the C++ function with all arguments if the function
+    /// corresponding to `ffi_item_id` should be exposed as a function with
+    /// optional arguments.
+    pub fn optional_arguments_group(&self, ffi_item_id: &ItemId) -> Result<Option<ItemId>> {
+        let cpp_item = match self.data.db.source_cpp_item(ffi_item_id)? {
+            Some(item) => item,
+            None => return Ok(None),
+        };
+        let mut full_function = match cpp_item.filter_map(|item| item.as_function_ref()) {
+            Some(function) => function,
+            None => return Ok(None),
+        };
+
+        // `cpp_omitting_arguments` creates functions with omitted arguments
+        // from the function with all arguments
+        if let Some(source_id) = full_function.source_id.clone() {
+            let source_function = self
+                .data
+                .db
+                .item(&source_id)
+                .ok()
+                .and_then(|item| item.filter_map(|item| item.as_cpp_item()))
+                .and_then(|item| item.filter_map(|item| item.as_function_ref()));
+            if let Some(source_function) = source_function {
+                if source_function.item.path == full_function.item.path
+                    && source_function.item.arguments.len() > full_function.item.arguments.len()
+                {
+                    full_function = source_function;
+                }
+            }
+        }
+
+        if !full_function
+            .item
+            .arguments
+            .iter()
+            .any(|arg| arg.has_default_value)
+        {
+            return Ok(None);
+        }
+        let mode = self
+            .data
+            .config
+            .default_arguments_mode_for(full_function.item)?;
+        if mode != DefaultArgumentsMode::OptionalArguments {
+            return Ok(None);
+        }
+        Ok(Some(full_function.id))
+    }
+
+    /// Generates a function with optional arguments from the functions
+    /// generated for each number of omitted arguments.
+    pub fn process_optional_arguments(
+        variants: &[ItemWithSource<FunctionWithDesiredPath>],
+    ) -> Result<ItemWithSource<FunctionWithDesiredPath>> {
+        let variants = variants
+            .iter()
+            .sorted_by_key(|variant| std::cmp::Reverse(variant.item.function.arguments.len()))
+            .collect_vec();
+        if variants.len() < 2 {
+            bail!("no functions with omitted arguments");
+        }
+        let full = &variants[0].item;
+        let required_count = variants[variants.len() - 1].item.function.arguments.len();
+
+        let mut result_variants = Vec::new();
+        for (index, variant) in variants.iter().enumerate() {
+            let function = &variant.item.function;
+            // a variant must be available for any number of set arguments
+            if function.arguments.len() != full.function.arguments.len() - index {
+                bail!("some of functions with omitted arguments are not available");
+            }
+            if variant.item.desired_path != full.desired_path
+                || function.return_type != full.function.return_type
+                || function.is_unsafe != full.function.is_unsafe
+                || function.arguments[..] != full.function.arguments[..function.arguments.len()]
+            {
+                bail!("functions with omitted arguments are not compatible");
+            }
+            let ffi_function_path = if let RustFunctionKind::FfiWrapper(data) = &function.kind {
+                data.ffi_function_path.clone()
+            } else {
+                bail!("unexpected function kind: {:?}", function.kind);
+            };
+
+            let mut arguments = function.arguments.clone();
+            for arg in &mut arguments[required_count..] {
+                // arguments are stored in struct fields, so they can't be generic
+                if let RustToFfiTypeConversion::ImplCastInto(conversion) =
+                    arg.argument_type.conversion()
+                {
+                    arg.argument_type = RustFinalType::new(
+                        arg.argument_type.ffi_type().clone(),
+                        (**conversion).clone(),
+                    )?;
+                }
+                if arg.argument_type.api_type().lifetime().is_some() {
+                    bail!("optional argument can't have a lifetime: {:?}", arg);
+                }
+            }
+            result_variants.push(RustOptionalArgumentsVariant {
+                ffi_function_path,
+                ffi_item_id: variant.source_id.clone(),
+                arguments,
+            });
+        }
+
+        let mut function = full.function.clone();
+        function.arguments.truncate(required_count);
+        function.kind =
+            RustFunctionKind::OptionalArgumentsWrapper(RustOptionalArgumentsWrapperData {
+                // the struct is created in `finalize_functions` when the function's path is known
+                arguments_struct_path: full.desired_path.clone(),
+                variants: result_variants,
+            });
+        Ok(ItemWithSource::new(
+            &variants[0].source_id,
+            FunctionWithDesiredPath {
+                function,
+                desired_path: full.desired_path.clone(),
+            },
+        ))
+    }
+
+    /// Adds the argument struct of a function with optional arguments
+    /// and returns its path.
+    fn add_optional_arguments_struct(
+        &mut self,
+        function: &RustFunction,
+        source_id: &ItemId,
+    ) -> Result<RustPath> {
+        let data = if let RustFunctionKind::OptionalArgumentsWrapper(data) = &function.kind {
+            data
+        } else {
+            bail!("function doesn't have optional arguments");
+        };
+
+        let parent = function.path.parent()?;
+        let name = function.path.last().to_class_case();
+        let is_method = self
+            .data
+            .db
+            .find_rust_item(&parent)
+            .map_or(false, |item| item.item.is_wrapper_type());
+        let path = if is_method {
+            parent
+                .parent()?
+                .join(format!("{}{}Args", parent.last(), name))
+        } else {
+            parent.join(format!("{}Args", name))
+        };
+
+        let path = self.data.db.make_unique_rust_path(&path);
+
+        let fields = data.variants[0].arguments[function.arguments.len()..]
+            .iter()
+            .map(|arg| RustStructField {
+                name: arg.name.clone(),
+                field_type: arg.argument_type.api_type().clone(),
+            })
+            .collect();
+        let rust_struct = RustStruct {
+            path: path.clone(),
+            kind: RustStructKind::OptionalArguments(RustOptionalArgumentsStruct {
+                function_path: function.path.clone(),
+                fields,
+            }),
+            is_public: true,
+            raw_slot_wrapper_data: None,
+        };
+        self.data
+            .db
+            .add_rust_item(Some(source_id.clone()), RustItem::Struct(rust_struct))?;
+        Ok(path)
+    }
+
     fn try_caption_strategy(
         &self,
         functions: &[ItemWithSource<FunctionWithDesiredPath>],
@@ -2475,7 +3481,17 @@ impl State<'_, '_> {
                     function.item.desired_path
                 };
                 let final_path = self.data.db.make_unique_rust_path(&path);
-                let item = RustItem::Function(function.item.function.with_path(final_path));
+                let mut rust_function = function.item.function.with_path(final_path);
+                if let RustFunctionKind::OptionalArgumentsWrapper(_) = &rust_function.kind {
+                    let struct_path =
+                        self.add_optional_arguments_struct(&rust_function, &function.source_id)?;
+                    if let RustFunctionKind::OptionalArgumentsWrapper(data) =
+                        &mut rust_function.kind
+                    {
+                        data.arguments_struct_path = struct_path;
+                    }
+                }
+                let item = RustItem::Function(rust_function);
                 self.data.db.add_rust_item(Some(function.source_id), item)?;
             }
         }
@@ -2510,6 +3526,9 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
     }
 
     state.process_cpp_items()?;
+    if state.data.config.is_c_library() {
+        state.process_c_structs()?;
+    }
     let grouped_functions = state.process_ffi_items()?;
     state.finalize_functions(grouped_functions)?;
 
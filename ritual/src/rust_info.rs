@@ -3,11 +3,12 @@
 use crate::cpp_data::CppPath;
 use crate::cpp_ffi_data::CppFfiFunction;
 use crate::cpp_type::CppType;
-use crate::database::DbItem;
+use crate::database::{DbItem, ItemId};
 use crate::rust_code_generator::{rust_common_type_to_code, rust_type_to_code};
 use crate::rust_type::{
     RustCommonType, RustFinalType, RustPath, RustPointerLikeTypeKind, RustType,
 };
+use itertools::Itertools;
 use ritual_common::errors::{bail, Result};
 use serde_derive::{Deserialize, Serialize};
 
@@ -31,7 +32,21 @@ pub struct RustQtSlotWrapper {
 pub enum RustWrapperTypeKind {
     EnumWrapper,
     ImmovableClassWrapper,
-    MovableClassWrapper { sized_type_path: RustPath },
+    MovableClassWrapper {
+        sized_type_path: RustPath,
+    },
+    /// C struct with the same layout on the Rust side
+    ReprCStruct {
+        fields: Vec<RustStructField>,
+    },
+}
+
+/// Public field of a `#[repr(C)]` struct
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct RustStructField {
+    pub name: String,
+    /// FFI-compatible type of the field
+    pub field_type: RustType,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
@@ -39,12 +54,23 @@ pub struct RustSizedType {
     pub cpp_path: CppPath,
 }
 
+/// Argument struct of a function with optional arguments
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct RustOptionalArgumentsStruct {
+    /// Path of the function accepting the struct
+    pub function_path: RustPath,
+    /// Optional arguments in the order of C++ arguments.
+    /// Fields have `Option` of the specified types.
+    pub fields: Vec<RustStructField>,
+}
+
 /// Information about a Rust type wrapper
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub enum RustStructKind {
     WrapperType(RustWrapperTypeKind),
     QtSlotWrapper(RustQtSlotWrapper),
     SizedType(RustSizedType),
+    OptionalArguments(RustOptionalArgumentsStruct),
 }
 
 impl RustStructKind {
@@ -86,6 +112,13 @@ impl RustStructKind {
                     false
                 }
             }
+            RustStructKind::OptionalArguments(data) => {
+                if let RustStructKind::OptionalArguments(other) = other {
+                    data.function_path == other.function_path
+                } else {
+                    false
+                }
+            }
         }
     }
 }
@@ -140,6 +173,32 @@ pub enum RustQtReceiverType {
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct RustFfiWrapperData {
     pub ffi_function_path: RustPath,
+    /// True if all arguments except `self` are passed as a single tuple argument
+    /// (used for `operator()` trait implementations).
+    #[serde(default)]
+    pub has_tuple_arguments: bool,
+}
+
+/// One of FFI functions called by a function with optional arguments,
+/// corresponding to a certain number of omitted arguments.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct RustOptionalArgumentsVariant {
+    pub ffi_function_path: RustPath,
+    /// ID of the FFI function.
+    pub ffi_item_id: ItemId,
+    /// Arguments passed to the FFI function, including the optional arguments
+    /// that are set in this variant.
+    pub arguments: Vec<RustFunctionArgument>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct RustOptionalArgumentsWrapperData {
+    /// Path of the struct containing optional arguments.
+    /// The struct is passed as the last argument of the function.
+    pub arguments_struct_path: RustPath,
+    /// Variants sorted by number of omitted arguments, starting with the variant
+    /// that doesn't omit any arguments.
+    pub variants: Vec<RustOptionalArgumentsVariant>,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
@@ -154,6 +213,7 @@ pub struct RustSignalOrSlotGetter {
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub enum RustFunctionKind {
     FfiWrapper(RustFfiWrapperData),
+    OptionalArgumentsWrapper(RustOptionalArgumentsWrapperData),
     SignalOrSlotGetter(RustSignalOrSlotGetter),
     FfiFunction,
 }
@@ -164,6 +224,13 @@ impl RustFunctionKind {
             RustFunctionKind::FfiWrapper(data) => {
                 format!("FfiWrapper({})", data.ffi_function_path.last())
             }
+            RustFunctionKind::OptionalArgumentsWrapper(data) => format!(
+                "OptionalArgumentsWrapper({})",
+                data.variants
+                    .iter()
+                    .map(|variant| variant.ffi_function_path.last())
+                    .join(", ")
+            ),
             RustFunctionKind::SignalOrSlotGetter(_) => "SignalOrSlotGetter".to_string(),
             RustFunctionKind::FfiFunction => "FfiFunction".to_string(),
         }
@@ -561,6 +628,41 @@ pub struct RustReexport {
     pub source: RustReexportSource,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RustTypeAlias {
+    pub path: RustPath,
+    pub target: RustType,
+}
+
+/// Virtual method that can be overridden by implementing a trait method
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RustVirtualMethod {
+    /// Name of the trait method
+    pub name: String,
+    /// Name of the C++ method
+    pub cpp_name: String,
+    /// True if the C++ method is const
+    pub is_const: bool,
+    pub arguments: Vec<RustFinalType>,
+    pub return_type: RustFinalType,
+    /// Name of the overrider's method that sets the callback
+    pub set_callback_function: String,
+    /// Name of the overrider's method that calls the base class implementation.
+    /// `None` for pure virtual methods.
+    pub base_function: Option<String>,
+}
+
+/// Trait allowing to implement virtual methods of a C++ class in Rust
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RustVirtualMethodsTrait {
+    pub path: RustPath,
+    /// Wrapper of the class that declares the virtual methods
+    pub base_class_path: RustPath,
+    /// Wrapper of the generated C++ subclass
+    pub overrider_path: RustPath,
+    pub methods: Vec<RustVirtualMethod>,
+}
+
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum RustItem {
@@ -571,6 +673,8 @@ pub enum RustItem {
     ExtraImpl(RustExtraImpl),
     Function(RustFunction),
     Reexport(RustReexport),
+    TypeAlias(RustTypeAlias),
+    VirtualMethodsTrait(RustVirtualMethodsTrait),
 }
 
 impl RustItem {
@@ -581,6 +685,8 @@ impl RustItem {
             RustItem::EnumValue(data) => Some(&data.path),
             RustItem::Function(data) => Some(&data.path),
             RustItem::Reexport(data) => Some(&data.path),
+            RustItem::TypeAlias(data) => Some(&data.path),
+            RustItem::VirtualMethodsTrait(data) => Some(&data.path),
             RustItem::TraitImpl(_) | RustItem::ExtraImpl(_) => None,
         }
     }
@@ -639,6 +745,20 @@ impl RustItem {
             None
         }
     }
+    pub fn as_type_alias_ref(&self) -> Option<&RustTypeAlias> {
+        if let RustItem::TypeAlias(value) = self {
+            Some(value)
+        } else {
+            None
+        }
+    }
+    pub fn as_virtual_methods_trait_ref(&self) -> Option<&RustVirtualMethodsTrait> {
+        if let RustItem::VirtualMethodsTrait(value) = self {
+            Some(value)
+        } else {
+            None
+        }
+    }
     pub fn as_function_ref(&self) -> Option<&RustFunction> {
         if let RustItem::Function(value) = self {
             Some(value)
@@ -710,6 +830,17 @@ impl RustItem {
                         false
                     }
                 }
+                RustFunctionKind::OptionalArgumentsWrapper(_) => {
+                    if let RustItem::Function(other) = other {
+                        if let RustFunctionKind::OptionalArgumentsWrapper(_) = &other.kind {
+                            true
+                        } else {
+                            false
+                        }
+                    } else {
+                        false
+                    }
+                }
                 RustFunctionKind::SignalOrSlotGetter(_) => {
                     if let RustItem::Function(other) = other {
                         if let RustFunctionKind::SignalOrSlotGetter(_) = &other.kind {
@@ -740,6 +871,20 @@ impl RustItem {
                     false
                 }
             }
+            RustItem::TypeAlias(_) => {
+                if let RustItem::TypeAlias(_) = other {
+                    true
+                } else {
+                    false
+                }
+            }
+            RustItem::VirtualMethodsTrait(_) => {
+                if let RustItem::VirtualMethodsTrait(_) = other {
+                    true
+                } else {
+                    false
+                }
+            }
         }
     }
 
@@ -804,6 +949,12 @@ impl RustItem {
                 data.path.full_name(None),
                 data.target.last()
             ),
+            RustItem::TypeAlias(data) => format!(
+                "type {} = {}",
+                data.path.full_name(None),
+                rust_type_to_code(&data.target, None)
+            ),
+            RustItem::VirtualMethodsTrait(data) => format!("trait {}", data.path.full_name(None)),
         }
     }
 }
@@ -844,6 +995,10 @@ pub enum NameType<'a> {
         signal_arguments: &'a [CppType],
         is_public: bool,
     },
+    Overrider {
+        base_class_path: &'a RustPath,
+        is_trait: bool,
+    },
 }
 
 impl NameType<'_> {
@@ -168,6 +168,14 @@ pub enum RustToFfiTypeConversion {
     AsCast {
         api_type: RustType,
     },
+    /// `&[T; N]` to `*const T` (or similar mutable types)
+    ArrayRefToPtr {
+        array_type: RustType,
+    },
+    /// `Ref<[T; N]>` to `*const T` (or similar mutable types)
+    UtilsArrayRefToPtr {
+        array_type: RustType,
+    },
     /// Rust public type has an additional reference (`&`)
     RefTo(Box<RustToFfiTypeConversion>),
     ImplCastInto(Box<RustToFfiTypeConversion>),
@@ -286,6 +294,17 @@ impl RustFinalType {
             RustToFfiTypeConversion::QFlagsToUInt { api_type }
             | RustToFfiTypeConversion::AsCast { api_type } => api_type.clone(),
             RustToFfiTypeConversion::UnitToAnything => RustType::unit(),
+            RustToFfiTypeConversion::ArrayRefToPtr { array_type } => {
+                RustType::new_reference(ffi_type.is_const_pointer_like()?, array_type.clone())
+            }
+            RustToFfiTypeConversion::UtilsArrayRefToPtr { array_type } => {
+                let array_ptr = RustType::PointerLike {
+                    kind: RustPointerLikeTypeKind::Pointer,
+                    is_const: ffi_type.is_const_pointer_like()?,
+                    target: Box::new(array_type.clone()),
+                };
+                utils_ref(&array_ptr, None)?
+            }
             RustToFfiTypeConversion::RefTo(conversion) => {
                 let intermediate = RustFinalType::new(ffi_type.clone(), (**conversion).clone())?;
                 RustType::new_reference(true, intermediate.api_type)
@@ -465,6 +484,11 @@ pub enum RustType {
         target: Box<RustType>,
     },
     ImplTrait(RustCommonType),
+    /// Fixed-size array (`[T; N]`)
+    Array {
+        element_type: Box<RustType>,
+        size: usize,
+    },
 }
 
 impl RustType {
@@ -593,6 +617,11 @@ impl RustType {
                 name
             }
             RustType::FunctionPointer { .. } => "fn".to_string(),
+            RustType::Array { element_type, size } => format!(
+                "{}_array_{}",
+                element_type.caption(context, strategy)?,
+                size
+            ),
             RustType::ImplTrait(trait_type) => {
                 if trait_type.path == RustPath::from_good_str("cpp_core::CastInto") {
                     trait_type
@@ -688,6 +717,7 @@ impl RustType {
                     || arguments.iter().any(RustType::is_unsafe_argument)
             }
             RustType::ImplTrait(_) => true,
+            RustType::Array { element_type, .. } => element_type.is_unsafe_argument(),
         }
     }
 
@@ -794,6 +824,17 @@ impl RustType {
                     false
                 }
             }
+            RustType::Array { element_type, size } => {
+                if let RustType::Array {
+                    element_type: other_element_type,
+                    size: other_size,
+                } = other
+                {
+                    size == other_size && element_type.can_be_same_as(other_element_type)
+                } else {
+                    false
+                }
+            }
         }
     }
 }
@@ -0,0 +1,22 @@
+use crate::cpp_annotations::CppAnnotation;
+
+#[test]
+fn parse_annotations() {
+    assert_eq!(CppAnnotation::parse("other").unwrap(), None);
+    assert_eq!(
+        CppAnnotation::parse("ritual:skip").unwrap(),
+        Some(CppAnnotation::Skip)
+    );
+    assert_eq!(
+        CppAnnotation::parse("ritual:rename=from_size").unwrap(),
+        Some(CppAnnotation::Rename("from_size".to_string()))
+    );
+    assert_eq!(
+        CppAnnotation::parse("ritual:transfer_ownership").unwrap(),
+        Some(CppAnnotation::TransferOwnership)
+    );
+    assert!(CppAnnotation::parse("ritual:rename").is_err());
+    assert!(CppAnnotation::parse("ritual:rename=a b").is_err());
+    assert!(CppAnnotation::parse("ritual:safe=1").is_err());
+    assert!(CppAnnotation::parse("ritual:unknown").is_err());
+}
@@ -0,0 +1,180 @@
+use crate::cpp_casts;
+use crate::cpp_data::*;
+use crate::cpp_ffi_data::CppCast;
+use crate::cpp_function::CppFunctionMemberData;
+use crate::cpp_type::{CppPointerLikeTypeKind, CppType};
+use crate::tests::cpp_method::{empty_membership, empty_regular_method};
+use crate::tests::fixtures::{
+    base, class, config, config_with_dependency, pairs, with_processor_data,
+};
+
+fn virtual_method(path: &str) -> CppItem {
+    let mut function = empty_regular_method();
+    function.path = CppPath::from_good_str(path);
+    function.member = Some(CppFunctionMemberData {
+        is_virtual: true,
+        ..empty_membership()
+    });
+    CppItem::Function(function)
+}
+
+fn class_name(pointer_type: &CppType) -> String {
+    match pointer_type {
+        CppType::PointerLike { target, .. } => match &**target {
+            CppType::Class(path) => path.to_cpp_pseudo_code(),
+            _ => panic!("unexpected type: {:?}", target),
+        },
+        _ => panic!("unexpected type: {:?}", pointer_type),
+    }
+}
+
+/// Runs `cpp_casts::run` on `items` with `dependency_items` added to a dependency crate (if any)
+/// and returns added cross casts as pairs of source and target class paths.
+fn run_cross_casts(items: Vec<CppItem>, dependency_items: Vec<CppItem>) -> Vec<(String, String)> {
+    let config = if dependency_items.is_empty() {
+        config()
+    } else {
+        config_with_dependency()
+    };
+    with_processor_data(&config, items, dependency_items, |data| {
+        cpp_casts::run(data).unwrap();
+
+        let mut result = data
+            .db
+            .cpp_items()
+            .filter_map(|item| item.item.as_function_ref())
+            .filter(|function| function.cast == Some(CppCast::CrossCast))
+            .map(|function| {
+                (
+                    class_name(&function.arguments[0].argument_type),
+                    class_name(&function.return_type),
+                )
+            })
+            .collect::<Vec<_>>();
+        result.sort();
+        result
+    })
+}
+
+#[test]
+fn cross_casts_between_bases() {
+    let casts = run_cross_casts(
+        vec![
+            class("A"),
+            class("B"),
+            class("C"),
+            virtual_method("A::a"),
+            virtual_method("B::b"),
+            base("C", "A", 0),
+            base("C", "B", 1),
+        ],
+        vec![],
+    );
+    assert_eq!(casts, pairs(&[("A", "B"), ("B", "A")]));
+}
+
+#[test]
+fn no_cross_casts_to_common_base() {
+    let casts = run_cross_casts(
+        vec![
+            class("Base"),
+            class("A"),
+            class("B"),
+            class("C"),
+            virtual_method("Base::f"),
+            base("A", "Base", 0),
+            base("B", "Base", 0),
+            base("C", "A", 0),
+            base("C", "B", 1),
+        ],
+        vec![],
+    );
+    // `A` and `B` are polymorphic because of their common base,
+    // but casts to and from `Base` itself are not cross casts
+    assert_eq!(casts, pairs(&[("A", "B"), ("B", "A")]));
+}
+
+#[test]
+fn no_cross_casts_for_non_polymorphic_classes() {
+    let casts = run_cross_casts(
+        vec![
+            class("A"),
+            class("B"),
+            class("D"),
+            class("C"),
+            virtual_method("A::a"),
+            virtual_method("D::d"),
+            base("C", "A", 0),
+            base("C", "B", 1),
+            base("C", "D", 2),
+        ],
+        vec![],
+    );
+    assert_eq!(casts, pairs(&[("A", "D"), ("D", "A")]));
+}
+
+#[test]
+fn cross_casts_require_local_class() {
+    let casts = run_cross_casts(
+        vec![
+            class("L"),
+            class("C"),
+            virtual_method("L::l"),
+            base("C", "X", 0),
+            base("C", "Y", 1),
+            base("C", "L", 2),
+        ],
+        vec![
+            class("X"),
+            class("Y"),
+            virtual_method("X::x"),
+            virtual_method("Y::y"),
+        ],
+    );
+    // casts between `X` and `Y` belong to the dependency
+    assert_eq!(
+        casts,
+        pairs(&[("L", "X"), ("L", "Y"), ("X", "L"), ("Y", "L")])
+    );
+}
+
+/// Runs `cpp_casts::run` on `items` and returns class paths of added
+/// most derived casts.
+fn run_most_derived_casts(items: Vec<CppItem>) -> Vec<String> {
+    with_processor_data(&config(), items, Vec::new(), |data| {
+        cpp_casts::run(data).unwrap();
+
+        let mut result = data
+            .db
+            .cpp_items()
+            .filter_map(|item| item.item.as_function_ref())
+            .filter(|function| function.cast == Some(CppCast::MostDerived))
+            .map(|function| {
+                assert_eq!(
+                    function.return_type,
+                    CppType::PointerLike {
+                        is_const: false,
+                        kind: CppPointerLikeTypeKind::Pointer,
+                        target: Box::new(CppType::Void),
+                    }
+                );
+                class_name(&function.arguments[0].argument_type)
+            })
+            .collect::<Vec<_>>();
+        result.sort();
+        result
+    })
+}
+
+#[test]
+fn most_derived_casts_for_polymorphic_classes() {
+    let casts = run_most_derived_casts(vec![
+        class("A"),
+        class("B"),
+        class("C"),
+        virtual_method("A::a"),
+        base("B", "A", 0),
+    ]);
+    // `B` is polymorphic because its base has virtual functions
+    assert_eq!(casts, vec!["A", "B"]);
+}
@@ -0,0 +1,175 @@
+use crate::cpp_data::*;
+use crate::cpp_implicit_methods::{Availability, ImplicitMethodsPredictor, SpecialMemberKind};
+use crate::cpp_type::{CppBuiltInNumericType, CppType};
+use crate::tests::fixtures::{config, with_processor_data};
+
+fn class(
+    path: &str,
+    kind: CppTypeDeclarationKind,
+    special_members: CppClassSpecialMembers,
+) -> CppItem {
+    CppItem::Type(CppTypeDeclaration {
+        path: CppPath::from_good_str(path),
+        kind,
+        special_members: Some(special_members),
+    })
+}
+
+fn field(class_path: &str, name: &str, field_type: CppType) -> CppItem {
+    CppItem::ClassField(CppClassField {
+        path: CppPath::from_good_str(class_path).join(CppPathItem::from_good_str(name)),
+        field_type,
+        visibility: CppVisibility::Public,
+        is_static: false,
+        bit_field_width: None,
+    })
+}
+
+fn declaration(is_deleted: bool) -> Option<CppSpecialMemberDeclaration> {
+    Some(CppSpecialMemberDeclaration {
+        visibility: CppVisibility::Public,
+        is_deleted,
+        is_defaulted: false,
+        has_usual_signature: true,
+    })
+}
+
+fn predict(items: Vec<CppItem>, class_path: &str, kind: SpecialMemberKind) -> Availability {
+    with_processor_data(&config(), items, Vec::new(), |data| {
+        let mut predictor = ImplicitMethodsPredictor::new(data.db);
+        predictor.predict(&CppPath::from_good_str(class_path), kind)
+    })
+}
+
+/// `MoveOnly(const MoveOnly&) = delete; MoveOnly(MoveOnly&&);`
+fn move_only() -> CppItem {
+    class(
+        "MoveOnly",
+        CppTypeDeclarationKind::Class,
+        CppClassSpecialMembers {
+            copy_constructor: declaration(true),
+            has_constructors: true,
+            has_move_operations: true,
+            ..CppClassSpecialMembers::default()
+        },
+    )
+}
+
+#[test]
+fn copy_is_deleted_with_move_only_member() {
+    let items = || {
+        vec![
+            move_only(),
+            class(
+                "Holder",
+                CppTypeDeclarationKind::Class,
+                CppClassSpecialMembers::default(),
+            ),
+            field(
+                "Holder",
+                "value",
+                CppType::Class(CppPath::from_good_str("MoveOnly")),
+            ),
+        ]
+    };
+    assert_eq!(
+        predict(items(), "Holder", SpecialMemberKind::CopyConstructor),
+        Availability::Unavailable
+    );
+    assert_eq!(
+        predict(items(), "Holder", SpecialMemberKind::CopyAssignment),
+        Availability::Unavailable
+    );
+    assert_eq!(
+        predict(items(), "Holder", SpecialMemberKind::DefaultConstructor),
+        Availability::Uncertain
+    );
+    assert_eq!(
+        predict(items(), "Holder", SpecialMemberKind::Destructor),
+        Availability::Available
+    );
+    assert_eq!(
+        predict(items(), "MoveOnly", SpecialMemberKind::CopyConstructor),
+        Availability::Unavailable
+    );
+}
+
+#[test]
+fn user_declared_destructor_suppresses_move() {
+    // a user-declared destructor suppresses implicit move operations,
+    // so the implicit copy operations are still declared
+    let items = || {
+        vec![class(
+            "WithDestructor",
+            CppTypeDeclarationKind::Class,
+            CppClassSpecialMembers {
+                destructor: declaration(false),
+                ..CppClassSpecialMembers::default()
+            },
+        )]
+    };
+    assert_eq!(
+        predict(
+            items(),
+            "WithDestructor",
+            SpecialMemberKind::CopyConstructor
+        ),
+        Availability::Available
+    );
+    assert_eq!(
+        predict(items(), "WithDestructor", SpecialMemberKind::CopyAssignment),
+        Availability::Available
+    );
+    assert_eq!(
+        predict(
+            items(),
+            "WithDestructor",
+            SpecialMemberKind::DefaultConstructor
+        ),
+        Availability::Available
+    );
+    // the user-declared destructor itself is parsed separately
+    assert_eq!(
+        predict(items(), "WithDestructor", SpecialMemberKind::Destructor),
+        Availability::Uncertain
+    );
+}
+
+#[test]
+fn union_with_class_member() {
+    let items = || {
+        vec![
+            class(
+                "Member",
+                CppTypeDeclarationKind::Class,
+                CppClassSpecialMembers::default(),
+            ),
+            class(
+                "U",
+                CppTypeDeclarationKind::Union,
+                CppClassSpecialMembers::default(),
+            ),
+            field(
+                "U",
+                "member",
+                CppType::Class(CppPath::from_good_str("Member")),
+            ),
+            class(
+                "Plain",
+                CppTypeDeclarationKind::Union,
+                CppClassSpecialMembers::default(),
+            ),
+            field(
+                "Plain",
+                "x",
+                CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
+            ),
+        ]
+    };
+    // special members of unions with non-trivial members are deleted,
+    // but triviality of the member is unknown
+    for &kind in SpecialMemberKind::all() {
+        assert_eq!(predict(items(), "U", kind), Availability::Uncertain);
+        assert_eq!(predict(items(), "Plain", kind), Availability::Available);
+    }
+}
@@ -0,0 +1,192 @@
+use crate::cpp_data::*;
+use crate::cpp_inheritance;
+use crate::tests::fixtures::{
+    base, base_with_visibility, class, config, method, pairs, with_processor_data,
+};
+
+/// Runs `cpp_inheritance::run` on `items` and returns added forwarding methods
+/// as pairs of their paths and paths of the classes they were inherited from.
+fn run_inheritance(items: Vec<CppItem>) -> Vec<(String, String)> {
+    with_processor_data(&config(), items, Vec::new(), |data| {
+        cpp_inheritance::run(data).unwrap();
+
+        let mut result = data
+            .db
+            .cpp_items()
+            .filter_map(|item| item.item.as_function_ref())
+            .filter_map(|function| {
+                let member = function.member.as_ref()?;
+                let inherited_from = member.inherited_from.as_ref()?;
+                Some((
+                    function.path.to_cpp_pseudo_code(),
+                    inherited_from.to_cpp_pseudo_code(),
+                ))
+            })
+            .collect::<Vec<_>>();
+        result.sort();
+        result
+    })
+}
+
+#[test]
+fn forwards_secondary_base_methods() {
+    let methods = run_inheritance(vec![
+        class("A"),
+        class("B"),
+        class("C"),
+        method("A::a"),
+        method("B::b"),
+        base("C", "A", 0),
+        base("C", "B", 1),
+    ]);
+    assert_eq!(methods, pairs(&[("C::b", "B")]));
+}
+
+#[test]
+fn derived_class_name_hides_base_method() {
+    let methods = run_inheritance(vec![
+        class("A"),
+        class("B"),
+        class("C"),
+        method("A::a"),
+        method("A::shared"),
+        method("B::b"),
+        method("B::c"),
+        method("B::shared"),
+        method("C::c"),
+        base("C", "A", 0),
+        base("C", "B", 1),
+    ]);
+    // `B::c` is hidden by `C::c`, `B::shared` is hidden by `A::shared`
+    // available through the primary base
+    assert_eq!(methods, pairs(&[("C::b", "B")]));
+}
+
+#[test]
+fn earlier_base_takes_precedence() {
+    let methods = run_inheritance(vec![
+        class("A"),
+        class("B1"),
+        class("B2"),
+        class("C"),
+        method("B1::f"),
+        method("B2::f"),
+        method("B2::g"),
+        base("C", "A", 0),
+        base("C", "B2", 2),
+        base("C", "B1", 1),
+    ]);
+    assert_eq!(methods, pairs(&[("C::f", "B1"), ("C::g", "B2")]));
+}
+
+#[test]
+fn diamond_base_is_visited_once() {
+    let methods = run_inheritance(vec![
+        class("Base"),
+        class("Left"),
+        class("Right"),
+        class("Right2"),
+        class("Diamond"),
+        method("Base::base_method"),
+        method("Right::right_method"),
+        method("Right2::right2_method"),
+        base("Left", "Base", 0),
+        base("Right", "Base", 0),
+        base("Right2", "Base", 0),
+        base("Diamond", "Left", 0),
+        base("Diamond", "Right", 1),
+        base("Diamond", "Right2", 2),
+    ]);
+    // `Base` is reachable through `Deref` implementations of `Left`,
+    // so its methods are not forwarded to `Diamond`
+    assert_eq!(
+        methods,
+        pairs(&[
+            ("Diamond::right2_method", "Right2"),
+            ("Diamond::right_method", "Right"),
+        ])
+    );
+}
+
+#[test]
+fn secondary_base_of_secondary_base() {
+    let methods = run_inheritance(vec![
+        class("A"),
+        class("B"),
+        class("BBase"),
+        class("C"),
+        method("BBase::f"),
+        method("B::g"),
+        base("B", "BBase", 0),
+        base("C", "A", 0),
+        base("C", "B", 1),
+    ]);
+    // `BBase` is the primary base of `B`, but not of `C`,
+    // so `C` needs forwarding methods for both of them
+    assert_eq!(methods, pairs(&[("C::f", "BBase"), ("C::g", "B")]));
+}
+
+#[test]
+fn secondary_base_of_primary_base() {
+    let methods = run_inheritance(vec![
+        class("X"),
+        class("S"),
+        class("P"),
+        class("D"),
+        method("X::x"),
+        method("S::s"),
+        base("P", "X", 0),
+        base("P", "S", 1),
+        base("D", "P", 0),
+    ]);
+    // `D` reaches the forwarding methods of `P` through `Deref`,
+    // so `S` is only forwarded to `P`
+    assert_eq!(methods, pairs(&[("P::s", "S")]));
+}
+
+#[test]
+fn secondary_base_of_unknown_primary_base() {
+    let methods = run_inheritance(vec![
+        class("X"),
+        class("S"),
+        class("D"),
+        method("X::x"),
+        method("S::s"),
+        base("P", "X", 0),
+        base("P", "S", 1),
+        base("D", "P", 0),
+    ]);
+    // `P` is not available, so it doesn't have its own forwarding methods
+    assert_eq!(methods, pairs(&[("D::s", "S")]));
+}
+
+#[test]
+fn non_public_primary_base() {
+    let methods = run_inheritance(vec![
+        class("A"),
+        class("B"),
+        class("C"),
+        method("A::a"),
+        method("A::shared"),
+        method("B::b"),
+        method("B::shared"),
+        base_with_visibility("C", "A", 0, CppVisibility::Private),
+        base("C", "B", 1),
+    ]);
+    // methods of the private base are neither forwarded
+    // nor hide methods of public bases
+    assert_eq!(methods, pairs(&[("C::b", "B"), ("C::shared", "B")]));
+}
+
+#[test]
+fn non_public_secondary_base() {
+    let methods = run_inheritance(vec![
+        class("A"),
+        class("B"),
+        class("C"),
+        method("B::b"),
+        base("C", "A", 0),
+        base_with_visibility("C", "B", 1, CppVisibility::Protected),
+    ]);
+    assert!(methods.is_empty());
+}
@@ -30,6 +30,10 @@ pub fn empty_membership() -> CppFunctionMemberData {
         visibility: CppVisibility::Public,
         is_signal: false,
         is_slot: false,
+        ref_qualifier: CppRefQualifier::None,
+        is_defaulted: false,
+        is_explicit: false,
+        inherited_from: None,
     }
 }
 
@@ -43,6 +47,9 @@ pub fn empty_regular_method() -> CppFunction {
         operator: None,
         declaration_code: None,
         cast: None,
+        is_noexcept: false,
+        is_deleted: false,
+        is_friend: false,
     }
 }
 
@@ -605,6 +612,10 @@ fn short_text1() {
             visibility: CppVisibility::Protected,
             is_signal: false,
             is_slot: false,
+            ref_qualifier: CppRefQualifier::None,
+            is_defaulted: false,
+            is_explicit: false,
+            inherited_from: None,
         }),
         operator: None,
         return_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
@@ -622,6 +633,9 @@ fn short_text1() {
         ],
         allows_variadic_arguments: false,
         cast: None,
+        is_noexcept: false,
+        is_deleted: false,
+        is_friend: false,
         declaration_code: None,
     };
     assert_eq!(
@@ -629,3 +643,188 @@ fn short_text1() {
         "protected int Class1::method1(int arg1, double arg2 = …) const"
     );
 }
+
+fn field_accessor_to_ffi(
+    field: &CppClassField,
+    accessor_type: CppFieldAccessorType,
+) -> CppFfiFunction {
+    crate::cpp_ffi_generator::to_ffi_method(
+        NewFfiFunctionKind::FieldAccessor {
+            field: field.clone(),
+            accessor_type,
+        },
+        &[],
+        &mut crate::cpp_ffi_generator::FfiNameProvider::testing(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn c_signature_bit_field_accessors() {
+    let field = CppClassField {
+        path: CppPath::from_good_str("Flags::level"),
+        field_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
+        visibility: CppVisibility::Public,
+        is_static: false,
+        bit_field_width: Some(12),
+    };
+    let int16 = CppType::SpecificNumeric(CppSpecificNumericType {
+        path: CppPath::from_good_str("int16_t"),
+        bits: 16,
+        kind: CppSpecificNumericTypeKind::Integer { is_signed: true },
+    });
+
+    let getter = field_accessor_to_ffi(&field, CppFieldAccessorType::CopyGetter);
+    assert_eq!(getter.arguments.len(), 1);
+    assert_eq!(getter.arguments[0].meaning, CppFfiArgumentMeaning::This);
+    assert_eq!(getter.return_type.original_type(), &field.field_type);
+    assert_eq!(getter.return_type.ffi_type(), &int16);
+    assert_eq!(
+        getter.return_type.ffi_type().to_cpp_code(None).unwrap(),
+        "int16_t"
+    );
+
+    let setter = field_accessor_to_ffi(&field, CppFieldAccessorType::Setter);
+    assert_eq!(setter.arguments.len(), 2);
+    assert_eq!(
+        setter.arguments[1].meaning,
+        CppFfiArgumentMeaning::Argument(0)
+    );
+    assert_eq!(setter.arguments[1].argument_type.ffi_type(), &int16);
+    assert!(setter.return_type.ffi_type().is_void());
+
+    assert!(crate::cpp_ffi_generator::to_ffi_method(
+        NewFfiFunctionKind::FieldAccessor {
+            field,
+            accessor_type: CppFieldAccessorType::MutRefGetter,
+        },
+        &[],
+        &mut crate::cpp_ffi_generator::FfiNameProvider::testing(),
+    )
+    .is_err());
+}
+
+#[test]
+fn c_signature_direct_call() {
+    let point = CppType::Class(CppPath::from_good_str("Point"));
+    let mut function = empty_regular_method();
+    function.path = CppPath::from_good_str("point_scale");
+    function.return_type = point.clone();
+    function.arguments.push(CppFunctionArgument {
+        name: "point".to_string(),
+        argument_type: point.clone(),
+        has_default_value: false,
+    });
+    function.arguments.push(CppFunctionArgument {
+        name: "factor".to_string(),
+        argument_type: CppType::new_pointer(
+            true,
+            CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
+        ),
+        has_default_value: false,
+    });
+
+    let r = crate::cpp_ffi_generator::to_direct_ffi_function(&function).unwrap();
+    assert!(r.is_direct_call);
+    assert_eq!(r.path, function.path);
+    assert_eq!(
+        r.allocation_place,
+        ReturnValueAllocationPlace::NotApplicable
+    );
+    assert_eq!(r.arguments.len(), 2);
+    assert_eq!(r.arguments[0].argument_type.ffi_type(), &point);
+    assert_eq!(
+        r.arguments[0].argument_type.conversion(),
+        &CppToFfiTypeConversion::NoChange
+    );
+    assert_eq!(r.arguments[1].meaning, CppFfiArgumentMeaning::Argument(1));
+    assert_eq!(r.return_type.ffi_type(), &point);
+
+    let mut reference_arg = function.clone();
+    reference_arg.arguments[1].argument_type = CppType::new_reference(true, point);
+    assert!(crate::cpp_ffi_generator::to_direct_ffi_function(&reference_arg).is_err());
+
+    let mut method = function;
+    method.path = CppPath::from_good_str("Point::scale");
+    method.member = Some(empty_membership());
+    assert!(crate::cpp_ffi_generator::to_direct_ffi_function(&method).is_err());
+}
+
+#[test]
+fn converting_constructors() {
+    let class_type = CppType::Class(CppPath::from_good_str("QString"));
+    let constructor = |argument_type: CppType, is_explicit: bool| CppFunction {
+        path: CppPath::from_good_str("QString::QString"),
+        member: Some(CppFunctionMemberData {
+            kind: CppFunctionKind::Constructor,
+            is_explicit,
+            ..empty_membership()
+        }),
+        arguments: vec![CppFunctionArgument {
+            name: "arg1".to_string(),
+            argument_type,
+            has_default_value: false,
+        }],
+        ..empty_regular_method()
+    };
+    let c_string = CppType::new_pointer(true, CppType::BuiltInNumeric(CppBuiltInNumericType::Char));
+
+    assert!(constructor(c_string.clone(), false).is_converting_constructor());
+    assert!(!constructor(c_string.clone(), true).is_converting_constructor());
+    assert!(
+        !constructor(CppType::new_reference(true, class_type.clone()), false)
+            .is_converting_constructor()
+    );
+    assert!(!constructor(
+        CppType::PointerLike {
+            kind: CppPointerLikeTypeKind::RValueReference,
+            is_const: false,
+            target: Box::new(class_type),
+        },
+        false
+    )
+    .is_converting_constructor());
+
+    let mut two_args = constructor(c_string.clone(), false);
+    two_args.arguments.push(CppFunctionArgument {
+        name: "size".to_string(),
+        argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
+        has_default_value: true,
+    });
+    assert!(!two_args.is_converting_constructor());
+
+    let mut regular = constructor(c_string, false);
+    regular.path = CppPath::from_good_str("QString::append");
+    regular.member.as_mut().unwrap().kind = CppFunctionKind::Regular;
+    assert!(!regular.is_converting_constructor());
+}
+
+#[test]
+fn member_pointer_type() {
+    let mut method = CppFunction {
+        path: CppPath::from_good_str("QWidget::setVisible"),
+        member: Some(CppFunctionMemberData {
+            is_const: true,
+            ..empty_membership()
+        }),
+        arguments: vec![CppFunctionArgument {
+            name: "visible".to_string(),
+            argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Bool),
+            has_default_value: false,
+        }],
+        ..empty_regular_method()
+    };
+    assert_eq!(
+        method
+            .member_pointer_type()
+            .unwrap()
+            .to_cpp_code(None)
+            .unwrap(),
+        "void (QWidget::*)(bool) const"
+    );
+
+    method.member.as_mut().unwrap().is_static = true;
+    assert!(method.member_pointer_type().is_err());
+    method.member = None;
+    assert!(method.member_pointer_type().is_err());
+}
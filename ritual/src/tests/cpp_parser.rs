@@ -1,13 +1,20 @@
-use crate::config::{Config, CrateProperties};
+use crate::config::{Config, CrateProperties, TemplateInstantiation};
+use crate::cpp_annotations::CppAnnotations;
 use crate::cpp_data::*;
 use crate::cpp_function::*;
 use crate::cpp_operator::CppOperator;
+use crate::cpp_parser::{
+    file_content_hash, is_const_function_type, is_deleted_declaration, is_explicit_declaration,
+    CppParserFingerprint, StableHasher,
+};
 use crate::cpp_type::*;
 use crate::processor;
 use crate::workspace::Workspace;
 use ritual_common::cpp_build_config::CppBuildPaths;
 use ritual_common::file_utils::create_dir;
 use ritual_common::file_utils::create_file;
+use std::fs;
+use std::hash::Hasher;
 use std::io::Write;
 
 struct ParserCppData {
@@ -17,9 +24,15 @@ struct ParserCppData {
     methods: Vec<CppFunction>,
     enum_values: Vec<CppEnumValue>,
     namespaces: Vec<CppPath>,
+    type_aliases: Vec<CppTypeAlias>,
+    annotations: Vec<(CppPath, CppAnnotations)>,
 }
 
 fn run_parser(code: &'static str) -> ParserCppData {
+    run_parser_with_config(code, |_| {})
+}
+
+fn run_parser_with_config(code: &'static str, f: impl FnOnce(&mut Config)) -> ParserCppData {
     let dir = tempdir::TempDir::new("test_cpp_parser_run").unwrap();
 
     let mut workspace = Workspace::new(dir.path().into()).unwrap();
@@ -39,6 +52,7 @@ fn run_parser(code: &'static str) -> ParserCppData {
     let mut config = Config::new(CrateProperties::new("A", "0.0.0"));
     config.add_include_directive(include_name);
     config.set_cpp_build_paths(paths);
+    f(&mut config);
 
     processor::process(&mut workspace, &config, &["cpp_parser".into()], None).unwrap();
 
@@ -77,6 +91,18 @@ fn run_parser(code: &'static str) -> ParserCppData {
             .filter_map(|item| item.item.as_namespace_ref())
             .map(|ns| ns.path.clone())
             .collect(),
+        type_aliases: database
+            .cpp_items()
+            .filter_map(|item| item.item.as_type_alias_ref())
+            .cloned()
+            .collect(),
+        annotations: database
+            .cpp_items()
+            .filter_map(|item| {
+                let annotations = database.find_annotations_for(&item.id).unwrap()?;
+                Some((item.item.path()?.clone(), annotations.clone()))
+            })
+            .collect(),
     }
 }
 
@@ -99,6 +125,9 @@ fn simple_func() {
             }],
             allows_variadic_arguments: false,
             cast: None,
+            is_noexcept: false,
+            is_deleted: false,
+            is_friend: false,
             declaration_code: Some("int func1 ( int x )".to_string()),
         }
     );
@@ -129,6 +158,9 @@ fn simple_func_with_default_value() {
             }],
             allows_variadic_arguments: false,
             cast: None,
+            is_noexcept: false,
+            is_deleted: false,
+            is_friend: false,
             declaration_code: Some("bool func1 ( int x = 42 )".to_string()),
         }
     );
@@ -162,6 +194,7 @@ fn functions_with_class_arg() {
     );
     assert_eq!(data.fields[0].visibility, CppVisibility::Public);
     assert_eq!(data.fields[0].is_static, false);
+    assert_eq!(data.fields[0].bit_field_width, None);
 
     assert_eq!(data.fields[1].path, CppPath::from_good_str("Magic::b"));
     assert_eq!(
@@ -194,6 +227,9 @@ fn functions_with_class_arg() {
             }],
             allows_variadic_arguments: false,
             cast: None,
+            is_noexcept: false,
+            is_deleted: false,
+            is_friend: false,
             declaration_code: Some("bool func1 ( Magic x )".to_string()),
         }
     );
@@ -214,6 +250,9 @@ fn functions_with_class_arg() {
             }],
             allows_variadic_arguments: false,
             cast: None,
+            is_noexcept: false,
+            is_deleted: false,
+            is_friend: false,
             declaration_code: Some("bool func1 ( Magic * x )".to_string()),
         }
     );
@@ -234,6 +273,9 @@ fn functions_with_class_arg() {
             }],
             allows_variadic_arguments: false,
             cast: None,
+            is_noexcept: false,
+            is_deleted: false,
+            is_friend: false,
             declaration_code: Some("bool func2 ( const Magic & )".to_string()),
         }
     );
@@ -287,6 +329,9 @@ fn variadic_func() {
             }],
             allows_variadic_arguments: true,
             cast: None,
+            is_noexcept: false,
+            is_deleted: false,
+            is_friend: false,
             declaration_code: Some("int my_printf ( const char * format , ... )".to_string()),
         }
     );
@@ -334,6 +379,9 @@ fn free_template_func() {
             }],
             allows_variadic_arguments: false,
             cast: None,
+            is_noexcept: false,
+            is_deleted: false,
+            is_friend: false,
             declaration_code: Some("template < typename T > T abs ( T value )".to_string()),
         }
     );
@@ -375,6 +423,9 @@ fn free_func_operator_sub() {
                 ],
                 allows_variadic_arguments: false,
                 cast: None,
+                is_noexcept: false,
+                is_deleted: false,
+                is_friend: false,
                 declaration_code: Some("C1 operator - ( C1 a , C1 b )".to_string()),
             }
         );
@@ -414,6 +465,10 @@ fn simple_class_method() {
                 visibility: CppVisibility::Public,
                 is_signal: false,
                 is_slot: false,
+                ref_qualifier: CppRefQualifier::None,
+                is_defaulted: false,
+                is_explicit: false,
+                inherited_from: None,
             }),
             operator: None,
             return_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
@@ -424,6 +479,9 @@ fn simple_class_method() {
             }],
             allows_variadic_arguments: false,
             cast: None,
+            is_noexcept: false,
+            is_deleted: false,
+            is_friend: false,
             declaration_code: Some("int func1 ( int x )".to_string()),
         }
     );
@@ -604,6 +662,10 @@ fn template_class_method() {
                 visibility: CppVisibility::Public,
                 is_signal: false,
                 is_slot: false,
+                ref_qualifier: CppRefQualifier::None,
+                is_defaulted: false,
+                is_explicit: false,
+                inherited_from: None,
             }),
             operator: None,
             return_type: CppType::TemplateParameter(CppTemplateParameter {
@@ -618,6 +680,9 @@ fn template_class_method() {
             }],
             allows_variadic_arguments: false,
             cast: None,
+            is_noexcept: false,
+            is_deleted: false,
+            is_friend: false,
             declaration_code: Some("T get ( int index )".to_string()),
         }
     );
@@ -1039,6 +1104,7 @@ fn fixed_size_integers() {
         QVector<GLuint64> f2();
         ",
     );
+    assert!(data.type_aliases.is_empty());
     assert_eq!(data.methods.len(), 2);
     assert_eq!(&data.methods[0].path, &CppPath::from_good_str("f1"));
     let type1 = CppType::SpecificNumeric(CppSpecificNumericType {
@@ -1219,3 +1285,768 @@ fn elaborated_enum_type() {
         }
     );
 }
+
+#[test]
+fn type_aliases() {
+    let data = run_parser(
+        "
+        template<typename T>
+        class QList {};
+        class QString {};
+        typedef double qreal;
+        typedef QList<QString> QStringList;
+        namespace ns {
+            using Handle = void*;
+        }
+        class A {
+        public:
+            typedef int Index;
+        private:
+            typedef int Private;
+        };
+        ",
+    );
+    assert_eq!(data.type_aliases.len(), 4);
+    assert_eq!(
+        data.type_aliases[0],
+        CppTypeAlias {
+            path: CppPath::from_good_str("qreal"),
+            target_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Double),
+        }
+    );
+    assert_eq!(
+        data.type_aliases[1],
+        CppTypeAlias {
+            path: CppPath::from_good_str("QStringList"),
+            target_type: CppType::Class(CppPath::from_item(CppPathItem {
+                name: "QList".to_string(),
+                template_arguments: Some(vec![CppType::Class(CppPath::from_good_str("QString"))]),
+            })),
+        }
+    );
+    assert_eq!(
+        data.type_aliases[2],
+        CppTypeAlias {
+            path: CppPath::from_good_str("ns::Handle"),
+            target_type: CppType::new_pointer(false, CppType::Void),
+        }
+    );
+    assert_eq!(
+        data.type_aliases[3],
+        CppTypeAlias {
+            path: CppPath::from_good_str("A::Index"),
+            target_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
+        }
+    );
+}
+
+#[test]
+fn unions() {
+    let data = run_parser(
+        "
+        union U1 {
+            int a;
+            float b;
+        };
+        struct Event {
+            int type;
+            union {
+                int x;
+                struct {
+                    short y;
+                    short z;
+                };
+            };
+            struct { int w; } named;
+        };
+        U1 func1(U1 u);
+        ",
+    );
+    assert_eq!(data.types.len(), 2);
+    assert_eq!(data.types[0].path, CppPath::from_good_str("U1"));
+    assert_eq!(data.types[0].kind, CppTypeDeclarationKind::Union);
+    assert!(data.types[0].kind.is_class());
+    assert_eq!(data.types[1].path, CppPath::from_good_str("Event"));
+    assert_eq!(data.types[1].kind, CppTypeDeclarationKind::Class);
+
+    let field_paths = data
+        .fields
+        .iter()
+        .map(|f| f.path.to_cpp_pseudo_code())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        field_paths,
+        vec![
+            "U1::a",
+            "U1::b",
+            "Event::type",
+            "Event::x",
+            "Event::y",
+            "Event::z"
+        ]
+    );
+
+    assert_eq!(data.methods.len(), 1);
+    assert_eq!(
+        data.methods[0].return_type,
+        CppType::Class(CppPath::from_good_str("U1"))
+    );
+}
+
+#[test]
+fn c_struct_with_anonymous_union() {
+    let data = run_parser_with_config(
+        "
+        struct Point {
+            int x;
+            int y;
+        };
+        struct Event {
+            int type;
+            union {
+                int key;
+                struct Point position;
+            };
+        };
+        ",
+        |config| config.set_is_c_library(true),
+    );
+    assert_eq!(data.types.len(), 2);
+    assert_eq!(data.types[0].path, CppPath::from_good_str("Point"));
+    assert!(
+        !data.types[0]
+            .special_members
+            .as_ref()
+            .unwrap()
+            .has_unknown_members
+    );
+    // members of the anonymous union are flattened, so the parsed fields
+    // don't describe the layout of the struct
+    assert_eq!(data.types[1].path, CppPath::from_good_str("Event"));
+    assert!(
+        data.types[1]
+            .special_members
+            .as_ref()
+            .unwrap()
+            .has_unknown_members
+    );
+    let field_paths = data
+        .fields
+        .iter()
+        .map(|f| f.path.to_cpp_pseudo_code())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        field_paths,
+        vec![
+            "Point::x",
+            "Point::y",
+            "Event::type",
+            "Event::key",
+            "Event::position"
+        ]
+    );
+}
+
+#[test]
+fn arrays() {
+    let data = run_parser(
+        "
+        struct Color {
+            unsigned char rgba[4];
+            float matrix[4][4];
+        };
+        void set_matrix(float m[4][4]);
+        void fill(const int (&values)[16]);
+        ",
+    );
+    assert_eq!(data.fields.len(), 2);
+    assert_eq!(
+        data.fields[0].field_type,
+        CppType::Array {
+            element_type: Box::new(CppType::BuiltInNumeric(CppBuiltInNumericType::UChar)),
+            size: 4,
+        }
+    );
+    let float_row = CppType::Array {
+        element_type: Box::new(CppType::BuiltInNumeric(CppBuiltInNumericType::Float)),
+        size: 4,
+    };
+    let float_matrix = CppType::Array {
+        element_type: Box::new(float_row),
+        size: 4,
+    };
+    assert_eq!(data.fields[1].field_type, float_matrix);
+
+    assert_eq!(data.methods.len(), 2);
+    assert_eq!(data.methods[0].path, CppPath::from_good_str("set_matrix"));
+    assert_eq!(data.methods[0].arguments[0].argument_type, float_matrix);
+    assert_eq!(data.methods[1].path, CppPath::from_good_str("fill"));
+    assert_eq!(
+        data.methods[1].arguments[0].argument_type,
+        CppType::new_reference(
+            true,
+            CppType::Array {
+                element_type: Box::new(CppType::BuiltInNumeric(CppBuiltInNumericType::Int)),
+                size: 16,
+            }
+        )
+    );
+}
+
+#[test]
+fn bit_fields() {
+    let data = run_parser(
+        "
+        struct Flags {
+            unsigned int visible : 1;
+            int level : 12;
+            bool enabled : 1;
+            int value;
+        };
+        ",
+    );
+    assert_eq!(data.fields.len(), 4);
+    assert_eq!(
+        data.fields[0].path,
+        CppPath::from_good_str("Flags::visible")
+    );
+    assert_eq!(
+        data.fields[0].field_type,
+        CppType::BuiltInNumeric(CppBuiltInNumericType::UInt)
+    );
+    assert_eq!(data.fields[0].bit_field_width, Some(1));
+    assert_eq!(data.fields[1].path, CppPath::from_good_str("Flags::level"));
+    assert_eq!(data.fields[1].bit_field_width, Some(12));
+    assert_eq!(
+        data.fields[2].path,
+        CppPath::from_good_str("Flags::enabled")
+    );
+    assert_eq!(
+        data.fields[2].field_type,
+        CppType::BuiltInNumeric(CppBuiltInNumericType::Bool)
+    );
+    assert_eq!(data.fields[2].bit_field_width, Some(1));
+    assert_eq!(data.fields[3].path, CppPath::from_good_str("Flags::value"));
+    assert_eq!(data.fields[3].bit_field_width, None);
+}
+
+#[test]
+fn template_partial_specialization() {
+    let data = run_parser(
+        "
+        template<typename T>
+        class List {
+        public:
+            T at(int i);
+        };
+        template<typename T>
+        class List<T*> {
+        public:
+            T* first();
+        };
+        ",
+    );
+    let param = CppType::TemplateParameter(CppTemplateParameter {
+        nested_level: 0,
+        index: 0,
+        name: "T".into(),
+    });
+    let primary_path = CppPath::from_items(vec![CppPathItem {
+        name: "List".into(),
+        template_arguments: Some(vec![param.clone()]),
+    }]);
+    let specialization_path = CppPath::from_items(vec![CppPathItem {
+        name: "List".into(),
+        template_arguments: Some(vec![CppType::new_pointer(false, param.clone())]),
+    }]);
+
+    assert_eq!(data.types.len(), 2);
+    assert_eq!(data.types[0].path, primary_path);
+    assert_eq!(data.types[1].path, specialization_path);
+    assert!(data.types[1].kind.is_class());
+
+    assert_eq!(data.methods.len(), 2);
+    assert_eq!(
+        data.methods[0].path,
+        primary_path.join(CppPathItem::from_good_str("at"))
+    );
+    assert_eq!(data.methods[0].return_type, param);
+    assert_eq!(
+        data.methods[1].path,
+        specialization_path.join(CppPathItem::from_good_str("first"))
+    );
+    assert_eq!(
+        data.methods[1].return_type,
+        CppType::new_pointer(false, param)
+    );
+}
+
+#[test]
+fn function_specifiers() {
+    let data = run_parser(
+        "
+        class MyClass {
+        public:
+            MyClass() = default;
+            MyClass(const MyClass& other) = delete;
+            int value() const & noexcept;
+            int take() &&;
+        };
+        void free_func() throw();
+        ",
+    );
+    assert_eq!(data.methods.len(), 5);
+
+    assert_eq!(
+        data.methods[0].path.to_cpp_pseudo_code(),
+        "MyClass::MyClass"
+    );
+    assert!(data.methods[0].member.as_ref().unwrap().is_defaulted);
+    assert!(!data.methods[0].is_deleted);
+
+    assert!(data.methods[1].is_copy_constructor());
+    assert!(data.methods[1].is_deleted);
+    assert!(!data.methods[1].member.as_ref().unwrap().is_defaulted);
+
+    assert_eq!(data.methods[2].path.to_cpp_pseudo_code(), "MyClass::value");
+    assert!(data.methods[2].is_noexcept);
+    let info = data.methods[2].member.as_ref().unwrap();
+    assert!(info.is_const);
+    assert_eq!(info.ref_qualifier, CppRefQualifier::LValue);
+
+    assert_eq!(data.methods[3].path.to_cpp_pseudo_code(), "MyClass::take");
+    assert!(!data.methods[3].is_noexcept);
+    assert_eq!(
+        data.methods[3].member.as_ref().unwrap().ref_qualifier,
+        CppRefQualifier::RValue
+    );
+
+    assert_eq!(data.methods[4].path.to_cpp_pseudo_code(), "free_func");
+    assert!(data.methods[4].is_noexcept);
+    assert!(!data.methods[4].is_deleted);
+}
+
+#[test]
+fn friend_functions() {
+    let data = run_parser(
+        "
+        namespace ns {
+        class Point {
+        public:
+            friend bool operator==(const Point& a, const Point& b) { return true; }
+            friend Point operator+(const Point& a, const Point& b);
+            friend void swap(Point& a, Point& b);
+            friend class Other;
+        };
+        template<typename T>
+        class Wrapper {
+            friend bool operator==(const Wrapper& a, const Wrapper& b) { return true; }
+        };
+        }
+        ",
+    );
+    assert_eq!(data.methods.len(), 3);
+    for method in &data.methods {
+        assert!(method.member.is_none());
+        assert!(method.is_friend);
+    }
+
+    assert_eq!(data.methods[0].path.to_cpp_pseudo_code(), "ns::operator==");
+    assert_eq!(data.methods[0].operator, Some(CppOperator::EqualTo));
+    assert_eq!(data.methods[0].arguments.len(), 2);
+
+    assert_eq!(data.methods[1].path.to_cpp_pseudo_code(), "ns::operator+");
+    assert_eq!(data.methods[1].operator, Some(CppOperator::Addition));
+    assert_eq!(
+        data.methods[1].return_type,
+        CppType::Class(CppPath::from_good_str("ns::Point"))
+    );
+
+    assert_eq!(data.methods[2].path.to_cpp_pseudo_code(), "ns::swap");
+    assert_eq!(data.methods[2].operator, None);
+}
+
+#[test]
+fn member_pointers() {
+    let data = run_parser(
+        "
+        class Object {
+        public:
+            int value;
+            int get(bool b) const;
+        };
+        void connect(void (Object::*func)(), int Object::*field);
+        int call(int (Object::*func)(bool) const);
+        ",
+    );
+    assert_eq!(data.methods.len(), 3);
+    assert_eq!(data.methods[1].path.to_cpp_pseudo_code(), "connect");
+    assert_eq!(
+        data.methods[1].arguments[0].argument_type,
+        CppType::MemberPointer(CppMemberPointerType {
+            class_path: CppPath::from_good_str("Object"),
+            target: CppMemberPointerTarget::Method {
+                function_type: CppFunctionPointerType {
+                    return_type: Box::new(CppType::Void),
+                    arguments: vec![],
+                    allows_variadic_arguments: false,
+                },
+                is_const: false,
+            },
+        })
+    );
+    assert_eq!(
+        data.methods[1].arguments[1].argument_type,
+        CppType::MemberPointer(CppMemberPointerType {
+            class_path: CppPath::from_good_str("Object"),
+            target: CppMemberPointerTarget::Field(Box::new(CppType::BuiltInNumeric(
+                CppBuiltInNumericType::Int
+            ))),
+        })
+    );
+    assert_eq!(
+        data.methods[2].arguments[0]
+            .argument_type
+            .to_cpp_code(None)
+            .unwrap(),
+        "int (Object::*)(bool) const"
+    );
+}
+
+#[test]
+fn annotations() {
+    let data = run_parser(
+        "
+        #define RITUAL(x) __attribute__((annotate(\"ritual:\" x)))
+        class RITUAL(\"movable\") RITUAL(\"rename=Size2D\") Size {
+        public:
+            int width RITUAL(\"rename=w\");
+            int hidden RITUAL(\"skip\");
+        };
+        class Object {};
+        RITUAL(\"safe\") int size_area(Size size);
+        RITUAL(\"transfer_ownership\") Object* create_object();
+        void destroy_object(Object* object RITUAL(\"transfer_ownership\"));
+        RITUAL(\"skip\") void internal_function();
+        ",
+    );
+    assert_eq!(data.types.len(), 2);
+    assert_eq!(data.fields.len(), 1);
+    assert_eq!(data.methods.len(), 3);
+    assert!(data
+        .methods
+        .iter()
+        .all(|m| m.path.to_cpp_pseudo_code() != "internal_function"));
+
+    let annotations = |path: &str| {
+        data.annotations
+            .iter()
+            .find(|(item_path, _)| item_path == &CppPath::from_good_str(path))
+            .map(|(_, annotations)| annotations.clone())
+            .unwrap_or_default()
+    };
+    assert_eq!(
+        annotations("Size"),
+        CppAnnotations {
+            rename: Some("Size2D".to_string()),
+            is_movable: Some(true),
+            ..CppAnnotations::default()
+        }
+    );
+    assert_eq!(annotations("Size::width").rename, Some("w".to_string()));
+    assert!(annotations("size_area").is_safe);
+    assert!(annotations("create_object").returns_ownership);
+    assert_eq!(
+        annotations("destroy_object").ownership_transferring_arguments,
+        vec![0]
+    );
+    assert!(annotations("Object").is_empty());
+}
+
+#[test]
+fn skip_annotation_overrides_parser_path_hook() {
+    let data = run_parser_with_config(
+        "
+        #define RITUAL(x) __attribute__((annotate(\"ritual:\" x)))
+        RITUAL(\"skip\") void skipped_function();
+        void normal_function();
+        void blocked_function();
+        ",
+        |config| {
+            config.set_cpp_parser_path_hook(|path| {
+                Ok(path.to_cpp_pseudo_code() != "blocked_function")
+            })
+        },
+    );
+    let names = data
+        .methods
+        .iter()
+        .map(|m| m.path.to_cpp_pseudo_code())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["normal_function"]);
+}
+
+#[test]
+fn special_members() {
+    let data = run_parser(
+        "
+        class Simple {
+        public:
+            int x;
+        };
+        class NonCopyable {
+        public:
+            NonCopyable(int x);
+            NonCopyable(const NonCopyable& other) = delete;
+        private:
+            ~NonCopyable();
+        };
+        class Abstract {
+        public:
+            Abstract() = default;
+            virtual void f() = 0;
+            Abstract& operator=(Abstract&& other);
+        private:
+            const int c;
+            int& r;
+        };
+        ",
+    );
+    assert_eq!(data.types.len(), 3);
+    let special_members = |index: usize| data.types[index].special_members.clone().unwrap();
+
+    assert_eq!(special_members(0), CppClassSpecialMembers::default());
+
+    let non_copyable = special_members(1);
+    assert!(non_copyable.has_constructors);
+    assert!(non_copyable.default_constructor.is_none());
+    assert_eq!(
+        non_copyable.copy_constructor,
+        Some(CppSpecialMemberDeclaration {
+            visibility: CppVisibility::Public,
+            is_deleted: true,
+            is_defaulted: false,
+            has_usual_signature: true,
+        })
+    );
+    assert_eq!(
+        non_copyable.destructor.unwrap().visibility,
+        CppVisibility::Private
+    );
+    assert!(!non_copyable.has_move_operations);
+
+    let abstract_class = special_members(2);
+    assert!(abstract_class.default_constructor.unwrap().is_defaulted);
+    assert!(abstract_class.has_pure_virtual_methods);
+    assert!(abstract_class.has_move_operations);
+    assert!(abstract_class.copy_assignment.is_none());
+    assert!(abstract_class.has_const_fields);
+    assert!(abstract_class.has_reference_fields);
+}
+
+#[test]
+fn requested_template_instantiations() {
+    let data = run_parser_with_config(
+        "
+        template<typename T>
+        class Vector {
+        public:
+            T get(int index) const;
+        };
+        class Item {};
+        typedef Item ItemAlias;
+        ",
+        |config| {
+            config.add_template_instantiation(TemplateInstantiation::new(
+                "Vector",
+                vec!["ItemAlias".to_string()],
+            ));
+            config.add_template_instantiation(TemplateInstantiation::new(
+                "Vector",
+                vec!["Vector<int>".to_string()],
+            ));
+        },
+    );
+    let type_paths = data
+        .types
+        .iter()
+        .map(|t| t.path.to_cpp_pseudo_code())
+        .collect::<Vec<_>>();
+    assert!(type_paths.contains(&"Vector<Item>".to_string()));
+    assert!(type_paths.contains(&"Vector<Vector<int>>".to_string()));
+    assert!(type_paths.contains(&"Vector<int>".to_string()));
+    assert!(data
+        .namespaces
+        .iter()
+        .all(|ns| ns.to_cpp_pseudo_code() != "ritual_template_instantiations"));
+}
+
+#[test]
+fn invalid_template_instantiation_is_skipped() {
+    let data = run_parser_with_config(
+        "
+        template<typename T>
+        class Vector {
+        public:
+            T get(int index) const;
+        };
+        ",
+        |config| {
+            config.add_template_instantiation(TemplateInstantiation::new(
+                "Vector",
+                vec!["int".to_string()],
+            ));
+            config.add_template_instantiation(TemplateInstantiation::new(
+                "Vector",
+                vec!["Missing".to_string()],
+            ));
+        },
+    );
+    let type_paths = data
+        .types
+        .iter()
+        .map(|t| t.path.to_cpp_pseudo_code())
+        .collect::<Vec<_>>();
+    assert!(type_paths.contains(&"Vector<int>".to_string()));
+    assert!(!type_paths.iter().any(|path| path.contains("Missing")));
+}
+
+#[test]
+fn deleted_specifier_is_detected() {
+    assert!(is_deleted_declaration(
+        "MyClass ( const MyClass & ) = delete"
+    ));
+    assert!(is_deleted_declaration("MyClass(const MyClass&)=delete"));
+    assert!(!is_deleted_declaration("MyClass ( ) = default"));
+    assert!(!is_deleted_declaration("void func1 ( int x = 0 )"));
+    assert!(!is_deleted_declaration("void undelete ( )"));
+}
+
+#[test]
+fn fingerprint_detects_changed_files() {
+    let dir = tempdir::TempDir::new("ritual_test").unwrap();
+    let path = dir.path().join("a.h");
+    fs::write(&path, "class A {};").unwrap();
+    let fingerprint = CppParserFingerprint {
+        settings_hash: 0,
+        files: vec![(path.clone(), file_content_hash(&path).unwrap())],
+        output: Default::default(),
+    };
+    assert!(fingerprint.files_unchanged().unwrap());
+
+    fs::write(&path, "class A { int x; };").unwrap();
+    assert!(!fingerprint.files_unchanged().unwrap());
+
+    fs::remove_file(&path).unwrap();
+    assert!(!fingerprint.files_unchanged().unwrap());
+}
+
+#[test]
+fn stable_hasher_is_fnv1a() {
+    let mut hasher = StableHasher::new();
+    hasher.write(b"a");
+    assert_eq!(hasher.finish(), 0xaf63_dc4c_8601_ec8c);
+}
+
+#[test]
+fn explicit_specifier_is_detected() {
+    assert!(is_explicit_declaration("explicit QVariant ( int i )"));
+    assert!(is_explicit_declaration(
+        "Q_DECL_CONSTEXPR explicit QSize(int w)"
+    ));
+    assert!(!is_explicit_declaration(
+        "QString ( const char * explicit_text )"
+    ));
+    assert!(!is_explicit_declaration("QString ( const char * str )"));
+    assert!(is_explicit_declaration("explicit ( true ) QSize ( int w )"));
+    assert!(!is_explicit_declaration(
+        "explicit ( false ) QSize ( int w )"
+    ));
+    assert!(!is_explicit_declaration(
+        "explicit(false) operator bool ( ) const"
+    ));
+}
+
+#[test]
+fn const_function_type_is_detected() {
+    assert!(is_const_function_type("int (bool) const"));
+    assert!(is_const_function_type("void () const noexcept"));
+    assert!(!is_const_function_type("void (const int *)"));
+}
+
+fn argument_types(data: &ParserCppData, function_name: &str) -> Vec<CppType> {
+    let function = data
+        .methods
+        .iter()
+        .find(|m| m.path.to_cpp_pseudo_code() == function_name)
+        .unwrap();
+    function
+        .arguments
+        .iter()
+        .map(|arg| arg.argument_type.clone())
+        .collect()
+}
+
+#[test]
+fn extended_numeric_types() {
+    let data = run_parser(
+        "
+        long double func1(long double x, const long double* y);
+        __int128 func2(__int128 x, unsigned __int128 y);
+        ",
+    );
+    assert_eq!(data.methods.len(), 2);
+    let long_double = CppType::BuiltInNumeric(CppBuiltInNumericType::LongDouble);
+    assert_eq!(data.methods[0].return_type, long_double);
+    assert_eq!(
+        argument_types(&data, "func1"),
+        vec![long_double.clone(), CppType::new_pointer(true, long_double)]
+    );
+    assert_eq!(
+        data.methods[1].return_type,
+        CppType::BuiltInNumeric(CppBuiltInNumericType::Int128)
+    );
+    assert_eq!(
+        argument_types(&data, "func2"),
+        vec![
+            CppType::BuiltInNumeric(CppBuiltInNumericType::Int128),
+            CppType::BuiltInNumeric(CppBuiltInNumericType::UInt128),
+        ]
+    );
+}
+
+#[test]
+fn float16_type() {
+    let data = run_parser("_Float16 func1(_Float16 x);");
+    assert_eq!(data.methods.len(), 1);
+    let float16 = CppType::BuiltInNumeric(CppBuiltInNumericType::Float16);
+    assert_eq!(data.methods[0].return_type, float16);
+    assert_eq!(argument_types(&data, "func1"), vec![float16]);
+}
+
+#[test]
+fn char8_type() {
+    // `char8_t` is not exposed by libclang, so its spelling is used instead
+    let data = run_parser_with_config(
+        "
+        char8_t func1(char8_t x, const char8_t* y);
+        char16_t func2(char32_t x);
+        ",
+        |config| config.add_cpp_parser_argument("-std=c++2a"),
+    );
+    assert_eq!(data.methods.len(), 2);
+    let char8 = CppType::BuiltInNumeric(CppBuiltInNumericType::Char8);
+    assert_eq!(data.methods[0].return_type, char8);
+    assert_eq!(
+        argument_types(&data, "func1"),
+        vec![char8.clone(), CppType::new_pointer(true, char8)]
+    );
+    assert_eq!(
+        data.methods[1].return_type,
+        CppType::BuiltInNumeric(CppBuiltInNumericType::Char16)
+    );
+    assert_eq!(
+        argument_types(&data, "func2"),
+        vec![CppType::BuiltInNumeric(CppBuiltInNumericType::Char32)]
+    );
+}
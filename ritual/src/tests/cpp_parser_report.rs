@@ -0,0 +1,133 @@
+use crate::cpp_data::CppOriginLocation;
+use crate::cpp_parser_report::*;
+use ritual_common::errors::{err_msg, format_err, ResultExt};
+
+fn location(file: &str, line: u32) -> Option<CppOriginLocation> {
+    Some(CppOriginLocation {
+        include_file_path: file.to_string(),
+        line,
+        column: 5,
+    })
+}
+
+#[test]
+fn report_text_is_grouped() {
+    let mut report = CppParserReport::default();
+    for &(name, file, line, kind, reason) in &[
+        (
+            "A::f1",
+            "a.h",
+            10,
+            CppParserEntityKind::Function,
+            CppParserSkipReason::UnsupportedType,
+        ),
+        (
+            "B::f2",
+            "b.h",
+            20,
+            CppParserEntityKind::Function,
+            CppParserSkipReason::UnsupportedType,
+        ),
+        (
+            "A::x",
+            "a.h",
+            5,
+            CppParserEntityKind::ClassField,
+            CppParserSkipReason::UnsupportedType,
+        ),
+        (
+            "A::f3",
+            "a.h",
+            30,
+            CppParserEntityKind::Function,
+            CppParserSkipReason::TemplateParameter,
+        ),
+    ] {
+        report.add(CppParserSkippedEntity {
+            display_name: name.to_string(),
+            location: location(file, line),
+            kind,
+            reason,
+            message: "error".to_string(),
+        });
+    }
+    assert_eq!(
+        report.to_text(),
+        "Total skipped: 4\n\
+         \n\
+         unsupported type (3)\n  \
+         a.h (2)\n    \
+         10:5: function A::f1: error\n    \
+         5:5: field A::x: error\n  \
+         b.h (1)\n    \
+         20:5: function B::f2: error\n\
+         \n\
+         unsupported use of template parameters (1)\n  \
+         a.h (1)\n    \
+         30:5: function A::f3: error\n"
+    );
+}
+
+#[test]
+fn skip_reason_from_error() {
+    let reason = |error| CppParserSkipReason::from_error(&error);
+    assert_eq!(
+        reason(err_msg("Unsupported kind of type: Atomic")),
+        CppParserSkipReason::UnsupportedType
+    );
+    assert_eq!(
+        reason(err_msg("Volatile type")),
+        CppParserSkipReason::UnsupportedType
+    );
+    assert_eq!(
+        reason(err_msg("Non-type template parameter is not supported")),
+        CppParserSkipReason::TemplateParameter
+    );
+    assert_eq!(
+        reason(err_msg("Anonymous type")),
+        CppParserSkipReason::AnonymousEntity
+    );
+    assert_eq!(
+        reason(err_msg("No info about location.")),
+        CppParserSkipReason::MissingLocation
+    );
+    assert_eq!(
+        reason(format_err!("Type uses private class ({})", "A::B")),
+        CppParserSkipReason::InaccessibleType
+    );
+    assert_eq!(
+        reason(err_msg("something unexpected")),
+        CppParserSkipReason::Other
+    );
+}
+
+#[test]
+fn skip_reason_uses_root_cause() {
+    let result: Result<(), _> = Err(err_msg("Unsupported kind of type: Atomic"));
+    let error = result
+        .with_context(|_| err_msg("failed to parse field type"))
+        .with_context(|_| err_msg("Origin of class field is unknown"))
+        .unwrap_err();
+    assert_eq!(
+        CppParserSkipReason::from_error(&error.into()),
+        CppParserSkipReason::UnsupportedType
+    );
+}
+
+#[test]
+fn add_error_classifies_error() {
+    let mut report = CppParserReport::default();
+    report.add_error(
+        "A : B<T>".to_string(),
+        location("a.h", 3),
+        CppParserEntityKind::ClassBase,
+        &err_msg("Template argument of unexposed type is not parsed"),
+    );
+    let item = &report.skipped()[0];
+    assert_eq!(item.kind, CppParserEntityKind::ClassBase);
+    assert_eq!(item.reason, CppParserSkipReason::TemplateParameter);
+    assert_eq!(
+        item.message,
+        "Template argument of unexposed type is not parsed"
+    );
+}
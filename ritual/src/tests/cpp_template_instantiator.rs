@@ -0,0 +1,72 @@
+use crate::cpp_data::{CppPath, CppPathItem};
+use crate::cpp_template_instantiator::{
+    is_more_specialized, match_path, path_nesting_depth, type_nesting_depth, Substitution,
+};
+use crate::cpp_type::{CppBuiltInNumericType, CppTemplateParameter, CppType};
+
+#[test]
+fn partial_specialization_matching() {
+    fn list(arg: CppType) -> CppPath {
+        CppPath::from_items(vec![CppPathItem {
+            name: "QList".into(),
+            template_arguments: Some(vec![arg]),
+        }])
+    }
+    let param = CppTemplateParameter {
+        nested_level: 0,
+        index: 0,
+        name: "T".into(),
+    };
+    let primary = list(CppType::TemplateParameter(param.clone()));
+    let pointer_pattern = list(CppType::new_pointer(
+        false,
+        CppType::TemplateParameter(param),
+    ));
+    let object = CppType::Class(CppPath::from_good_str("QObject"));
+    let concrete = list(CppType::new_pointer(false, object.clone()));
+
+    let mut substitution = Substitution::default();
+    assert!(match_path(&pointer_pattern, &concrete, &mut substitution));
+    assert_eq!(
+        substitution.levels().unwrap(),
+        vec![(0, vec![object.clone()])]
+    );
+    assert!(match_path(
+        &primary,
+        &concrete,
+        &mut Substitution::default()
+    ));
+    assert!(!match_path(
+        &pointer_pattern,
+        &list(object),
+        &mut Substitution::default()
+    ));
+
+    assert!(is_more_specialized(&pointer_pattern, &primary));
+    assert!(!is_more_specialized(&primary, &pointer_pattern));
+}
+
+#[test]
+fn nesting_depth() {
+    fn list(arg: CppType) -> CppType {
+        CppType::Class(CppPath::from_items(vec![CppPathItem {
+            name: "QList".into(),
+            template_arguments: Some(vec![arg]),
+        }]))
+    }
+    let int = CppType::BuiltInNumeric(CppBuiltInNumericType::Int);
+    assert_eq!(type_nesting_depth(&int), 0);
+    assert_eq!(type_nesting_depth(&list(int.clone())), 1);
+    assert_eq!(
+        type_nesting_depth(&CppType::new_pointer(false, list(list(int.clone())))),
+        2
+    );
+    let nested = CppPath::from_items(vec![
+        CppPathItem {
+            name: "QHash".into(),
+            template_arguments: Some(vec![list(int.clone()), int]),
+        },
+        CppPathItem::from_good_str("iterator"),
+    ]);
+    assert_eq!(path_nesting_depth(&nested), 2);
+}
@@ -2,8 +2,8 @@ use crate::cpp_data::{CppPath, CppPathItem};
 use crate::cpp_ffi_data::CppToFfiTypeConversion;
 use crate::cpp_ffi_generator::ffi_type;
 use crate::cpp_type::{
-    CppBuiltInNumericType, CppFunctionPointerType, CppSpecificNumericType,
-    CppSpecificNumericTypeKind, CppTemplateParameter, CppType, CppTypeRole,
+    CppBuiltInNumericType, CppFunctionPointerType, CppMemberPointerTarget, CppMemberPointerType,
+    CppSpecificNumericType, CppSpecificNumericTypeKind, CppTemplateParameter, CppType, CppTypeRole,
 };
 
 fn assert_type_to_ffi_unchanged(t: &CppType) {
@@ -59,6 +59,68 @@ fn bool_ptr() {
     assert_type_to_ffi_unchanged(&type1);
 }
 
+#[test]
+fn extended_built_in_types() {
+    let char8 = CppType::BuiltInNumeric(CppBuiltInNumericType::Char8);
+    assert_eq!(char8.to_cpp_code(None).unwrap(), "char8_t");
+    assert_type_to_ffi_unchanged(&char8);
+
+    let int128 = CppType::BuiltInNumeric(CppBuiltInNumericType::Int128);
+    assert_eq!(int128.to_cpp_code(None).unwrap(), "__int128_t");
+    // `__int128` has no stable C ABI in Rust, so it's passed by pointer
+    let ffi_int128 = ffi_type(&int128, CppTypeRole::ReturnType).unwrap();
+    assert_eq!(
+        ffi_int128.conversion(),
+        &CppToFfiTypeConversion::ValueToPointer {
+            is_ffi_const: false
+        }
+    );
+    assert_type_to_ffi_unchanged(&CppType::new_pointer(false, int128));
+
+    let float16 = CppType::BuiltInNumeric(CppBuiltInNumericType::Float16);
+    assert_eq!(float16.to_cpp_code(None).unwrap(), "_Float16");
+    assert_eq!(float16.ascii_caption(), "_Float16");
+}
+
+#[test]
+fn long_double() {
+    let type1 = CppType::BuiltInNumeric(CppBuiltInNumericType::LongDouble);
+    assert_eq!(type1.to_cpp_code(None).unwrap(), "long double");
+    assert_eq!(type1.ascii_caption(), "long_double");
+
+    let ffi_return_type = ffi_type(&type1, CppTypeRole::ReturnType).unwrap();
+    assert_eq!(ffi_return_type.original_type(), &type1);
+    assert_eq!(
+        ffi_return_type.ffi_type().to_cpp_code(None).unwrap(),
+        "long double*"
+    );
+    assert_eq!(
+        ffi_return_type.conversion(),
+        &CppToFfiTypeConversion::ValueToPointer {
+            is_ffi_const: false
+        }
+    );
+
+    let ffi_arg = ffi_type(&type1, CppTypeRole::NotReturnType).unwrap();
+    assert_eq!(
+        ffi_arg.ffi_type().to_cpp_code(None).unwrap(),
+        "const long double*"
+    );
+    assert_eq!(
+        ffi_arg.conversion(),
+        &CppToFfiTypeConversion::ValueToPointer { is_ffi_const: true }
+    );
+
+    let ptr = CppType::new_pointer(false, type1.clone());
+    assert_type_to_ffi_unchanged(&ptr);
+
+    let array = CppType::Array {
+        element_type: Box::new(type1),
+        size: 4,
+    };
+    assert!(ffi_type(&array, CppTypeRole::NotReturnType).is_err());
+}
+
 #[test]
 fn char_ptr_ptr() {
     let type1 = CppType::new_pointer(
@@ -380,3 +442,172 @@ fn instantiate1() {
         )
     );
 }
+
+#[test]
+fn int_array() {
+    let type1 = CppType::Array {
+        element_type: Box::new(CppType::BuiltInNumeric(CppBuiltInNumericType::Int)),
+        size: 16,
+    };
+    assert_eq!(type1.is_void(), false);
+    assert_eq!(type1.is_class(), false);
+    assert_eq!(type1.is_array(), true);
+    assert_eq!(type1.to_cpp_code(None).unwrap(), "int[16]");
+    assert_eq!(type1.to_cpp_code(Some("data")).unwrap(), "int data[16]");
+    assert_eq!(type1.ascii_caption(), "int_array_16");
+
+    let ffi1 = ffi_type(&type1, CppTypeRole::NotReturnType).unwrap();
+    assert_eq!(ffi1.original_type(), &type1);
+    assert_eq!(
+        ffi1.ffi_type(),
+        &CppType::new_pointer(false, CppType::BuiltInNumeric(CppBuiltInNumericType::Int))
+    );
+    assert_eq!(
+        ffi1.conversion(),
+        &CppToFfiTypeConversion::ArrayToPointer {
+            is_ffi_const: false
+        }
+    );
+    assert!(ffi_type(&type1, CppTypeRole::ReturnType).is_err());
+}
+
+#[test]
+fn float_matrix_const_ref() {
+    let float = CppType::BuiltInNumeric(CppBuiltInNumericType::Float);
+    let array = CppType::Array {
+        element_type: Box::new(CppType::Array {
+            element_type: Box::new(float.clone()),
+            size: 4,
+        }),
+        size: 3,
+    };
+    assert_eq!(array.to_cpp_code(None).unwrap(), "float[3][4]");
+    assert_eq!(array.to_cpp_code(Some("(*)")).unwrap(), "float (*)[3][4]");
+    assert_eq!(array.to_cpp_pseudo_code(), "float[3][4]");
+    assert_eq!(array.array_dimensions(), (&float, vec![3, 4]));
+
+    let type1 = CppType::new_reference(true, array);
+    for role in &[CppTypeRole::NotReturnType, CppTypeRole::ReturnType] {
+        let ffi1 = ffi_type(&type1, *role).unwrap();
+        assert_eq!(ffi1.original_type(), &type1);
+        assert_eq!(ffi1.ffi_type(), &CppType::new_pointer(true, float.clone()));
+        assert_eq!(ffi1.ffi_type().to_cpp_code(None).unwrap(), "const float*");
+        assert_eq!(
+            ffi1.conversion(),
+            &CppToFfiTypeConversion::ArrayToPointer { is_ffi_const: true }
+        );
+    }
+}
+
+#[test]
+fn class_array() {
+    let type1 = CppType::Array {
+        element_type: Box::new(CppType::Class(CppPath::from_good_str("QPoint"))),
+        size: 2,
+    };
+    assert!(ffi_type(&type1, CppTypeRole::NotReturnType).is_err());
+    assert!(ffi_type(
+        &CppType::new_pointer(false, type1),
+        CppTypeRole::NotReturnType
+    )
+    .is_err());
+}
+
+#[test]
+fn member_function_pointer() {
+    let type1 = CppType::MemberPointer(CppMemberPointerType {
+        class_path: CppPath::from_good_str("QWidget"),
+        target: CppMemberPointerTarget::Method {
+            function_type: CppFunctionPointerType {
+                allows_variadic_arguments: false,
+                return_type: Box::new(CppType::BuiltInNumeric(CppBuiltInNumericType::Int)),
+                arguments: vec![CppType::BuiltInNumeric(CppBuiltInNumericType::Bool)],
+            },
+            is_const: true,
+        },
+    });
+    assert_eq!(type1.is_member_pointer(), true);
+    assert_eq!(type1.is_function_pointer(), false);
+    assert_eq!(
+        type1.to_cpp_code(None).unwrap(),
+        "int (QWidget::*)(bool) const"
+    );
+    assert_eq!(
+        type1.to_cpp_code(Some("const value")).unwrap(),
+        "int (QWidget::*const value)(bool) const"
+    );
+    assert_eq!(type1.to_cpp_pseudo_code(), "int (QWidget::*)(bool) const");
+    assert_eq!(type1.ascii_caption(), "QWidget_member_ptr");
+
+    let ffi1 = ffi_type(&type1, CppTypeRole::NotReturnType).unwrap();
+    assert_eq!(ffi1.original_type(), &type1);
+    assert_eq!(ffi1.ffi_type(), &CppType::new_pointer(true, CppType::Void));
+    assert_eq!(
+        ffi1.conversion(),
+        &CppToFfiTypeConversion::MemberPointerToVoidPointer
+    );
+    assert!(ffi_type(&type1, CppTypeRole::ReturnType).is_err());
+    assert!(ffi_type(
+        &CppType::new_reference(true, type1),
+        CppTypeRole::NotReturnType
+    )
+    .is_err());
+}
+
+#[test]
+fn instantiate_member_function_pointer() {
+    let param = |index: usize, name: &str| {
+        CppType::TemplateParameter(CppTemplateParameter {
+            nested_level: 0,
+            index,
+            name: name.into(),
+        })
+    };
+    let type1 = CppType::MemberPointer(CppMemberPointerType {
+        class_path: CppPath::from_good_str("QList"),
+        target: CppMemberPointerTarget::Method {
+            function_type: CppFunctionPointerType {
+                allows_variadic_arguments: false,
+                return_type: Box::new(CppType::new_reference(true, param(0, "T"))),
+                arguments: vec![param(1, "U")],
+            },
+            is_const: true,
+        },
+    });
+    assert!(type1.is_or_contains_template_parameter());
+    let int = CppType::BuiltInNumeric(CppBuiltInNumericType::Int);
+    let bool1 = CppType::BuiltInNumeric(CppBuiltInNumericType::Bool);
+    let r = type1.instantiate(0, &[int.clone(), bool1.clone()]).unwrap();
+    assert_eq!(
+        r,
+        CppType::MemberPointer(CppMemberPointerType {
+            class_path: CppPath::from_good_str("QList"),
+            target: CppMemberPointerTarget::Method {
+                function_type: CppFunctionPointerType {
+                    allows_variadic_arguments: false,
+                    return_type: Box::new(CppType::new_reference(true, int)),
+                    arguments: vec![bool1],
+                },
+                is_const: true,
+            },
+        })
+    );
+    assert!(!r.is_or_contains_template_parameter());
+}
+
+#[test]
+fn data_member_pointer() {
+    let type1 = CppType::MemberPointer(CppMemberPointerType {
+        class_path: CppPath::from_good_str("QPoint"),
+        target: CppMemberPointerTarget::Field(Box::new(CppType::BuiltInNumeric(
+            CppBuiltInNumericType::Int,
+        ))),
+    });
+    assert_eq!(type1.to_cpp_code(None).unwrap(), "int QPoint::*");
+    assert_eq!(
+        type1.to_cpp_code(Some("const*")).unwrap(),
+        "int QPoint::*const*"
+    );
+    assert_eq!(type1.to_cpp_pseudo_code(), "int QPoint::*");
+    assert!(!type1.is_or_contains_template_parameter());
+}
@@ -0,0 +1,270 @@
+use crate::cpp_code_generator;
+use crate::cpp_data::*;
+use crate::cpp_ffi_data::CppOverrider;
+use crate::cpp_function::{CppFunctionArgument, CppFunctionKind, CppFunctionMemberData};
+use crate::cpp_type::{CppBuiltInNumericType, CppType};
+use crate::cpp_virtual_methods;
+use crate::database::{DbItem, ItemId};
+use crate::rust_code_generator::{ConditionTexts, Generator};
+use crate::rust_info::{RustVirtualMethod, RustVirtualMethodsTrait};
+use crate::rust_type::{RustFinalType, RustPath, RustToFfiTypeConversion, RustType};
+use crate::tests::cpp_method::{empty_membership, empty_regular_method};
+use crate::tests::fixtures::{base, class, config, with_processor_data};
+use crate::workspace::Workspace;
+use itertools::Itertools;
+use ritual_common::file_utils::{create_file, file_to_string};
+
+/// Creates a virtual method with `int` arguments named after `argument_names`.
+fn virtual_method(path: &str, argument_names: &[&str], is_pure_virtual: bool) -> CppItem {
+    let mut function = empty_regular_method();
+    function.path = CppPath::from_good_str(path);
+    function.arguments = argument_names
+        .iter()
+        .map(|name| CppFunctionArgument {
+            name: name.to_string(),
+            argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
+            has_default_value: false,
+        })
+        .collect();
+    function.member = Some(CppFunctionMemberData {
+        is_virtual: true,
+        is_pure_virtual,
+        ..empty_membership()
+    });
+    CppItem::Function(function)
+}
+
+fn default_constructor(class_path: &str, visibility: CppVisibility) -> CppItem {
+    let class_path = CppPath::from_good_str(class_path);
+    let mut function = empty_regular_method();
+    function.path = class_path.join(class_path.last().clone());
+    function.member = Some(CppFunctionMemberData {
+        kind: CppFunctionKind::Constructor,
+        visibility,
+        ..empty_membership()
+    });
+    CppItem::Function(function)
+}
+
+/// Runs `cpp_virtual_methods::run` for the class `class_path` and
+/// returns the generated overrider, if any.
+fn run_virtual_methods(items: Vec<CppItem>, class_path: &str) -> Option<(CppOverrider, String)> {
+    let mut config = config();
+    config.add_overridable_class(CppPath::from_good_str(class_path));
+    with_processor_data(&config, items, Vec::new(), |data| {
+        cpp_virtual_methods::run(data).unwrap();
+
+        let overrider = data
+            .db
+            .ffi_items()
+            .filter_map(|item| item.item.as_overrider_ref())
+            .next()?
+            .clone();
+        let code = cpp_code_generator::overrider(data.db, &overrider).unwrap();
+        Some((overrider, code))
+    })
+}
+
+fn method_names(overrider: &CppOverrider) -> Vec<(&str, &str)> {
+    overrider
+        .methods
+        .iter()
+        .map(|method| (method.name.as_str(), method.unique_name.as_str()))
+        .collect()
+}
+
+#[test]
+fn overrider_for_class_hierarchy() {
+    let (overrider, code) = run_virtual_methods(
+        vec![
+            class("Base"),
+            class("Derived"),
+            default_constructor("Derived", CppVisibility::Public),
+            virtual_method("Base::f", &["x"], false),
+            virtual_method("Base::g", &[], true),
+            virtual_method("Derived::f", &["y"], false),
+            base("Derived", "Base", 0),
+        ],
+        "Derived",
+    )
+    .unwrap();
+    assert_eq!(overrider.base_class_path, CppPath::from_good_str("Derived"));
+    // `Base::f` is overridden by `Derived::f`
+    assert_eq!(method_names(&overrider), vec![("f", "f"), ("g", "g")]);
+
+    assert!(code.contains("public Derived {"));
+    assert!(code.contains("void f(int arg0) override {"));
+    assert!(code.contains("return Derived::f(arg0);"));
+    assert!(code.contains("void base_f(int arg0) {"));
+    assert!(code.contains("void set_g_callback("));
+    // the pure virtual method has no base implementation
+    assert!(code.contains("std::abort();"));
+    assert!(!code.contains("base_g"));
+}
+
+#[test]
+fn overrider_skips_hidden_methods() {
+    let (overrider, code) = run_virtual_methods(
+        vec![
+            class("Base"),
+            class("Derived"),
+            default_constructor("Derived", CppVisibility::Public),
+            virtual_method("Base::f", &["x"], false),
+            virtual_method("Base::g", &[], false),
+            virtual_method("Derived::f", &[], false),
+            base("Derived", "Base", 0),
+        ],
+        "Derived",
+    )
+    .unwrap();
+    // `Base::f(int)` is hidden by `Derived::f()` and can't be called
+    // as `Derived::f(arg0)`
+    assert_eq!(method_names(&overrider), vec![("f", "f"), ("g", "g")]);
+    assert_eq!(overrider.methods[0].arguments.len(), 0);
+    assert!(code.contains("void f() override {"));
+    assert!(!code.contains("void f(int arg0)"));
+}
+
+#[test]
+fn overrider_fails_for_hidden_pure_virtual_method() {
+    let overrider = run_virtual_methods(
+        vec![
+            class("Base"),
+            class("Derived"),
+            default_constructor("Derived", CppVisibility::Public),
+            virtual_method("Base::f", &["x"], true),
+            virtual_method("Derived::f", &[], false),
+            base("Derived", "Base", 0),
+        ],
+        "Derived",
+    );
+    // the subclass would remain abstract
+    assert!(overrider.is_none());
+}
+
+#[test]
+fn overrider_names_overloads() {
+    let (overrider, code) = run_virtual_methods(
+        vec![
+            class("Base"),
+            default_constructor("Base", CppVisibility::Protected),
+            virtual_method("Base::f", &[], false),
+            virtual_method("Base::f", &["x"], false),
+            virtual_method("Base::g", &[], false),
+        ],
+        "Base",
+    )
+    .unwrap();
+    assert_eq!(
+        method_names(&overrider),
+        vec![("f", "f_1"), ("f", "f_2"), ("g", "g")]
+    );
+    assert!(code.contains("void set_f_1_callback("));
+    assert!(code.contains("void set_f_2_callback("));
+    assert!(code.contains("void base_f_2(int arg0) {"));
+    assert!(code.contains("void f(int arg0) override {"));
+    assert!(code.contains("return Base::f(arg0);"));
+}
+
+#[test]
+fn overrider_requires_default_constructor() {
+    let run = |constructor: Option<CppItem>| {
+        let mut items = vec![class("Base"), virtual_method("Base::f", &[], false)];
+        items.extend(constructor);
+        let mut config = config();
+        config.add_overridable_class(CppPath::from_good_str("Base"));
+        with_processor_data(&config, items, Vec::new(), |data| {
+            cpp_virtual_methods::run(data)
+        })
+    };
+    assert!(run(None).is_err());
+    assert!(run(Some(default_constructor("Base", CppVisibility::Private))).is_err());
+
+    let mut with_argument = empty_regular_method();
+    with_argument.path = CppPath::from_good_str("Base::Base");
+    with_argument.member = Some(CppFunctionMemberData {
+        kind: CppFunctionKind::Constructor,
+        ..empty_membership()
+    });
+    with_argument.arguments = vec![CppFunctionArgument {
+        name: "x".into(),
+        argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
+        has_default_value: false,
+    }];
+    assert!(run(Some(CppItem::Function(with_argument.clone()))).is_err());
+
+    with_argument.arguments[0].has_default_value = true;
+    assert!(run(Some(CppItem::Function(with_argument))).is_ok());
+    assert!(run(Some(default_constructor("Base", CppVisibility::Protected))).is_ok());
+}
+
+fn final_type(rust_type: RustType) -> RustFinalType {
+    RustFinalType::new(rust_type, RustToFfiTypeConversion::None).unwrap()
+}
+
+#[test]
+fn virtual_methods_trait() {
+    let dir = tempdir::TempDir::new("test_virtual_methods_trait").unwrap();
+    let mut workspace = Workspace::new(dir.path().into()).unwrap();
+    let db = workspace
+        .get_database_client("a", &[], false, true)
+        .unwrap();
+    let data = RustVirtualMethodsTrait {
+        path: RustPath::from_good_str("a::BaseVirtualMethods"),
+        base_class_path: RustPath::from_good_str("a::Base"),
+        overrider_path: RustPath::from_good_str("a::BaseOverrider"),
+        methods: vec![
+            RustVirtualMethod {
+                name: "f".into(),
+                cpp_name: "f".into(),
+                is_const: true,
+                arguments: vec![final_type(RustType::Primitive("i32".into()))],
+                return_type: final_type(RustType::Primitive("i32".into())),
+                set_callback_function: "set_f_callback".into(),
+                base_function: Some("base_f".into()),
+            },
+            RustVirtualMethod {
+                name: "g".into(),
+                cpp_name: "g".into(),
+                is_const: false,
+                arguments: Vec::new(),
+                return_type: final_type(RustType::unit()),
+                set_callback_function: "set_g_callback".into(),
+                base_function: None,
+            },
+        ],
+    };
+    let path = dir.path().join("lib.rs");
+    let mut generator = Generator {
+        destination: vec![create_file(&path).unwrap()],
+        output_src_path: dir.path().into(),
+        crate_template_src_path: None,
+        current_database: &db,
+    };
+    let item = DbItem {
+        id: ItemId::new("a".into(), 1),
+        source_id: None,
+        item: &data,
+    };
+    generator
+        .generate_virtual_methods_trait(item, &ConditionTexts::default())
+        .unwrap();
+    drop(generator);
+
+    let code = file_to_string(&path).unwrap();
+    let code = code.split_whitespace().join(" ");
+    assert!(code.contains("pub trait BaseVirtualMethods {"));
+    assert!(code.contains(
+        "unsafe fn f(&self, this: ::cpp_core::Ptr<crate::BaseOverrider>, arg0: i32) -> i32 \
+         { this.base_f(arg0) }"
+    ));
+    assert!(code.contains("unsafe fn g(&self, this: ::cpp_core::MutPtr<crate::BaseOverrider>);"));
+    // methods may be called re-entrantly, so no mutable references are created
+    assert!(code.contains("let methods = &*(data as *const T);"));
+    assert!(!code.contains("&mut *(data"));
+    // panics must not unwind into C++ code
+    assert_eq!(code.matches("::std::panic::catch_unwind(").count(), 2);
+    assert!(code.contains("Err(_) => ::std::process::abort(),"));
+    assert!(code.contains("obj.set_f_callback(Some(callback_0::<T>));"));
+    assert!(code.contains("obj.set_g_callback(Some(callback_1::<T>));"));
+}
@@ -0,0 +1,110 @@
+use crate::config::{Config, CrateDependencyKind, CrateDependencySource, CrateProperties};
+use crate::cpp_data::*;
+use crate::processor::ProcessorData;
+use crate::tests::cpp_method::{empty_membership, empty_regular_method};
+use crate::workspace::Workspace;
+
+/// Name of the crate processed in tests.
+pub const CRATE_NAME: &str = "a";
+
+/// Name of the dependency crate added by `config_with_dependency`.
+pub const DEPENDENCY_NAME: &str = "dep";
+
+pub fn config() -> Config {
+    Config::new(CrateProperties::new(CRATE_NAME, "0.0.0"))
+}
+
+/// Returns config of a crate that depends on `DEPENDENCY_NAME` crate.
+pub fn config_with_dependency() -> Config {
+    let mut crate_properties = CrateProperties::new(CRATE_NAME, "0.0.0");
+    crate_properties
+        .add_dependency(
+            DEPENDENCY_NAME,
+            CrateDependencyKind::Ritual,
+            CrateDependencySource::CurrentWorkspace,
+        )
+        .unwrap();
+    Config::new(crate_properties)
+}
+
+/// Creates a temporary workspace containing a database with `items`
+/// and calls `f` with it. If the crate of `config` has dependencies,
+/// `dependency_items` are added to their databases.
+pub fn with_processor_data<R>(
+    config: &Config,
+    items: Vec<CppItem>,
+    dependency_items: Vec<CppItem>,
+    f: impl FnOnce(&mut ProcessorData<'_>) -> R,
+) -> R {
+    let dir = tempdir::TempDir::new("test_processor_data").unwrap();
+    let mut workspace = Workspace::new(dir.path().into()).unwrap();
+    for dependency in config.crate_properties().dependencies() {
+        let mut dependency_db = workspace
+            .get_database_client(dependency.name(), &[], false, true)
+            .unwrap();
+        for item in dependency_items.iter().cloned() {
+            dependency_db.add_cpp_item(None, item).unwrap();
+        }
+        workspace.save_database(&mut dependency_db).unwrap();
+    }
+    let mut db = workspace
+        .get_database_client(
+            config.crate_properties().name(),
+            config.crate_properties().dependencies(),
+            false,
+            true,
+        )
+        .unwrap();
+    for item in items {
+        db.add_cpp_item(None, item).unwrap();
+    }
+    let mut data = ProcessorData {
+        workspace: &mut workspace,
+        config,
+        db: &mut db,
+    };
+    f(&mut data)
+}
+
+pub fn class(path: &str) -> CppItem {
+    CppItem::Type(CppTypeDeclaration {
+        path: CppPath::from_good_str(path),
+        kind: CppTypeDeclarationKind::Class,
+        special_members: None,
+    })
+}
+
+/// Creates a public non-virtual base specifier.
+pub fn base(derived: &str, base: &str, index: usize) -> CppItem {
+    base_with_visibility(derived, base, index, CppVisibility::Public)
+}
+
+pub fn base_with_visibility(
+    derived: &str,
+    base: &str,
+    index: usize,
+    visibility: CppVisibility,
+) -> CppItem {
+    CppItem::ClassBase(CppBaseSpecifier {
+        base_class_type: CppPath::from_good_str(base),
+        base_index: index,
+        is_virtual: false,
+        visibility,
+        derived_class_type: CppPath::from_good_str(derived),
+    })
+}
+
+/// Creates a public regular method without arguments.
+pub fn method(path: &str) -> CppItem {
+    let mut function = empty_regular_method();
+    function.path = CppPath::from_good_str(path);
+    function.member = Some(empty_membership());
+    CppItem::Function(function)
+}
+
+pub fn pairs(items: &[(&str, &str)]) -> Vec<(String, String)> {
+    items
+        .iter()
+        .map(|(a, b)| (a.to_string(), b.to_string()))
+        .collect()
+}
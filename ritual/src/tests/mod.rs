@@ -1,7 +1,17 @@
 #![allow(clippy::cognitive_complexity)]
 
+mod cpp_annotations;
+mod cpp_casts;
 mod cpp_ffi_data;
+mod cpp_implicit_methods;
+mod cpp_inheritance;
 mod cpp_method;
 mod cpp_operator;
 mod cpp_parser;
+mod cpp_parser_report;
+mod cpp_template_instantiator;
 mod cpp_type;
+mod cpp_virtual_methods;
+mod fixtures;
+mod rust_generator;
+mod type_allocation_places;
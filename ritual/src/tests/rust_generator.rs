@@ -0,0 +1,634 @@
+use crate::config::DefaultArgumentsMode;
+use crate::cpp_checks::CppChecksItem;
+use crate::cpp_data::{CppClassField, CppItem, CppPath, CppPathItem, CppVisibility};
+use crate::cpp_ffi_data::CppCast;
+use crate::cpp_function::{CppFunction, CppFunctionArgument, CppOperator};
+use crate::cpp_type::{CppBuiltInNumericType, CppType};
+use crate::database::{ItemId, ItemWithSource};
+use crate::processor::ProcessorData;
+use crate::rust_generator::{
+    c_struct_layout_snippet, FunctionWithDesiredPath, State, TraitImplInfo, TraitTypes,
+};
+use crate::rust_info::{
+    RustFfiWrapperData, RustFunctionArgument, RustFunctionKind, RustFunctionSelfArgKind,
+    RustStructKind, UnnamedRustFunction,
+};
+use crate::rust_type::{
+    RustCommonType, RustFinalType, RustPath, RustToFfiTypeConversion, RustType,
+};
+use crate::tests::cpp_method::{empty_membership, empty_regular_method};
+use crate::tests::fixtures::{config, with_processor_data};
+use crate::{
+    cpp_checker, cpp_ffi_generator, cpp_omitting_arguments, rust_code_generator, rust_generator,
+};
+use itertools::Itertools;
+use ritual_common::file_utils::file_to_string;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+fn class_type(path: &str) -> RustType {
+    RustType::Common(RustCommonType {
+        path: RustPath::from_good_str(path),
+        generic_arguments: None,
+    })
+}
+
+fn converting_constructor(argument_type: RustType) -> UnnamedRustFunction {
+    UnnamedRustFunction {
+        is_public: true,
+        is_unsafe: true,
+        kind: RustFunctionKind::FfiWrapper(RustFfiWrapperData {
+            ffi_function_path: RustPath::from_good_str("a::__ffi::ctr_a_ffi_QVariant_QVariant"),
+            has_tuple_arguments: false,
+        }),
+        arguments: vec![RustFunctionArgument {
+            argument_type: RustFinalType::new(argument_type, RustToFfiTypeConversion::None)
+                .unwrap(),
+            name: "value".into(),
+            ffi_index: 0,
+        }],
+        return_type: RustFinalType::new(
+            RustType::new_pointer(false, class_type("a::QVariant")),
+            RustToFfiTypeConversion::CppBoxToPtr,
+        )
+        .unwrap(),
+    }
+}
+
+fn trait_type(path: &str, argument: RustType) -> RustCommonType {
+    RustCommonType {
+        path: RustPath::from_good_str(path),
+        generic_arguments: Some(vec![argument]),
+    }
+}
+
+#[test]
+fn converting_constructor_with_local_argument() {
+    let arg_type = class_type("a::QString");
+    let impls =
+        State::process_converting_constructor(converting_constructor(arg_type.clone()), "a", &[])
+            .unwrap();
+    assert_eq!(impls.len(), 2);
+
+    let box_type = RustFinalType::new(
+        RustType::new_pointer(false, class_type("a::QVariant")),
+        RustToFfiTypeConversion::CppBoxToPtr,
+    )
+    .unwrap()
+    .api_type()
+    .clone();
+    assert_eq!(impls[0].target_type, box_type);
+    assert_eq!(
+        impls[0].trait_type,
+        trait_type("cpp_core::CastFrom", arg_type.clone())
+    );
+    assert_eq!(
+        impls[0].functions[0].path,
+        RustPath::from_good_str("cpp_core::CastFrom::cast_from")
+    );
+
+    assert_eq!(impls[1].target_type, class_type("a::QVariant"));
+    assert_eq!(
+        impls[1].trait_type,
+        trait_type("cpp_core::NewFrom", arg_type)
+    );
+    assert_eq!(
+        impls[1].functions[0].path,
+        RustPath::from_good_str("cpp_core::NewFrom::new_from")
+    );
+}
+
+#[test]
+fn converting_constructor_with_non_local_argument() {
+    for arg_type in &[RustType::Primitive("i32".into()), class_type("b::QString")] {
+        let impls = State::process_converting_constructor(
+            converting_constructor(arg_type.clone()),
+            "a",
+            &[],
+        )
+        .unwrap();
+        assert_eq!(impls.len(), 2);
+
+        // `impl CastFrom<i32> for CppBox<QVariant>` is not allowed by the orphan rules,
+        // so `CastFrom` is provided by `ImplicitNewFrom` instead.
+        assert_eq!(impls[0].target_type, class_type("a::QVariant"));
+        assert_eq!(
+            impls[0].trait_type,
+            trait_type("cpp_core::ImplicitNewFrom", arg_type.clone())
+        );
+        assert!(impls[0].functions.is_empty());
+        assert_eq!(impls[0].parent_path, RustPath::from_good_str("a"));
+
+        assert_eq!(impls[1].target_type, class_type("a::QVariant"));
+        assert_eq!(
+            impls[1].trait_type,
+            trait_type("cpp_core::NewFrom", arg_type.clone())
+        );
+        assert_eq!(impls[1].functions.len(), 1);
+    }
+}
+
+#[test]
+fn c_struct_layout_snippet_test() {
+    let path = CppPath::from_good_str("Color");
+    let field = |name: &str, field_type: CppType| CppClassField {
+        path: path.join(CppPathItem::from_good_str(name)),
+        field_type,
+        visibility: CppVisibility::Public,
+        is_static: false,
+        bit_field_width: None,
+    };
+    let alpha = field("alpha", CppType::BuiltInNumeric(CppBuiltInNumericType::Int));
+    let rgb = field(
+        "rgb",
+        CppType::Array {
+            element_type: Box::new(CppType::BuiltInNumeric(CppBuiltInNumericType::UChar)),
+            size: 3,
+        },
+    );
+    let snippet = c_struct_layout_snippet(&path, &[&alpha, &rgb]).unwrap();
+    assert_eq!(
+        snippet.code(),
+        "#include <cstddef>
+struct ritual_layout_Color {
+    int alpha;
+    unsigned char rgb[3];
+};
+static_assert(sizeof(Color) == sizeof(ritual_layout_Color), \"sizeof mismatch\");
+static_assert(alignof(Color) == alignof(ritual_layout_Color), \"alignof mismatch\");
+static_assert(offsetof(Color, alpha) == offsetof(ritual_layout_Color, alpha), \"offset of alpha mismatch\");
+static_assert(offsetof(Color, rgb) == offsetof(ritual_layout_Color, rgb), \"offset of rgb mismatch\");
+"
+    );
+}
+
+fn call_operator(operator: CppOperator, is_const: bool, arg_count: usize) -> CppFunction {
+    let mut function = empty_regular_method();
+    function.path = CppPath::from_good_str("Functor::operator()");
+    function.member = Some(empty_membership());
+    function.member.as_mut().unwrap().is_const = is_const;
+    function.arguments = (0..arg_count)
+        .map(|i| CppFunctionArgument {
+            argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
+            name: format!("arg{}", i),
+            has_default_value: false,
+        })
+        .collect();
+    function.operator = Some(operator);
+    function
+}
+
+#[test]
+fn subscript_and_function_call_trait_info() {
+    let info = TraitImplInfo::new(&call_operator(CppOperator::Subscript, true, 1)).unwrap();
+    assert_eq!(info.trait_path, "cpp_core::ops::Index");
+    assert_eq!(info.function_name, "index");
+    assert!(info.trait_arg_is_second_arg_type);
+    assert!(!info.trait_args_are_tuple);
+    assert_eq!(info.self_arg_kind, RustFunctionSelfArgKind::ConstRef);
+
+    let info = TraitImplInfo::new(&call_operator(CppOperator::Subscript, false, 1)).unwrap();
+    assert_eq!(info.trait_path, "cpp_core::ops::IndexMut");
+    assert_eq!(info.function_name, "index_mut");
+    assert_eq!(info.self_arg_kind, RustFunctionSelfArgKind::MutRef);
+
+    assert!(TraitImplInfo::new(&call_operator(CppOperator::Subscript, true, 2)).is_none());
+
+    for &arg_count in &[0, 1, 3] {
+        let info =
+            TraitImplInfo::new(&call_operator(CppOperator::FunctionCall, true, arg_count)).unwrap();
+        assert_eq!(info.trait_path, "cpp_core::ops::Call");
+        assert_eq!(info.function_name, "call");
+        assert!(!info.trait_arg_is_second_arg_type);
+        assert!(info.trait_args_are_tuple);
+        assert_eq!(info.self_arg_kind, RustFunctionSelfArgKind::ConstRef);
+
+        let info = TraitImplInfo::new(&call_operator(CppOperator::FunctionCall, false, arg_count))
+            .unwrap();
+        assert_eq!(info.trait_path, "cpp_core::ops::CallMut");
+        assert_eq!(info.function_name, "call_mut");
+        assert!(info.trait_args_are_tuple);
+        assert_eq!(info.self_arg_kind, RustFunctionSelfArgKind::MutRef);
+    }
+
+    let mut variadic = call_operator(CppOperator::FunctionCall, true, 1);
+    variadic.allows_variadic_arguments = true;
+    assert!(TraitImplInfo::new(&variadic).is_none());
+
+    let mut static_function = call_operator(CppOperator::FunctionCall, true, 1);
+    static_function.member.as_mut().unwrap().is_static = true;
+    assert!(TraitImplInfo::new(&static_function).is_none());
+}
+
+#[test]
+fn function_call_trait_impl_with_tuple_arguments() {
+    let functor_type = class_type("a::Functor");
+    let argument = |name: &str, type1: RustType, ffi_index: usize| RustFunctionArgument {
+        argument_type: RustFinalType::new(type1, RustToFfiTypeConversion::None).unwrap(),
+        name: name.into(),
+        ffi_index,
+    };
+    let function = UnnamedRustFunction {
+        is_public: true,
+        is_unsafe: false,
+        kind: RustFunctionKind::FfiWrapper(RustFfiWrapperData {
+            ffi_function_path: RustPath::from_good_str("a::__ffi::ctr_a_ffi_Functor_call"),
+            has_tuple_arguments: false,
+        }),
+        arguments: vec![
+            argument(
+                "this_ptr",
+                RustType::new_pointer(false, functor_type.clone()),
+                0,
+            ),
+            argument("x", RustType::Primitive("i32".into()), 1),
+            argument("y", RustType::bool(), 2),
+        ],
+        return_type: RustFinalType::new(RustType::bool(), RustToFfiTypeConversion::None).unwrap(),
+    };
+
+    let info = TraitImplInfo::new(&call_operator(CppOperator::FunctionCall, false, 2)).unwrap();
+    let trait_impl = State::process_operator_as_trait_impl(function, info, "a", &[]).unwrap();
+    assert_eq!(trait_impl.target_type, functor_type);
+    assert_eq!(
+        trait_impl.trait_type,
+        trait_type(
+            "cpp_core::ops::CallMut",
+            RustType::Tuple(vec![RustType::Primitive("i32".into()), RustType::bool()])
+        )
+    );
+    assert_eq!(trait_impl.functions.len(), 1);
+    let function = &trait_impl.functions[0];
+    assert_eq!(
+        function.path,
+        RustPath::from_good_str("cpp_core::ops::CallMut::call_mut")
+    );
+    assert_eq!(
+        function.kind,
+        RustFunctionKind::FfiWrapper(RustFfiWrapperData {
+            ffi_function_path: RustPath::from_good_str("a::__ffi::ctr_a_ffi_Functor_call"),
+            has_tuple_arguments: true,
+        })
+    );
+    assert_eq!(
+        function
+            .arguments
+            .iter()
+            .map(|arg| arg.name.as_str())
+            .collect_vec(),
+        vec!["self", "x", "y"]
+    );
+}
+
+/// Creates `void name(int x, int y = 0, int z = 0)`.
+fn function_with_default_arguments(name: &str) -> CppFunction {
+    let arg = |name: &str, has_default_value: bool| CppFunctionArgument {
+        argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
+        name: name.into(),
+        has_default_value,
+    };
+    let mut function = empty_regular_method();
+    function.path = CppPath::from_good_str(name);
+    function.arguments = vec![arg("x", false), arg("y", true), arg("z", true)];
+    function
+}
+
+/// Runs C++ processing steps needed for the Rust generator on `functions`,
+/// marks all FFI items as successfully checked and calls `f`.
+fn with_ffi_items(
+    mode: DefaultArgumentsMode,
+    functions: Vec<CppFunction>,
+    f: impl FnOnce(&mut ProcessorData<'_>),
+) {
+    let mut config = config();
+    config.set_default_arguments_mode(mode);
+    let items = functions.into_iter().map(CppItem::Function).collect();
+    with_processor_data(&config, items, Vec::new(), |data| {
+        cpp_omitting_arguments::run(data).unwrap();
+        cpp_ffi_generator::run(data).unwrap();
+        let environments = cpp_checker::environments(data);
+        for ffi_item_id in data.db.ffi_item_ids().collect_vec() {
+            for env in &environments {
+                let item = CppChecksItem {
+                    env: env.clone(),
+                    is_success: true,
+                    is_predicted: false,
+                };
+                data.db.add_cpp_checks_item(ffi_item_id.clone(), item);
+            }
+        }
+        f(data);
+    })
+}
+
+fn variant(
+    arguments: &[&str],
+    ffi_name: &str,
+    source_id: u32,
+) -> ItemWithSource<FunctionWithDesiredPath> {
+    let arguments = arguments
+        .iter()
+        .enumerate()
+        .map(|(index, name)| RustFunctionArgument {
+            argument_type: RustFinalType::new(
+                RustType::Primitive("i32".into()),
+                RustToFfiTypeConversion::None,
+            )
+            .unwrap(),
+            name: name.to_string(),
+            ffi_index: index,
+        })
+        .collect();
+    let function = UnnamedRustFunction {
+        is_public: true,
+        is_unsafe: true,
+        kind: RustFunctionKind::FfiWrapper(RustFfiWrapperData {
+            ffi_function_path: RustPath::from_good_str(&format!("a::__ffi::{}", ffi_name)),
+            has_tuple_arguments: false,
+        }),
+        arguments,
+        return_type: RustFinalType::new(RustType::unit(), RustToFfiTypeConversion::None).unwrap(),
+    };
+    ItemWithSource::new(
+        &ItemId::new("a".into(), source_id),
+        FunctionWithDesiredPath {
+            function,
+            desired_path: RustPath::from_good_str("a::f"),
+        },
+    )
+}
+
+#[test]
+fn process_optional_arguments_test() {
+    let variants = vec![
+        variant(&["x"], "f1", 1),
+        variant(&["x", "y", "z"], "f3", 3),
+        variant(&["x", "y"], "f2", 2),
+    ];
+    let result = State::process_optional_arguments(&variants).unwrap();
+    assert_eq!(result.source_id, ItemId::new("a".into(), 3));
+    assert_eq!(result.item.desired_path, RustPath::from_good_str("a::f"));
+    let function = &result.item.function;
+    assert_eq!(
+        function.arguments.iter().map(|arg| &arg.name).collect_vec(),
+        vec!["x"]
+    );
+    let data = if let RustFunctionKind::OptionalArgumentsWrapper(data) = &function.kind {
+        data
+    } else {
+        panic!("unexpected function kind: {:?}", function.kind);
+    };
+    assert_eq!(
+        data.variants
+            .iter()
+            .map(|variant| (variant.ffi_function_path.last(), variant.arguments.len()))
+            .collect_vec(),
+        vec![("f3", 3), ("f2", 2), ("f1", 1)]
+    );
+
+    // a variant for each number of set arguments is required
+    let missing = vec![variant(&["x"], "f1", 1), variant(&["x", "y", "z"], "f3", 3)];
+    assert!(State::process_optional_arguments(&missing).is_err());
+
+    let single = vec![variant(&["x", "y"], "f2", 2)];
+    assert!(State::process_optional_arguments(&single).is_err());
+
+    let incompatible = vec![variant(&["x"], "f1", 1), variant(&["a", "y"], "f2", 2)];
+    assert!(State::process_optional_arguments(&incompatible).is_err());
+}
+
+#[test]
+fn optional_arguments_group_test() {
+    for &mode in &[
+        DefaultArgumentsMode::OptionalArguments,
+        DefaultArgumentsMode::Overloads,
+    ] {
+        with_ffi_items(mode, vec![function_with_default_arguments("f")], |data| {
+            let full_function_id = data
+                .db
+                .cpp_items()
+                .filter_map(|item| item.filter_map(|item| item.as_function_ref()))
+                .find(|function| function.item.arguments.len() == 3)
+                .unwrap()
+                .id;
+            let ffi_item_ids = data.db.ffi_item_ids().collect_vec();
+            assert_eq!(ffi_item_ids.len(), 3);
+
+            let state = State {
+                data,
+                special_module_paths: HashMap::new(),
+            };
+            for ffi_item_id in &ffi_item_ids {
+                let group = state.optional_arguments_group(ffi_item_id).unwrap();
+                if mode == DefaultArgumentsMode::OptionalArguments {
+                    assert_eq!(group.as_ref(), Some(&full_function_id));
+                } else {
+                    assert_eq!(group, None);
+                }
+            }
+        });
+    }
+}
+
+#[test]
+fn optional_arguments_generated_code() {
+    with_ffi_items(
+        DefaultArgumentsMode::OptionalArguments,
+        vec![
+            function_with_default_arguments("f"),
+            function_with_default_arguments("g"),
+        ],
+        |data| {
+            rust_generator::run(data).unwrap();
+
+            let structs = data
+                .db
+                .rust_items()
+                .filter_map(|item| item.item.as_struct_ref())
+                .filter_map(|rust_struct| {
+                    if let RustStructKind::OptionalArguments(data) = &rust_struct.kind {
+                        Some((&rust_struct.path, data))
+                    } else {
+                        None
+                    }
+                })
+                .sorted_by_key(|(path, _)| path.last().to_string())
+                .collect_vec();
+            assert_eq!(structs.len(), 2);
+            assert_eq!(structs[0].0, &RustPath::from_good_str("a::FArgs"));
+            assert_eq!(structs[0].1.function_path, RustPath::from_good_str("a::f"));
+            assert_eq!(structs[1].0, &RustPath::from_good_str("a::GArgs"));
+            assert_eq!(structs[1].1.function_path, RustPath::from_good_str("a::g"));
+            for (_, data) in &structs {
+                assert_eq!(
+                    data.fields.iter().map(|field| &field.name).collect_vec(),
+                    vec!["y", "z"]
+                );
+            }
+
+            let output_dir = tempdir::TempDir::new("test_optional_arguments_output").unwrap();
+            rust_code_generator::generate(data.db, output_dir.path(), None::<PathBuf>).unwrap();
+            let code = file_to_string(output_dir.path().join("lib.rs")).unwrap();
+            let code = code.split_whitespace().join(" ");
+            assert!(code.contains(
+                "#[derive(Default)] pub struct FArgs { \
+                 pub y: Option<::std::os::raw::c_int>, pub z: Option<::std::os::raw::c_int>, }"
+            ));
+            assert!(code.contains("pub unsafe fn f(x: ::std::os::raw::c_int, args: crate::FArgs)"));
+            assert!(code.contains("let crate::FArgs { y, z } = args; match (y, z,) {"));
+            assert!(code.contains("(Some(y), Some(z),) => {"));
+            assert!(code.contains("(Some(y), None,) => {"));
+            assert!(code.contains("(None, None,) => {"));
+            assert!(code.contains("_ => panic!("));
+            assert!(code.contains("Panics if a field of `FArgs` is set"));
+        },
+    );
+}
+
+#[test]
+fn conversion_operator_from_impls() {
+    let conversion =
+        |self_path: &str, is_const: bool, return_type: RustFinalType| UnnamedRustFunction {
+            is_public: true,
+            is_unsafe: true,
+            kind: RustFunctionKind::FfiWrapper(RustFfiWrapperData {
+                ffi_function_path: RustPath::from_good_str("a::__ffi::ctr_a_ffi_convert"),
+                has_tuple_arguments: false,
+            }),
+            arguments: vec![RustFunctionArgument {
+                argument_type: RustFinalType::new(
+                    RustType::new_pointer(is_const, class_type(self_path)),
+                    RustToFfiTypeConversion::None,
+                )
+                .unwrap(),
+                name: "this_ptr".into(),
+                ffi_index: 0,
+            }],
+            return_type,
+        };
+    let int_type = RustType::Primitive("i32".into());
+    let to_int = RustFinalType::new(int_type.clone(), RustToFfiTypeConversion::None).unwrap();
+    let to_box = RustFinalType::new(
+        RustType::new_pointer(false, class_type("a::Target")),
+        RustToFfiTypeConversion::CppBoxToPtr,
+    )
+    .unwrap();
+    let from_trait = |is_const: bool, path: &str| RustCommonType {
+        path: RustPath::from_good_str("std::convert::From"),
+        generic_arguments: Some(vec![RustType::new_reference(is_const, class_type(path))]),
+    };
+
+    // class to primitive
+    let trait_impl =
+        State::process_conversion_operator(conversion("a::Source", true, to_int.clone()), "a", &[])
+            .unwrap();
+    assert_eq!(trait_impl.target_type, int_type);
+    assert_eq!(trait_impl.trait_type, from_trait(true, "a::Source"));
+    assert_eq!(trait_impl.parent_path, RustPath::from_good_str("a"));
+    let function = &trait_impl.functions[0];
+    assert_eq!(
+        function.path,
+        RustPath::from_good_str("std::convert::From::from")
+    );
+    assert!(!function.is_unsafe);
+    assert_eq!(function.arguments[0].name, "value");
+    assert_eq!(
+        function.arguments[0].argument_type.api_type(),
+        &RustType::new_reference(true, class_type("a::Source"))
+    );
+
+    // class to class
+    let trait_impl = State::process_conversion_operator(
+        conversion("a::Source", false, to_box.clone()),
+        "a",
+        &[],
+    )
+    .unwrap();
+    assert_eq!(trait_impl.target_type, to_box.api_type().clone());
+    assert_eq!(trait_impl.trait_type, from_trait(false, "a::Source"));
+
+    // only one `From<&Source>` impl is allowed for a target type
+    let existing = TraitTypes {
+        target_type: int_type.clone(),
+        trait_type: from_trait(true, "a::Source"),
+    };
+    assert!(State::process_conversion_operator(
+        conversion("a::Source", true, to_int.clone()),
+        "a",
+        &[existing]
+    )
+    .is_err());
+    let other = TraitTypes {
+        target_type: int_type,
+        trait_type: from_trait(true, "a::Other"),
+    };
+    assert!(State::process_conversion_operator(
+        conversion("a::Source", true, to_int.clone()),
+        "a",
+        &[other]
+    )
+    .is_ok());
+
+    // orphan rules require the source type to be local
+    assert!(
+        State::process_conversion_operator(conversion("b::Source", true, to_int), "a", &[])
+            .is_err()
+    );
+    let to_unit = RustFinalType::new(RustType::unit(), RustToFfiTypeConversion::None).unwrap();
+    assert!(
+        State::process_conversion_operator(conversion("a::Source", true, to_unit), "a", &[])
+            .is_err()
+    );
+}
+
+#[test]
+fn most_derived_cast_trait_impl() {
+    let pointer = |target: RustType| {
+        RustFinalType::new(
+            RustType::new_pointer(false, target),
+            RustToFfiTypeConversion::None,
+        )
+        .unwrap()
+    };
+    let c_void = class_type("std::ffi::c_void");
+    let function = UnnamedRustFunction {
+        is_public: true,
+        is_unsafe: false,
+        kind: RustFunctionKind::FfiWrapper(RustFfiWrapperData {
+            ffi_function_path: RustPath::from_good_str("a::__ffi::ctr_a_ffi_dynamic_cast"),
+            has_tuple_arguments: false,
+        }),
+        arguments: vec![RustFunctionArgument {
+            argument_type: pointer(class_type("a::Base")),
+            name: "ptr".into(),
+            ffi_index: 0,
+        }],
+        return_type: pointer(c_void.clone()),
+    };
+    let impls = State::process_cast(function, &CppCast::MostDerived, "a", &[]).unwrap();
+    assert_eq!(impls.len(), 1);
+    assert_eq!(impls[0].target_type, class_type("a::Base"));
+    assert_eq!(
+        impls[0].trait_type,
+        RustCommonType {
+            path: RustPath::from_good_str("cpp_core::MostDerived"),
+            generic_arguments: None,
+        }
+    );
+    assert_eq!(
+        impls[0]
+            .functions
+            .iter()
+            .map(|function| function.path.last())
+            .collect_vec(),
+        vec!["most_derived", "most_derived_mut"]
+    );
+    assert_eq!(
+        impls[0].functions[0].return_type.api_type(),
+        &RustType::Common(trait_type("cpp_core::Ptr", c_void.clone()))
+    );
+    assert_eq!(
+        impls[0].functions[1].return_type.api_type(),
+        &RustType::Common(trait_type("cpp_core::MutPtr", c_void))
+    );
+}
@@ -0,0 +1,83 @@
+use crate::config::{Config, MovableTypesHookOutput};
+use crate::cpp_annotations::CppAnnotations;
+use crate::cpp_checker;
+use crate::cpp_checks::CppChecksItem;
+use crate::cpp_data::CppPath;
+use crate::tests::fixtures::{class, config, with_processor_data};
+use crate::type_allocation_places::{is_movable, movable_type_snippet};
+
+#[test]
+fn movable_type_snippet_code() {
+    let snippet = movable_type_snippet(&CppPath::from_good_str("ns::C")).unwrap();
+    assert_eq!(
+        snippet.code(),
+        "static_assert(std::is_trivially_copyable<ns::C>::value, \
+         \"std::is_trivially_copyable\");\n\
+         static_assert(std::is_nothrow_move_constructible<ns::C>::value, \
+         \"std::is_nothrow_move_constructible\");\n\
+         static_assert(std::is_copy_constructible<ns::C>::value, \
+         \"std::is_copy_constructible\");"
+    );
+}
+
+/// Adds class `C` with the given annotation and checks result to a new database
+/// and returns the result of `is_movable` for it.
+fn check_is_movable(config: &Config, annotation: Option<bool>, check_result: Option<bool>) -> bool {
+    with_processor_data(config, vec![class("C")], Vec::new(), |data| {
+        let path = CppPath::from_good_str("C");
+        let id = data.db.cpp_items().next().unwrap().id;
+        if annotation.is_some() {
+            let annotations = CppAnnotations {
+                is_movable: annotation,
+                ..CppAnnotations::default()
+            };
+            data.db.add_annotations_item(id.clone(), annotations);
+        }
+        if let Some(is_success) = check_result {
+            for env in cpp_checker::environments(data) {
+                data.db.add_environment(env.clone());
+                data.db.add_movable_type_checks_item(
+                    id.clone(),
+                    CppChecksItem {
+                        env,
+                        is_success,
+                        is_predicted: false,
+                    },
+                );
+            }
+        }
+        is_movable(data, &id, &path).unwrap()
+    })
+}
+
+#[test]
+fn is_movable_precedence() {
+    let mut config = config();
+    // checks are ignored unless detection is enabled
+    assert!(!check_is_movable(&config, None, None));
+    assert!(!check_is_movable(&config, None, Some(true)));
+    assert!(check_is_movable(&config, Some(true), None));
+
+    config.set_detect_movable_types(true);
+    assert!(!check_is_movable(&config, None, None));
+    assert!(check_is_movable(&config, None, Some(true)));
+    assert!(!check_is_movable(&config, None, Some(false)));
+
+    // annotations take precedence over checks
+    assert!(!check_is_movable(&config, Some(false), Some(true)));
+    assert!(check_is_movable(&config, Some(true), Some(false)));
+
+    // the hook takes precedence over annotations
+    config.set_movable_types_hook(|path| {
+        Ok(if path.to_cpp_pseudo_code() == "C" {
+            MovableTypesHookOutput::Movable
+        } else {
+            MovableTypesHookOutput::Unknown
+        })
+    });
+    assert!(check_is_movable(&config, Some(false), Some(false)));
+
+    config.set_movable_types_hook(|_| Ok(MovableTypesHookOutput::Unknown));
+    assert!(!check_is_movable(&config, Some(false), Some(true)));
+    assert!(check_is_movable(&config, None, Some(true)));
+}
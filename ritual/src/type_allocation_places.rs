@@ -1,11 +1,15 @@
-#![allow(dead_code)]
-
 use crate::config::MovableTypesHookOutput;
+use crate::cpp_checker::{
+    self, LocalSnippetTask, Snippet, SnippetRunner, SnippetTask, SnippetTaskLocalData,
+};
+use crate::cpp_checks::CppChecksItem;
 use crate::cpp_data::{CppItem, CppPath};
 use crate::cpp_type::{CppPointerLikeTypeKind, CppType};
+use crate::database::{DatabaseClient, ItemId};
 use crate::processor::ProcessorData;
-use log::{info, trace};
-use ritual_common::errors::Result;
+use itertools::Itertools;
+use log::{debug, info, trace};
+use ritual_common::errors::{err_msg, Result};
 use std::collections::HashMap;
 
 #[derive(Default, Debug)]
@@ -99,6 +103,196 @@ fn check_type(
 
 const MAX_ITEMS: usize = 10;
 
+/// Returns the decision of `movable_types_hook` about the class `path`
+/// declared by the C++ item `id`. If the hook is not set or doesn't make a decision,
+/// `ritual:movable` and `ritual:immovable` annotations of the class are used.
+pub fn movable_types_decision(
+    data: &ProcessorData<'_>,
+    id: &ItemId,
+    path: &CppPath,
+) -> Result<MovableTypesHookOutput> {
+    if let Some(hook) = data.config.movable_types_hook() {
+        let output = hook(path)?;
+        if output != MovableTypesHookOutput::Unknown {
+            return Ok(output);
+        }
+    }
+    let is_movable = data
+        .db
+        .find_annotations_for(id)?
+        .and_then(|annotations| annotations.is_movable);
+    Ok(match is_movable {
+        Some(true) => MovableTypesHookOutput::Movable,
+        Some(false) => MovableTypesHookOutput::Immovable,
+        None => MovableTypesHookOutput::Unknown,
+    })
+}
+
+/// Returns true if the class `path` declared by the C++ item `id` is movable.
+///
+/// `movable_types_hook` and annotations take precedence. Otherwise, the class is movable
+/// if its checks performed by `set_allocation_places` succeeded in all environments.
+/// Classes of the current crate are only checked if `Config::detect_movable_types` is enabled.
+pub fn is_movable(data: &ProcessorData<'_>, id: &ItemId, path: &CppPath) -> Result<bool> {
+    Ok(match movable_types_decision(data, id, path)? {
+        MovableTypesHookOutput::Movable => true,
+        MovableTypesHookOutput::Immovable => false,
+        MovableTypesHookOutput::Unknown => {
+            // dependencies only have checks if detection was enabled for them
+            let is_detection_enabled =
+                data.config.detect_movable_types() || id.crate_name() != data.db.crate_name();
+            is_detection_enabled && is_movable_by_checks(data.db, id)?
+        }
+    })
+}
+
+fn is_movable_by_checks(db: &DatabaseClient, id: &ItemId) -> Result<bool> {
+    let checks = db.movable_type_checks(id)?;
+    Ok(!checks.is_empty() && checks.all_success(db.environments()))
+}
+
+/// Returns paths of all known movable classes, including classes of dependencies.
+pub fn movable_types(data: &ProcessorData<'_>) -> Result<Vec<CppPath>> {
+    let mut result = Vec::new();
+    for item in data.db.all_cpp_items() {
+        if let Some(type1) = item.item.as_type_ref() {
+            if type1.kind.is_class() && is_movable(data, &item.id, &type1.path)? {
+                result.push(type1.path.clone());
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Returns code that only compiles if values of the class `path`
+/// can be moved in memory and returned by value.
+pub fn movable_type_snippet(path: &CppPath) -> Result<Snippet> {
+    let code = path.to_cpp_code()?;
+    let code = [
+        "std::is_trivially_copyable",
+        "std::is_nothrow_move_constructible",
+        "std::is_copy_constructible",
+    ]
+    .iter()
+    .map(|trait_name| {
+        format!(
+            "static_assert({}<{}>::value, \"{}\");",
+            trait_name, code, trait_name
+        )
+    })
+    .join("\n");
+    Ok(Snippet::new_in_main(code, false))
+}
+
+/// Detects movable classes by checking their type traits in all environments.
+///
+/// A class is considered movable if it's trivially copyable (so that it can be moved
+/// in memory like a Rust value), nothrow move constructible and copy constructible
+/// (so that it can be returned by value from the FFI functions).
+/// Classes decided by `movable_types_hook` or annotations are not checked.
+/// The results are saved as movable type checks items of the class items.
+/// Does nothing unless `Config::detect_movable_types` is enabled.
+pub fn set_allocation_places(data: &mut ProcessorData<'_>) -> Result<()> {
+    if !data.config.detect_movable_types() {
+        return Ok(());
+    }
+    let environments = cpp_checker::environments(data);
+    for env in &environments {
+        data.db.add_environment(env.clone());
+    }
+    let crate_name = data.db.crate_name().to_string();
+
+    let mut snippets = Vec::new();
+    for item in data.db.cpp_items() {
+        let type1 = match item.item.as_type_ref() {
+            Some(type1) => type1,
+            None => continue,
+        };
+        if !type1.kind.is_class()
+            || CppType::Class(type1.path.clone()).is_or_contains_template_parameter()
+        {
+            continue;
+        }
+        if movable_types_decision(data, &item.id, &type1.path)? != MovableTypesHookOutput::Unknown {
+            continue;
+        }
+        let checks = data.db.movable_type_checks(&item.id)?;
+        if checks.has_all_envs(&environments) {
+            continue;
+        }
+        let snippet = match movable_type_snippet(&type1.path) {
+            Ok(snippet) => snippet,
+            Err(err) => {
+                debug!(
+                    "can't create snippet: {}: {:?}",
+                    type1.path.to_cpp_pseudo_code(),
+                    err
+                );
+                continue;
+            }
+        };
+        for env in &environments {
+            if checks.has_env(env) {
+                continue;
+            }
+            snippets.push(SnippetTask {
+                snippet: snippet.clone(),
+                output: None,
+                data: SnippetTaskLocalData {
+                    item_id: item.id.clone(),
+                    crate_name: crate_name.clone(),
+                    library_target: env.clone(),
+                },
+            });
+        }
+    }
+    if snippets.is_empty() {
+        return Ok(());
+    }
+
+    info!("Checking {} classes for movability", snippets.len());
+    let mut runner = SnippetRunner::new(data)?;
+    runner.check(&mut snippets)?;
+    save_results(data, snippets)
+}
+
+fn save_results(data: &mut ProcessorData<'_>, snippets: Vec<LocalSnippetTask>) -> Result<()> {
+    let mut movable_count = 0;
+    let mut immovable_count = 0;
+    for snippet in snippets {
+        let output = snippet
+            .output
+            .ok_or_else(|| err_msg("no output for movable type check"))?;
+        let path = data
+            .db
+            .cpp_item(&snippet.data.item_id)?
+            .item
+            .path()
+            .ok_or_else(|| err_msg("movable type check of an item without path"))?
+            .to_cpp_pseudo_code();
+        if output.is_success() {
+            debug!("movable: {}", path);
+            movable_count += 1;
+        } else {
+            debug!("immovable: {}: {:?}", path, output);
+            immovable_count += 1;
+        }
+        data.db.add_movable_type_checks_item(
+            snippet.data.item_id,
+            CppChecksItem {
+                env: snippet.data.library_target,
+                is_success: output.is_success(),
+                is_predicted: false,
+            },
+        );
+    }
+    info!(
+        "Movable: {} classes; immovable: {} classes",
+        movable_count, immovable_count
+    );
+    Ok(())
+}
+
 /// Detects the preferred type allocation place for each type based on
 /// API of all known methods. Doesn't actually change the data,
 /// only suggests stack allocated types for manual configuration.
@@ -113,10 +307,10 @@ pub fn suggest_allocation_places(data: &mut ProcessorData<'_>) -> Result<()> {
             if !type1.kind.is_class() {
                 continue;
             }
-            if let Some(hook) = data.config.movable_types_hook() {
-                if hook(&type1.path)? != MovableTypesHookOutput::Unknown {
-                    continue;
-                }
+            if movable_types_decision(data, &item.id, &type1.path)?
+                != MovableTypesHookOutput::Unknown
+            {
+                continue;
             }
             let good_path = type1.path.deinstantiate();
             data_map.insert(good_path, Default::default());
@@ -10,6 +10,9 @@
 // placement new statements require this
 #include <new>
 
+// for abort() in overriders of pure virtual methods
+#include <cstdlib>
+
 // original C++ library includes generated by cpp_to_rust
 {include_directives_code}
 
@@ -0,0 +1,21 @@
+// Class generated by ritual.
+// See the template at "ritual/templates/c_lib/overrider.h".
+class {class_name} : public {base_name} {{
+public:
+    {class_name}() : m_data(nullptr), m_delete(nullptr) {{ }}
+    {class_name}(const {class_name}&) = delete;
+    {class_name}& operator=(const {class_name}&) = delete;
+    ~{class_name}() {{
+        if (m_delete) {{
+            m_delete(m_data);
+        }}
+    }}
+    void set_data(void* data, void (*deleter)(void*)) {{
+        m_data = data;
+        m_delete = deleter;
+    }}
+{methods}
+private:
+    void* m_data;
+    void (*m_delete)(void*);
+{fields}}};
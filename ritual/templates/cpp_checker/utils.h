@@ -14,6 +14,9 @@
 // placement new statements require this
 #include <new>
 
+// for movable types detection
+#include <type_traits>
+
 // for exit()
 #include <cstdlib>
 
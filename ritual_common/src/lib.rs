@@ -30,6 +30,10 @@ pub struct BuildScriptData {
     pub cpp_wrapper_lib_name: String,
     /// Environments the generator was used in
     pub known_targets: Vec<LibraryTarget>,
+    /// True if the crate calls functions of a C library directly,
+    /// so there is no wrapper library to build
+    #[serde(default)]
+    pub is_c_library: bool,
 }
 
 #[derive(Debug)]